@@ -32,6 +32,9 @@ import httpx
 DEEPSEEK_API_KEY = os.environ.get("LLM_API_KEY") or os.environ.get("DEEPSEEK_API_KEY", "")
 DEEPSEEK_BASE_URL = os.environ.get("LLM_BASE_URL") or os.environ.get("DEEPSEEK_BASE_URL", "https://api.deepseek.com/v1")
 DEEPSEEK_MODEL = os.environ.get("LLM_MODEL") or os.environ.get("DEEPSEEK_MODEL", "deepseek-chat")
+# "azure" sends the key as an `api-key` header instead of a bearer token
+LLM_AUTH_STYLE = os.environ.get("LLM_AUTH_STYLE", "bearer")
+LLM_API_VERSION = os.environ.get("LLM_API_VERSION", "")
 
 SYSTEM_PROMPT = os.environ.get("SYSTEM_PROMPT", (
     "You are CoPaw Assistant, a helpful AI assistant running on AgentScope Runtime. "
@@ -43,6 +46,13 @@ HOST = os.environ.get("COPAW_HOST", "0.0.0.0")
 
 app = FastAPI(title="CoPaw Runtime", version="0.1.0")
 
+
+def _auth_headers() -> dict:
+    if LLM_AUTH_STYLE == "azure":
+        return {"api-key": DEEPSEEK_API_KEY, "Content-Type": "application/json"}
+    return {"Authorization": f"Bearer {DEEPSEEK_API_KEY}", "Content-Type": "application/json"}
+
+
 # ── Session memory (simple in-memory history) ──
 
 sessions: dict[str, list[dict]] = {}
@@ -87,10 +97,8 @@ async def stream_deepseek(
         async with client.stream(
             "POST",
             f"{DEEPSEEK_BASE_URL}/chat/completions",
-            headers={
-                "Authorization": f"Bearer {DEEPSEEK_API_KEY}",
-                "Content-Type": "application/json",
-            },
+            headers=_auth_headers(),
+            params={"api-version": LLM_API_VERSION} if LLM_API_VERSION else None,
             json={
                 "model": DEEPSEEK_MODEL,
                 "messages": api_messages,
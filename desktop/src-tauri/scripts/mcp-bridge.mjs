@@ -10,14 +10,21 @@
  *
  * Config file format (JSON array):
  *   [{ "name": "filesystem", "command": "npx", "args": ["-y", "@modelcontextprotocol/server-filesystem", "/tmp"], "env": {} }]
+ * Servers with `"enabled": false` are listed but not started.
  *
  * HTTP Endpoints:
  *   GET  /tools      — List all discovered tools from all servers
+ *   GET  /servers    — Per-server connection status: { name, connected, enabled, error, toolCount }
  *   POST /call       — Call a specific tool: { server, tool, arguments }
- *   POST /shutdown   — Graceful shutdown
+ *   POST /servers/refresh — Re-read one server from config and connect or drop it: { name }
+ *   POST /shutdown   — Close every MCP server, reply { ok, closed: [names] }, then exit
+ *
+ * When MCP_BRIDGE_TOKEN is set, every request must carry
+ * `Authorization: Bearer <token>`.
  */
 
 import { createServer } from 'http';
+import { timingSafeEqual } from 'crypto';
 import { readFileSync, existsSync } from 'fs';
 import { resolve } from 'path';
 import { homedir } from 'os';
@@ -28,10 +35,14 @@ import { StdioClientTransport } from '@modelcontextprotocol/sdk/client/stdio.js'
 
 const DEFAULT_CONFIG_PATH = resolve(homedir(), '.agentos', 'mcp-config.json');
 const configPath = process.argv[2] || DEFAULT_CONFIG_PATH;
+const authToken = process.env.MCP_BRIDGE_TOKEN || '';
 
 // ── MCP Connection Manager ──
 
 const connections = new Map(); // name → { client, transport, tools }
+const failures = new Map(); // name → error message for servers that failed to connect
+const configuredServers = []; // names in config order
+const disabled = new Set(); // names with "enabled": false
 
 async function connectServer(config) {
   const { name, command, args = [], env } = config;
@@ -60,11 +71,53 @@ async function connectServer(config) {
   }));
 
   connections.set(name, { client, transport, tools, config });
+  failures.delete(name);
   console.error(`[MCP Bridge] "${name}" connected: ${tools.length} tools`);
 
   return tools;
 }
 
+async function disconnectServer(name) {
+  const conn = connections.get(name);
+  if (!conn) return;
+  connections.delete(name);
+  try {
+    await conn.client.close();
+    console.error(`[MCP Bridge] Closed "${name}"`);
+  } catch (err) {
+    console.error(`[MCP Bridge] Error closing "${name}":`, err.message);
+  }
+}
+
+function readConfigs() {
+  return existsSync(configPath) ? JSON.parse(readFileSync(configPath, 'utf-8')) : [];
+}
+
+// Apply the current config for one server: reconnect it if enabled, drop it if not
+async function refreshServer(name) {
+  const config = readConfigs().find((c) => c.name === name);
+  await disconnectServer(name);
+  failures.delete(name);
+  disabled.delete(name);
+
+  if (!config) {
+    const index = configuredServers.indexOf(name);
+    if (index !== -1) configuredServers.splice(index, 1);
+    return;
+  }
+  if (!configuredServers.includes(name)) configuredServers.push(name);
+  if (config.enabled === false) {
+    disabled.add(name);
+    return;
+  }
+  try {
+    await connectServer(config);
+  } catch (err) {
+    console.error(`[MCP Bridge] Failed to connect "${name}":`, err.message);
+    failures.set(name, err.message);
+  }
+}
+
 async function callTool(serverName, toolName, args) {
   const conn = connections.get(serverName);
   if (!conn) throw new Error(`Server "${serverName}" not connected`);
@@ -81,15 +134,18 @@ async function callTool(serverName, toolName, args) {
 }
 
 async function shutdown() {
+  const closed = [];
   for (const [name, conn] of connections) {
     try {
       await conn.client.close();
+      closed.push(name);
       console.error(`[MCP Bridge] Closed "${name}"`);
     } catch (err) {
       console.error(`[MCP Bridge] Error closing "${name}":`, err.message);
     }
   }
   connections.clear();
+  return closed;
 }
 
 // ── HTTP Server ──
@@ -102,6 +158,19 @@ function getAllTools() {
   return allTools;
 }
 
+function getServerStatus() {
+  return configuredServers.map((name) => {
+    const conn = connections.get(name);
+    return {
+      name,
+      connected: !!conn,
+      enabled: !disabled.has(name),
+      error: conn ? null : (disabled.has(name) ? 'Disabled' : (failures.get(name) || 'Not connected')),
+      toolCount: conn ? conn.tools.length : 0,
+    };
+  });
+}
+
 function parseBody(req) {
   return new Promise((resolve, reject) => {
     let data = '';
@@ -122,13 +191,37 @@ function sendJson(res, status, data) {
   res.end(JSON.stringify(data));
 }
 
+function isAuthorized(req) {
+  if (!authToken) return true;
+  const expected = Buffer.from(`Bearer ${authToken}`);
+  const actual = Buffer.from(req.headers['authorization'] || '');
+  return actual.length === expected.length && timingSafeEqual(actual, expected);
+}
+
 const httpServer = createServer(async (req, res) => {
   const url = new URL(req.url, `http://localhost`);
 
+  if (!isAuthorized(req)) {
+    sendJson(res, 401, { error: 'Unauthorized' });
+    return;
+  }
+
   try {
     if (req.method === 'GET' && url.pathname === '/tools') {
       sendJson(res, 200, { tools: getAllTools() });
 
+    } else if (req.method === 'GET' && url.pathname === '/servers') {
+      sendJson(res, 200, { servers: getServerStatus() });
+
+    } else if (req.method === 'POST' && url.pathname === '/servers/refresh') {
+      const { name } = await parseBody(req);
+      if (!name) {
+        sendJson(res, 400, { error: 'Missing "name" in body' });
+        return;
+      }
+      await refreshServer(name);
+      sendJson(res, 200, { servers: getServerStatus(), tools: getAllTools() });
+
     } else if (req.method === 'POST' && url.pathname === '/call') {
       const body = await parseBody(req);
       const { server, tool, arguments: args } = body;
@@ -142,9 +235,10 @@ const httpServer = createServer(async (req, res) => {
       sendJson(res, 200, { result });
 
     } else if (req.method === 'POST' && url.pathname === '/shutdown') {
-      sendJson(res, 200, { ok: true });
-      await shutdown();
-      process.exit(0);
+      // Close the servers first so the caller learns which ones stopped
+      const closed = await shutdown();
+      res.on('finish', () => process.exit(0));
+      sendJson(res, 200, { ok: true, closed });
 
     } else if (req.method === 'GET' && url.pathname === '/health') {
       sendJson(res, 200, {
@@ -163,20 +257,35 @@ const httpServer = createServer(async (req, res) => {
 
 // ── Startup ──
 
+// Print a per-server progress marker to stdout — Tauri streams these to the UI
+function reportServer(info) {
+  console.log(`MCP_BRIDGE_SERVER=${JSON.stringify(info)}`);
+}
+
 async function main() {
   // Load config
   if (!existsSync(configPath)) {
     console.error(`[MCP Bridge] No config file at ${configPath}, starting with no servers`);
   } else {
     try {
-      const configs = JSON.parse(readFileSync(configPath, 'utf-8'));
+      const configs = readConfigs();
       console.error(`[MCP Bridge] Loading ${configs.length} server(s) from ${configPath}`);
 
       for (const config of configs) {
+        configuredServers.push(config.name);
+        if (config.enabled === false) {
+          disabled.add(config.name);
+          console.error(`[MCP Bridge] Skipping disabled server "${config.name}"`);
+          reportServer({ name: config.name, connected: false, error: 'Disabled', tools: [] });
+          continue;
+        }
         try {
-          await connectServer(config);
+          const tools = await connectServer(config);
+          reportServer({ name: config.name, connected: true, tools });
         } catch (err) {
           console.error(`[MCP Bridge] Failed to connect "${config.name}":`, err.message);
+          failures.set(config.name, err.message);
+          reportServer({ name: config.name, connected: false, error: err.message, tools: [] });
         }
       }
     } catch (err) {
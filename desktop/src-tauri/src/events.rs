@@ -0,0 +1,189 @@
+//! Typed events forwarded from the WebSocket read loop to the frontend.
+//!
+//! Every event is serialized as `{ "type": "...", "payload": { ... } }`, the
+//! same shape the frontend already consumes, but the payload fields are now
+//! declared here instead of being assembled ad hoc with `json!`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ChatChunk {
+    pub conversation_id: String,
+    pub delta: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ChatDone {
+    pub conversation_id: String,
+    pub full_content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skills_invoked: Option<Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SkillStart {
+    pub conversation_id: String,
+    pub skill_name: String,
+    pub description: String,
+    /// Shared with the matching `skill.result`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invocation_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SkillResult {
+    pub conversation_id: String,
+    pub skill_name: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invocation_id: Option<String>,
+    /// Time since the matching `skill.start`, measured by this client.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elapsed_ms: Option<u64>,
+}
+
+/// Live output of a running skill, between its `skill.start` and `skill.result`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SkillProgress {
+    pub conversation_id: String,
+    pub skill_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invocation_id: Option<String>,
+    /// Plain output, e.g. terminal text.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// Structured progress, e.g. one Claude Code turn.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event: Option<Value>,
+}
+
+impl SkillStart {
+    /// Key pairing this start with its result; servers that send no
+    /// invocation id fall back to conversation + skill name.
+    pub fn correlation_key(&self) -> String {
+        self.invocation_id
+            .clone()
+            .unwrap_or_else(|| format!("{}:{}", self.conversation_id, self.skill_name))
+    }
+}
+
+impl SkillResult {
+    pub fn correlation_key(&self) -> String {
+        self.invocation_id
+            .clone()
+            .unwrap_or_else(|| format!("{}:{}", self.conversation_id, self.skill_name))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PushMessage {
+    pub content: String,
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ServerError {
+    pub code: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Disconnected {
+    pub reason: String,
+}
+
+/// A server message whose type this client does not handle natively.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawEvent {
+    pub message_type: String,
+    pub payload: Value,
+}
+
+/// A server message of a type this client predates, forwarded so the
+/// frontend can handle newer server features without a desktop update.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnknownMessage {
+    pub original_type: String,
+    pub payload: Value,
+}
+
+/// Event sent over the IPC channel to the frontend.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "payload")]
+pub enum ClientEvent {
+    #[serde(rename = "chat.chunk")]
+    ChatChunk(ChatChunk),
+    #[serde(rename = "chat.done")]
+    ChatDone(ChatDone),
+    #[serde(rename = "skill.start")]
+    SkillStart(SkillStart),
+    #[serde(rename = "skill.result")]
+    SkillResult(SkillResult),
+    #[serde(rename = "skill.progress")]
+    SkillProgress(SkillProgress),
+    #[serde(rename = "push.message")]
+    PushMessage(PushMessage),
+    /// Skill list payloads are passed through untouched; their shape is owned by the server.
+    #[serde(rename = "skill.list.response")]
+    SkillListResponse(Value),
+    #[serde(rename = "skill.library.response")]
+    SkillLibraryResponse(Value),
+    #[serde(rename = "skill.config.response")]
+    SkillConfigResponse(Value),
+    #[serde(rename = "error")]
+    Error(ServerError),
+    #[serde(rename = "disconnected")]
+    Disconnected(Disconnected),
+    /// Forwarded only when raw event subscription is enabled.
+    #[serde(rename = "raw")]
+    Raw(RawEvent),
+    /// Forwarded unless disabled with `set_forward_unknown_messages`.
+    #[serde(rename = "unknown")]
+    Unknown(UnknownMessage),
+}
+
+impl ClientEvent {
+    /// Build a typed event from a server message type and its raw payload.
+    /// Returns None for message types that are not forwarded to the frontend.
+    pub fn from_server(msg_type: &str, payload: &Value) -> Option<Self> {
+        fn typed<T: serde::de::DeserializeOwned + Default>(payload: &Value) -> T {
+            serde_json::from_value(payload.clone()).unwrap_or_default()
+        }
+
+        Some(match msg_type {
+            "chat.chunk" => ClientEvent::ChatChunk(typed(payload)),
+            "chat.done" => ClientEvent::ChatDone(typed(payload)),
+            "skill.start" => ClientEvent::SkillStart(typed(payload)),
+            "skill.result" => ClientEvent::SkillResult(typed(payload)),
+            "skill.progress" => ClientEvent::SkillProgress(typed(payload)),
+            "push.message" => ClientEvent::PushMessage(typed(payload)),
+            "skill.list.response" => ClientEvent::SkillListResponse(payload.clone()),
+            "skill.library.response" => ClientEvent::SkillLibraryResponse(payload.clone()),
+            "skill.config.response" => ClientEvent::SkillConfigResponse(payload.clone()),
+            "error" => ClientEvent::Error(typed(payload)),
+            _ => return None,
+        })
+    }
+
+    pub fn disconnected(reason: impl Into<String>) -> Self {
+        ClientEvent::Disconnected(Disconnected { reason: reason.into() })
+    }
+}
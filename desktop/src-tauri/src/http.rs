@@ -0,0 +1,112 @@
+//! Outbound HTTP configuration. The optional proxy from settings is applied
+//! to reqwest clients and exported to spawned network tools (npm, pip, clawhub);
+//! the configured User-Agent applies to the shared client, and the default
+//! headers only to `http_fetch` requests for the hosts they are scoped to.
+
+use std::sync::Mutex as StdMutex;
+use std::time::Duration;
+
+/// Hosts that never go through the proxy: the local gateway, CoPaw and MCP bridge.
+const NO_PROXY_HOSTS: &str = "localhost,127.0.0.1,::1";
+
+/// Defaults for the shared client; individual requests may set a shorter timeout.
+const CONNECT_TIMEOUT_SECS: u64 = 10;
+const REQUEST_TIMEOUT_SECS: u64 = 60;
+const POOL_IDLE_TIMEOUT_SECS: u64 = 90;
+
+/// Shared client, built on first use and rebuilt after the proxy changes.
+static CLIENT: StdMutex<Option<reqwest::Client>> = StdMutex::new(None);
+
+/// The shared HTTP client. Reusing it keeps connections alive across repeated
+/// health checks and MCP calls. Cloning is cheap (the pool is reference counted).
+pub fn client() -> reqwest::Client {
+    CLIENT.lock().unwrap().get_or_insert_with(build_client).clone()
+}
+
+/// Drop the shared client so the next `client()` picks up new settings.
+pub fn reset_client() {
+    *CLIENT.lock().unwrap() = None;
+}
+
+/// Parse configured header pairs, skipping (and logging) invalid ones.
+pub fn header_map(headers: &std::collections::HashMap<String, String>) -> reqwest::header::HeaderMap {
+    let mut map = reqwest::header::HeaderMap::new();
+    for (name, value) in headers {
+        match (
+            reqwest::header::HeaderName::from_bytes(name.as_bytes()),
+            reqwest::header::HeaderValue::from_str(value),
+        ) {
+            (Ok(name), Ok(value)) => {
+                map.insert(name, value);
+            }
+            _ => println!("[Http] Ignoring invalid header {}", name),
+        }
+    }
+    map
+}
+
+/// The configured default headers if `url`'s host is one of the hosts they
+/// are scoped to (or a subdomain of one), otherwise none. Keeps auth-style
+/// headers from leaking to arbitrary hosts.
+pub fn default_headers_for(url: &str) -> reqwest::header::HeaderMap {
+    let host = match reqwest::Url::parse(url).ok().and_then(|u| u.host_str().map(|h| h.to_lowercase())) {
+        Some(host) => host,
+        None => return reqwest::header::HeaderMap::new(),
+    };
+    let settings = crate::settings::get();
+    let scoped = settings.default_header_hosts.iter().any(|allowed| {
+        let allowed = allowed.trim().to_lowercase();
+        !allowed.is_empty() && (host == allowed || host.ends_with(&format!(".{}", allowed)))
+    });
+    if scoped {
+        header_map(&settings.default_headers)
+    } else {
+        reqwest::header::HeaderMap::new()
+    }
+}
+
+/// Check a header name/value pair without building a request.
+pub fn validate_header(name: &str, value: &str) -> Result<(), String> {
+    reqwest::header::HeaderName::from_bytes(name.as_bytes())
+        .map_err(|_| format!("Invalid header name '{}'", name))?;
+    reqwest::header::HeaderValue::from_str(value)
+        .map_err(|_| format!("Invalid value for header '{}'", name))?;
+    Ok(())
+}
+
+/// Build a client honoring the configured proxy and User-Agent. Loopback
+/// requests bypass the proxy.
+fn build_client() -> reqwest::Client {
+    let mut builder = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(CONNECT_TIMEOUT_SECS))
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .pool_idle_timeout(Duration::from_secs(POOL_IDLE_TIMEOUT_SECS));
+    if let Some(ua) = crate::settings::user_agent() {
+        builder = builder.user_agent(ua);
+    }
+    if let Some(url) = crate::settings::http_proxy() {
+        match reqwest::Proxy::all(&url) {
+            Ok(proxy) => {
+                builder = builder.proxy(proxy.no_proxy(reqwest::NoProxy::from_string(NO_PROXY_HOSTS)));
+            }
+            Err(e) => println!("[Http] Ignoring invalid proxy {}: {}", url, e),
+        }
+    }
+    builder.build().unwrap_or_default()
+}
+
+/// Proxy variables to set on spawned processes, empty when no proxy is configured.
+/// Both cases are set since npm, pip and node tools disagree on which they read.
+pub fn proxy_envs() -> Vec<(&'static str, String)> {
+    match crate::settings::http_proxy() {
+        Some(url) => vec![
+            ("HTTP_PROXY", url.clone()),
+            ("HTTPS_PROXY", url.clone()),
+            ("http_proxy", url.clone()),
+            ("https_proxy", url),
+            ("NO_PROXY", NO_PROXY_HOSTS.to_string()),
+            ("no_proxy", NO_PROXY_HOSTS.to_string()),
+        ],
+        None => vec![],
+    }
+}
@@ -1,23 +1,34 @@
 mod ws_client;
+mod events;
+mod http;
+mod notifications;
 mod process_manager;
+mod settings;
 mod skill_executor;
+mod streams;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tauri::{
-    ipc::Channel, Manager,
+    ipc::{Channel, JavaScriptChannelId}, Emitter, Manager,
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     menu::{MenuBuilder, MenuItemBuilder},
 };
 use tokio::sync::Mutex;
 use ws_client::{WsClient, ConnectResult};
-use process_manager::ProcessManager;
+use events::ClientEvent;
+use process_manager::{ProcessManager, ProcessPriority};
 
 struct AppState {
     ws_client: Arc<Mutex<WsClient>>,
     process_manager: Arc<Mutex<ProcessManager>>,
+    /// Signalled by `cancel_clawhub_operation` to abort in-flight clawhub/npm commands.
+    clawhub_cancel: Arc<tokio::sync::Notify>,
+    /// Set by `cancel_install` to abort a running install.
+    install_cancel: Arc<InstallCancel>,
 }
 
 // ── Tauri Commands ──
@@ -35,18 +46,84 @@ async fn connect_server(
     agent_url: Option<String>,
     agent_token: Option<String>,
     agent_protocol: Option<String>,
-    on_event: Channel<Value>,
+    connect_timeout_secs: Option<u64>,
+    on_event: Channel<ClientEvent>,
 ) -> Result<ConnectResult, String> {
     println!("[Tauri] connect_server called (mode: {})", mode);
     let mut client = state.ws_client.lock().await;
     let result = client
-        .connect(&url, &mode, auth_token, api_key, model, copaw_url, copaw_token, agent_url, agent_token, agent_protocol, on_event)
+        .connect(
+            &url, &mode, auth_token, api_key, model, copaw_url, copaw_token,
+            agent_url, agent_token, agent_protocol, connect_timeout_secs, on_event,
+        )
         .await
         .map_err(|e| e.to_string());
     println!("[Tauri] connect_server result: {:?}", result);
     result
 }
 
+/// One server to try in `connect_server_with_fallback`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConnectCandidate {
+    url: String,
+    mode: String,
+    auth_token: Option<String>,
+    api_key: Option<String>,
+    model: Option<String>,
+    copaw_url: Option<String>,
+    copaw_token: Option<String>,
+    agent_url: Option<String>,
+    agent_token: Option<String>,
+    agent_protocol: Option<String>,
+}
+
+/// Per-candidate handshake timeout when none is given.
+const CONNECT_CANDIDATE_TIMEOUT_SECS: u64 = 20;
+
+/// Try each candidate in order (e.g. local gateway first, hosted second) and
+/// keep the first that completes the handshake. Failed attempts are fully
+/// disconnected before moving on.
+#[tauri::command]
+async fn connect_server_with_fallback(
+    state: tauri::State<'_, AppState>,
+    candidates: Vec<ConnectCandidate>,
+    timeout_secs: Option<u64>,
+    on_event: Channel<ClientEvent>,
+) -> Result<ConnectResult, String> {
+    if candidates.is_empty() {
+        return Err("No connection candidates given".to_string());
+    }
+    let timeout = std::time::Duration::from_secs(timeout_secs.unwrap_or(CONNECT_CANDIDATE_TIMEOUT_SECS));
+    let mut client = state.ws_client.lock().await;
+    let mut failures = Vec::new();
+
+    for (index, c) in candidates.into_iter().enumerate() {
+        println!("[Tauri] connect_server_with_fallback: trying #{} {} (mode: {})", index, c.url, c.mode);
+        let attempt = tokio::time::timeout(
+            timeout,
+            client.connect(
+                &c.url, &c.mode, c.auth_token, c.api_key, c.model, c.copaw_url, c.copaw_token,
+                c.agent_url, c.agent_token, c.agent_protocol, Some(timeout.as_secs()), on_event.clone(),
+            ),
+        )
+        .await;
+        match attempt {
+            Ok(Ok(mut result)) => {
+                println!("[Tauri] connect_server_with_fallback: connected via #{} {}", index, c.url);
+                result.candidate_index = Some(index);
+                return Ok(result);
+            }
+            Ok(Err(e)) => failures.push(format!("{}: {}", c.url, e)),
+            Err(_) => failures.push(format!("{}: timed out after {}s", c.url, timeout.as_secs())),
+        }
+        // Make sure nothing from the failed attempt lingers
+        client.disconnect().await;
+    }
+
+    Err(format!("All connection candidates failed: {}", failures.join("; ")))
+}
+
 #[tauri::command]
 async fn disconnect_server(state: tauri::State<'_, AppState>) -> Result<(), String> {
     println!("[Tauri] disconnect_server called");
@@ -57,16 +134,53 @@ async fn disconnect_server(state: tauri::State<'_, AppState>) -> Result<(), Stri
     Ok(())
 }
 
+#[derive(Serialize)]
+struct PingResult {
+    rtt_ms: u64,
+    avg_rtt_ms: f64,
+}
+
+/// Timeout for a single `ping_server` round trip.
+const PING_TIMEOUT_SECS: u64 = 10;
+
+#[tauri::command]
+async fn ping_server(state: tauri::State<'_, AppState>) -> Result<PingResult, String> {
+    let (ping_id, rx, started) = {
+        let client = state.ws_client.lock().await;
+        // Start timing before the send: the pong can arrive before send_ping returns
+        let started = std::time::Instant::now();
+        let (ping_id, rx) = client.send_ping().await.map_err(|e| e.to_string())?;
+        (ping_id, rx, started)
+    };
+
+    // Wait without holding the client lock so other commands keep flowing
+    let outcome = tokio::time::timeout(std::time::Duration::from_secs(PING_TIMEOUT_SECS), rx).await;
+    let rtt_ms = started.elapsed().as_millis() as u64;
+
+    let mut client = state.ws_client.lock().await;
+    match outcome {
+        Ok(Ok(())) => Ok(PingResult { rtt_ms, avg_rtt_ms: client.record_latency(rtt_ms) }),
+        Ok(Err(_)) => Err("Connection closed before pong was received".to_string()),
+        Err(_) => {
+            client.forget_ping(&ping_id);
+            Err(format!("No pong within {}s", PING_TIMEOUT_SECS))
+        }
+    }
+}
+
+/// Send a chat message. Fails with "Generation in progress" while the same
+/// conversation is still streaming; pass `force` to send anyway.
 #[tauri::command]
 async fn send_message(
     state: tauri::State<'_, AppState>,
     conversation_id: String,
     content: String,
     history: Vec<ChatMessage>,
+    force: Option<bool>,
 ) -> Result<(), String> {
     let client = state.ws_client.lock().await;
     client
-        .send_chat(&conversation_id, &content, &history)
+        .send_chat(&conversation_id, &content, &history, force.unwrap_or(false))
         .await
         .map_err(|e| e.to_string())
 }
@@ -77,12 +191,83 @@ async fn stop_generation(state: tauri::State<'_, AppState>) -> Result<(), String
     client.stop_chat().await.map_err(|e| e.to_string())
 }
 
+/// Set the max WebSocket message size in bytes that is parsed; larger
+/// messages are skipped (applies on next connect).
+#[tauri::command]
+async fn set_ws_max_message_size(state: tauri::State<'_, AppState>, bytes: usize) -> Result<(), String> {
+    if bytes < 64 * 1024 {
+        return Err("Max message size must be at least 64 KiB".to_string());
+    }
+    let mut client = state.ws_client.lock().await;
+    client.set_max_message_size(bytes);
+    Ok(())
+}
+
 #[tauri::command]
 async fn get_connection_status(state: tauri::State<'_, AppState>) -> Result<bool, String> {
     let client = state.ws_client.lock().await;
     Ok(client.is_connected())
 }
 
+/// Cancel an in-flight `desktop.command` by id. Returns false if it was not running.
+#[tauri::command]
+async fn cancel_command(state: tauri::State<'_, AppState>, command_id: String) -> Result<bool, String> {
+    let client = state.ws_client.lock().await;
+    Ok(client.cancel_command(&command_id).await)
+}
+
+/// Send a message of any type to the server (for protocol additions not yet wrapped here).
+#[tauri::command]
+async fn send_raw_message(
+    state: tauri::State<'_, AppState>,
+    r#type: String,
+    payload: Value,
+) -> Result<(), String> {
+    if r#type.is_empty() || r#type == "connect" {
+        return Err("Invalid message type".to_string());
+    }
+    let client = state.ws_client.lock().await;
+    client
+        .send_raw(&r#type, &payload)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Enable or disable forwarding of unhandled server message types as `raw` events.
+#[tauri::command]
+async fn subscribe_raw_events(state: tauri::State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    let client = state.ws_client.lock().await;
+    client.set_forward_raw_events(enabled);
+    Ok(())
+}
+
+/// Set the directory relative paths passed to file skills (`read_file`,
+/// `write_file`, ...) resolve against; paths outside it are then refused.
+/// `None` restores the home directory with no restriction.
+#[tauri::command]
+fn set_file_skill_base_dir(path: Option<String>) -> Result<(), String> {
+    let path = match path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty()) {
+        Some(p) => {
+            let expanded = skill_executor::expand_path_from_home(&p).map_err(|e| e.to_string())?;
+            if !std::path::Path::new(&expanded).is_dir() {
+                return Err(format!("Not a directory: {}", expanded));
+            }
+            Some(expanded)
+        }
+        None => None,
+    };
+    settings::update(|s| s.file_skill_base_dir = path)
+}
+
+/// Enable or disable forwarding of unrecognized server message types as
+/// `unknown` events (on by default).
+#[tauri::command]
+async fn set_forward_unknown_messages(state: tauri::State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    let client = state.ws_client.lock().await;
+    client.set_forward_unknown_messages(enabled);
+    Ok(())
+}
+
 // ── Process Manager commands ──
 
 #[derive(Serialize)]
@@ -98,9 +283,10 @@ async fn launch_agent(
     name: String,
     command: String,
     args: Vec<String>,
+    priority: Option<ProcessPriority>,
 ) -> Result<u32, String> {
     let mut pm = state.process_manager.lock().await;
-    pm.spawn(&name, &command, &args).map_err(|e| e.to_string())
+    pm.spawn_with_env(&name, &command, &args, None, None, priority).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -123,17 +309,202 @@ async fn list_agents(state: tauri::State<'_, AppState>) -> Result<Vec<AgentStatu
         .collect())
 }
 
+/// Recent log lines of a managed process. `stream` selects "stdout" or
+/// "stderr" alone (without prefixes); the default "both" is the merged view.
 #[tauri::command]
 async fn get_agent_logs(
     state: tauri::State<'_, AppState>,
     name: String,
     lines: Option<usize>,
+    stream: Option<process_manager::LogStream>,
 ) -> Result<Vec<String>, String> {
     let pm = state.process_manager.lock().await;
-    pm.get_logs(&name, lines.unwrap_or(100))
+    pm.get_stream_logs(&name, lines.unwrap_or(100), stream.unwrap_or_default())
         .map_err(|e| e.to_string())
 }
 
+/// Exit code/signal, time and last log lines of the named process's most
+/// recent exit; kept after the process is gone or restarted under a new pid.
+#[tauri::command]
+async fn get_last_exit(
+    state: tauri::State<'_, AppState>,
+    name: String,
+) -> Result<Option<process_manager::ExitRecord>, String> {
+    Ok(state.process_manager.lock().await.last_exit(&name))
+}
+
+#[tauri::command]
+async fn set_log_capacity(state: tauri::State<'_, AppState>, name: String, lines: usize) -> Result<(), String> {
+    let pm = state.process_manager.lock().await;
+    pm.set_log_capacity(&name, lines).map_err(|e| e.to_string())
+}
+
+/// Stop storing a process's output so the log view can be read without it
+/// scrolling. Output is still drained (and discarded) so the process never blocks.
+#[tauri::command]
+async fn pause_log_capture(state: tauri::State<'_, AppState>, name: String) -> Result<(), String> {
+    let pm = state.process_manager.lock().await;
+    pm.set_log_paused(&name, true).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn resume_log_capture(state: tauri::State<'_, AppState>, name: String) -> Result<(), String> {
+    let pm = state.process_manager.lock().await;
+    pm.set_log_paused(&name, false).map_err(|e| e.to_string())
+}
+
+/// Generation of the active combined log stream; bumping it ends older streams.
+static COMBINED_LOG_STREAM: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+/// Push output from all managed processes (gateway, MCP bridge, CoPaw, agents)
+/// to `channel` as `{ source, timestamp, line }`, interleaved in arrival order.
+/// Starting a new stream replaces the previous one.
+#[tauri::command]
+async fn stream_combined_logs(
+    state: tauri::State<'_, AppState>,
+    channel: Channel<process_manager::LogEvent>,
+) -> Result<(), String> {
+    let mut rx = state.process_manager.lock().await.subscribe_logs();
+    let generation = COMBINED_LOG_STREAM.fetch_add(1, Ordering::SeqCst) + 1;
+    let stream = streams::register("combined_logs");
+
+    tauri::async_runtime::spawn(async move {
+        use tokio::sync::broadcast::error::RecvError;
+        loop {
+            let received = tokio::select! {
+                received = rx.recv() => received,
+                _ = stream.closed() => break,
+            };
+            let event = match received {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    println!("[Tauri] combined log stream lagged, skipped {} lines", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            if COMBINED_LOG_STREAM.load(Ordering::SeqCst) != generation || channel.send(event).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(())
+}
+
+#[tauri::command]
+async fn stop_combined_logs() -> Result<(), String> {
+    COMBINED_LOG_STREAM.fetch_add(1, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Long-lived channels currently pushing to the frontend, for spotting
+/// subscriptions that were never closed.
+#[tauri::command]
+fn list_active_streams() -> Vec<streams::StreamInfo> {
+    streams::list()
+}
+
+/// End a stream listed by `list_active_streams`. Closing a `chat_events`
+/// stream disconnects from the server. Returns false for unknown ids.
+#[tauri::command]
+fn close_stream(id: String) -> bool {
+    streams::close(&id)
+}
+
+#[derive(Serialize)]
+struct ShutdownReport {
+    name: String,
+    action: String,
+    result: String,
+}
+
+/// Grace period for managed processes to exit after being asked to terminate.
+const SHUTDOWN_GRACE_SECS: u64 = 3;
+
+/// Set once the managed processes have been shut down for app exit.
+static SHUTDOWN_DONE: AtomicBool = AtomicBool::new(false);
+
+/// Stop all managed processes and sweep the known local agent ports for leftovers.
+async fn shutdown_managed_processes(process_manager: &Arc<Mutex<ProcessManager>>) -> Vec<ShutdownReport> {
+    let pending = process_manager.lock().await.begin_shutdown();
+    let mut report: Vec<ShutdownReport> = pending
+        .finish(std::time::Duration::from_secs(SHUTDOWN_GRACE_SECS))
+        .await
+        .into_iter()
+        .map(|(name, result)| ShutdownReport {
+            name,
+            action: "terminate".to_string(),
+            result: result.to_string(),
+        })
+        .collect();
+
+    let current = settings::get();
+    let mut pm = process_manager.lock().await;
+    for port in [current.openclaw_port, current.copaw_port] {
+        let killed = pm.kill_by_port(port);
+        report.push(ShutdownReport {
+            name: format!("port {}", port),
+            action: "port_sweep".to_string(),
+            result: if killed.is_empty() {
+                "free".to_string()
+            } else {
+                format!("killed {:?}", killed)
+            },
+        });
+    }
+    skill_executor::set_mcp_bridge_port(0);
+    skill_executor::set_mcp_bridge_token(None);
+
+    for entry in &report {
+        println!("[Tauri] shutdown: {} {} -> {}", entry.name, entry.action, entry.result);
+    }
+    report
+}
+
+/// Gracefully stop every managed process and report what happened to each.
+#[tauri::command]
+async fn shutdown_all(state: tauri::State<'_, AppState>) -> Result<Vec<ShutdownReport>, String> {
+    Ok(shutdown_managed_processes(&state.process_manager).await)
+}
+
+/// Shut down managed processes once before the app exits.
+fn shutdown_before_exit(app: &tauri::AppHandle) {
+    if SHUTDOWN_DONE.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let process_manager = app.state::<AppState>().process_manager.clone();
+    tauri::async_runtime::block_on(shutdown_managed_processes(&process_manager));
+}
+
+#[tauri::command]
+async fn clear_agent_logs(state: tauri::State<'_, AppState>, name: String) -> Result<(), String> {
+    let pm = state.process_manager.lock().await;
+    pm.clear_logs(&name).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn clear_all_logs(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let pm = state.process_manager.lock().await;
+    pm.clear_all_logs();
+    Ok(())
+}
+
+#[tauri::command]
+async fn write_agent_stdin(
+    state: tauri::State<'_, AppState>,
+    name: String,
+    data: String,
+) -> Result<(), String> {
+    let mut pm = state.process_manager.lock().await;
+    pm.write_stdin(&name, &data).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn close_agent_stdin(state: tauri::State<'_, AppState>, name: String) -> Result<(), String> {
+    let mut pm = state.process_manager.lock().await;
+    pm.close_stdin(&name).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 async fn request_skill_list(state: tauri::State<'_, AppState>) -> Result<(), String> {
     let client = state.ws_client.lock().await;
@@ -214,10 +585,205 @@ async fn set_skill_config(
         .map_err(|e| e.to_string())
 }
 
+/// Snapshots kept per skill in ~/.agentos/skill-config-history.json.
+const SKILL_CONFIG_HISTORY_LIMIT: usize = 20;
+/// How long to wait for the server's `skill.config.response`.
+const SKILL_CONFIG_TIMEOUT_SECS: u64 = 10;
+
+#[derive(Clone, Serialize, Deserialize)]
+struct SkillConfigSnapshot {
+    id: String,
+    created_at: u64,
+    /// The skill's config without its secret fields.
+    config: Value,
+    /// Secret fields left out of `config`; restoring keeps their current values.
+    #[serde(default)]
+    redacted: Vec<String>,
+}
+
+fn skill_config_history_path() -> Result<std::path::PathBuf, String> {
+    Ok(agentos_home()?.join("skill-config-history.json"))
+}
+
+/// Snapshot history per skill name, oldest first.
+fn load_skill_config_history() -> Result<HashMap<String, Vec<SkillConfigSnapshot>>, String> {
+    let path = skill_config_history_path()?;
+    match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e)),
+        Err(_) => Ok(HashMap::new()),
+    }
+}
+
+/// Write the history readable by the current user only.
+fn save_skill_config_history(history: &HashMap<String, Vec<SkillConfigSnapshot>>) -> Result<(), String> {
+    use std::io::Write;
+
+    let path = skill_config_history_path()?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(history).map_err(|e| e.to_string())?;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // The mode only applies on creation; tighten a file written by older versions
+        if path.exists() {
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+                .map_err(|e| format!("Failed to restrict {}: {}", path.display(), e))?;
+        }
+    }
+    options
+        .open(&path)
+        .and_then(|mut f| f.write_all(json.as_bytes()))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Request a skill's `skill.config.response` payload from the server.
+async fn fetch_skill_config_payload(state: &tauri::State<'_, AppState>, skill_name: &str) -> Result<Value, String> {
+    let rx = {
+        let client = state.ws_client.lock().await;
+        client.fetch_skill_config(skill_name).await.map_err(|e| e.to_string())?
+    };
+    match tokio::time::timeout(std::time::Duration::from_secs(SKILL_CONFIG_TIMEOUT_SECS), rx).await {
+        Ok(payload) => payload.map_err(|_| "Connection closed before the config arrived".to_string()),
+        Err(_) => {
+            state.ws_client.lock().await.forget_config_waiters(skill_name);
+            Err(format!("Timed out waiting for the config of '{}'", skill_name))
+        }
+    }
+}
+
+/// Keys of the fields a `skill.config.response` marks `secret`.
+fn secret_config_keys(payload: &Value) -> Vec<String> {
+    payload["fields"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|f| f["secret"].as_bool().unwrap_or(false))
+        .filter_map(|f| f["key"].as_str().map(String::from))
+        .collect()
+}
+
+/// Fetch a skill's current config from the server and keep it in the local
+/// history, so it can be restored after experimenting with settings. Secret
+/// fields (API keys) are not stored.
+#[tauri::command]
+async fn snapshot_skill_config(
+    state: tauri::State<'_, AppState>,
+    skill_name: String,
+) -> Result<SkillConfigSnapshot, String> {
+    let payload = fetch_skill_config_payload(&state, &skill_name).await?;
+
+    let redacted = secret_config_keys(&payload);
+    let mut config = payload["config"].clone();
+    if let Some(map) = config.as_object_mut() {
+        for key in &redacted {
+            map.remove(key);
+        }
+    }
+    let snapshot = SkillConfigSnapshot {
+        id: uuid::Uuid::new_v4().to_string(),
+        created_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64,
+        config,
+        redacted,
+    };
+
+    let mut history = load_skill_config_history()?;
+    let snapshots = history.entry(skill_name.clone()).or_default();
+    // Snapshots from before secrets were left out still hold them
+    for older in snapshots.iter_mut() {
+        if let Some(map) = older.config.as_object_mut() {
+            for key in &snapshot.redacted {
+                map.remove(key);
+            }
+        }
+    }
+    snapshots.push(snapshot.clone());
+    if snapshots.len() > SKILL_CONFIG_HISTORY_LIMIT {
+        let excess = snapshots.len() - SKILL_CONFIG_HISTORY_LIMIT;
+        snapshots.drain(..excess);
+    }
+    save_skill_config_history(&history)?;
+    println!("[Tauri] snapshot_skill_config: saved {} for '{}'", snapshot.id, skill_name);
+    Ok(snapshot)
+}
+
+/// Stored config snapshots of a skill, newest first.
+#[tauri::command]
+fn list_skill_config_snapshots(skill_name: String) -> Result<Vec<SkillConfigSnapshot>, String> {
+    let mut snapshots = load_skill_config_history()?.remove(&skill_name).unwrap_or_default();
+    snapshots.reverse();
+    Ok(snapshots)
+}
+
+/// Reapply a stored snapshot via `skill.config.set`. Secret fields were not
+/// stored, so their current values on the server are kept.
+#[tauri::command]
+async fn restore_skill_config(
+    state: tauri::State<'_, AppState>,
+    skill_name: String,
+    snapshot_id: String,
+) -> Result<(), String> {
+    let snapshot = load_skill_config_history()?
+        .remove(&skill_name)
+        .unwrap_or_default()
+        .into_iter()
+        .find(|s| s.id == snapshot_id)
+        .ok_or_else(|| format!("Snapshot '{}' not found for '{}'", snapshot_id, skill_name))?;
+
+    // skill.config.set replaces the whole config, so carry the secrets over
+    let current = fetch_skill_config_payload(&state, &skill_name).await?;
+    let mut config = snapshot.config;
+    if let Some(map) = config.as_object_mut() {
+        for key in secret_config_keys(&current) {
+            match current["config"].get(&key) {
+                Some(value) => map.insert(key, value.clone()),
+                None => map.remove(&key),
+            };
+        }
+    }
+
+    let client = state.ws_client.lock().await;
+    client
+        .send_skill_config_set(&skill_name, &config)
+        .await
+        .map_err(|e| e.to_string())?;
+    println!("[Tauri] restore_skill_config: restored {} for '{}'", snapshot_id, skill_name);
+    Ok(())
+}
+
 // ── Local OpenClaw management commands ──
 
 const OPENCLAW_PROCESS_NAME: &str = "local-openclaw";
 
+/// Set while a start sequence (spawn + health poll) is in progress.
+static OPENCLAW_STARTING: AtomicBool = AtomicBool::new(false);
+
+/// Clears a start-in-progress flag when the start sequence ends, including on early return.
+struct StartGuard(&'static AtomicBool);
+
+impl StartGuard {
+    /// Claim the flag, or return None if another start is already in flight.
+    fn acquire(flag: &'static AtomicBool) -> Option<Self> {
+        flag.compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| StartGuard(flag))
+    }
+}
+
+impl Drop for StartGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
 #[derive(Serialize)]
 struct PrerequisiteStatus {
     node_installed: bool,
@@ -230,43 +796,218 @@ struct PrerequisiteStatus {
 /// Build an extended PATH that includes common Node.js install locations (nvm, Homebrew, Volta, fnm).
 fn extended_path() -> String {
     let home = std::env::var("HOME").unwrap_or_default();
-    let mut path = std::env::var("PATH").unwrap_or_default();
+    let launch_path = std::env::var_os("PATH").unwrap_or_default();
+    let mut dirs: Vec<std::path::PathBuf> = std::env::split_paths(&launch_path).collect();
     // For nvm, find the latest installed version directory
     if let Ok(entries) = std::fs::read_dir(format!("{}/.nvm/versions/node", home)) {
         let mut versions: Vec<_> = entries.filter_map(|e| e.ok()).collect();
         versions.sort_by(|a, b| b.file_name().cmp(&a.file_name()));
         if let Some(latest) = versions.first() {
-            path = format!("{}/bin:{}", latest.path().display(), path);
+            dirs.insert(0, latest.path().join("bin"));
         }
     }
     let extra = [
-        "/usr/local/bin",
-        "/opt/homebrew/bin",
-        &format!("{}/.volta/bin", home),
-        &format!("{}/.fnm/aliases/default/bin", home),
+        "/usr/local/bin".to_string(),
+        "/opt/homebrew/bin".to_string(),
+        format!("{}/.volta/bin", home),
+        format!("{}/.fnm/aliases/default/bin", home),
     ];
     for p in extra {
-        if !path.contains(p) {
-            path = format!("{}:{}", p, path);
+        let p = std::path::PathBuf::from(p);
+        if !dirs.contains(&p) {
+            dirs.insert(0, p);
         }
     }
-    path
+    // npm's global bin dir (custom prefix, Volta, nvm) is where `npm install -g` puts openclaw
+    let joined = |dirs: &[std::path::PathBuf]| {
+        std::env::join_paths(dirs)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|_| launch_path.to_string_lossy().to_string())
+    };
+    if let Some(bin) = npm_global_bin(&joined(&dirs)) {
+        let bin = std::path::PathBuf::from(bin);
+        if !dirs.contains(&bin) {
+            dirs.push(bin);
+        }
+    }
+    joined(&dirs)
+}
+
+/// Result of the last npm global bin lookup (None if npm was missing) and
+/// when it finished.
+static NPM_GLOBAL_BIN: std::sync::Mutex<Option<(Option<String>, std::time::Instant)>> = std::sync::Mutex::new(None);
+
+/// Set while a background npm global bin lookup is running.
+static NPM_GLOBAL_BIN_LOOKUP: AtomicBool = AtomicBool::new(false);
+
+/// A lookup that found no npm is retried after this long.
+const NPM_GLOBAL_BIN_RETRY_SECS: u64 = 60;
+
+/// npm's global bin directory as last looked up. Never runs npm on the
+/// caller's thread: when nothing is cached yet, or npm was missing and the
+/// retry interval has passed, a lookup starts in the background and the
+/// current answer is returned meanwhile.
+fn npm_global_bin(search_path: &str) -> Option<String> {
+    let cached = NPM_GLOBAL_BIN.lock().unwrap().clone();
+    let stale = match cached {
+        Some((Some(_), _)) => false,
+        Some((None, checked_at)) => checked_at.elapsed() >= std::time::Duration::from_secs(NPM_GLOBAL_BIN_RETRY_SECS),
+        None => true,
+    };
+    if stale && !NPM_GLOBAL_BIN_LOOKUP.swap(true, Ordering::SeqCst) {
+        let search_path = search_path.to_string();
+        std::thread::spawn(move || {
+            lookup_npm_global_bin(&search_path);
+            NPM_GLOBAL_BIN_LOOKUP.store(false, Ordering::SeqCst);
+        });
+    }
+    cached.and_then(|(bin, _)| bin)
+}
+
+/// Directory `npm install -g` links executables into, from `npm prefix -g`
+/// (`<prefix>/bin`, or the prefix itself on Windows). `npm bin -g` was
+/// removed in npm 9, so the prefix is used instead. Blocks while npm runs;
+/// the result, found or not, is cached for `npm_global_bin`.
+fn lookup_npm_global_bin(search_path: &str) -> Option<String> {
+    // npm is a .cmd shim on Windows, which Command does not resolve by itself
+    let npm = if cfg!(target_os = "windows") { "npm.cmd" } else { "npm" };
+    let bin = std::process::Command::new(npm)
+        .args(["prefix", "-g"])
+        .env("PATH", search_path)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|output| {
+            let prefix = std::path::PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+            let bin = if cfg!(target_os = "windows") { prefix } else { prefix.join("bin") };
+            bin.to_string_lossy().to_string()
+        });
+    *NPM_GLOBAL_BIN.lock().unwrap() = Some((bin.clone(), std::time::Instant::now()));
+    bin
+}
+
+#[derive(Serialize)]
+struct NpmGlobalBinStatus {
+    bin_dir: Option<String>,
+    /// Whether the app's PATH includes it (it is added automatically when found).
+    on_path: bool,
+    /// Whether the PATH the app was launched with already had it.
+    on_launch_path: bool,
 }
 
+/// Report npm's global bin directory and whether it is reachable, for
+/// "installed but not found" problems after `npm install -g`.
 #[tauri::command]
-async fn check_openclaw_prerequisites() -> Result<PrerequisiteStatus, String> {
+async fn check_npm_global_bin() -> Result<NpmGlobalBinStatus, String> {
+    // Look up afresh (off the async runtime) rather than trust the cache
+    let search_path = extended_path();
+    let bin_dir = tauri::async_runtime::spawn_blocking(move || lookup_npm_global_bin(&search_path))
+        .await
+        .map_err(|e| format!("npm lookup failed: {}", e))?;
     let path = extended_path();
+    let contains = |p: &str| {
+        bin_dir
+            .as_ref()
+            .map(|bin| std::env::split_paths(p).any(|d| d == std::path::Path::new(bin)))
+            .unwrap_or(false)
+    };
+    Ok(NpmGlobalBinStatus {
+        on_path: contains(&path),
+        on_launch_path: contains(&std::env::var("PATH").unwrap_or_default()),
+        bin_dir,
+    })
+}
 
-    let node_output = std::process::Command::new("node")
-        .arg("--version")
-        .env("PATH", &path)
-        .output();
-    let (node_installed, node_version) = match node_output {
-        Ok(out) if out.status.success() => {
-            let ver = String::from_utf8_lossy(&out.stdout).trim().to_string();
-            // Check >= 18: parse "v18.x.y"
-            let major: u32 = ver
-                .trim_start_matches('v')
+/// Config directory for a local OpenClaw install (per-user when `user_id` is set).
+fn openclaw_config_dir(user_id: Option<&str>) -> Result<std::path::PathBuf, String> {
+    let home = dirs_next::home_dir().ok_or("Cannot find home directory")?;
+    Ok(match user_id {
+        Some(uid) => home.join(".agentos").join("openclaw").join("users").join(uid),
+        None => home.join(".agentos").join("openclaw"),
+    })
+}
+
+/// Write a config file, retrying briefly when it is locked by another process
+/// (e.g. a running gateway on Windows). Lock/permission failures get an
+/// actionable message instead of the raw OS error.
+async fn write_config_file(path: &std::path::Path, contents: String, label: &str) -> Result<(), String> {
+    fn is_locked(e: &std::io::Error) -> bool {
+        // ERROR_SHARING_VIOLATION / ERROR_LOCK_VIOLATION
+        cfg!(target_os = "windows") && matches!(e.raw_os_error(), Some(32) | Some(33))
+    }
+
+    let mut attempt = 0;
+    loop {
+        match std::fs::write(path, &contents) {
+            Ok(()) => return Ok(()),
+            Err(e) if is_locked(&e) && attempt < 4 => {
+                attempt += 1;
+                tokio::time::sleep(std::time::Duration::from_millis(250 * attempt)).await;
+            }
+            Err(e) if is_locked(&e) => {
+                return Err(format!(
+                    "Failed to write {}: the file is in use by another process. Stop the gateway before changing config and try again.",
+                    label
+                ));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                return Err(format!(
+                    "Failed to write {}: permission denied for {}. Stop the gateway and check that the file is writable.",
+                    label,
+                    path.display()
+                ));
+            }
+            Err(e) => return Err(format!("Failed to write {}: {}", label, e)),
+        }
+    }
+}
+
+/// Generate a random 48-char hex token for gateway auth.
+fn generate_gateway_token() -> String {
+    (0..48)
+        .map(|_| {
+            let idx = (rand::random::<u8>() % 16) as usize;
+            "0123456789abcdef".chars().nth(idx).unwrap()
+        })
+        .collect()
+}
+
+/// Spawn `openclaw gateway` under the process manager with the given config and state dir.
+fn spawn_openclaw_gateway(
+    pm: &mut ProcessManager,
+    config_path: &std::path::Path,
+    state_dir: &std::path::Path,
+) -> Result<u32, String> {
+    let mut envs = HashMap::new();
+    envs.insert("OPENCLAW_CONFIG_PATH".to_string(), config_path.to_string_lossy().to_string());
+    envs.insert("OPENCLAW_STATE_DIR".to_string(), state_dir.to_string_lossy().to_string());
+    envs.insert("PATH".to_string(), extended_path());
+    envs.extend(http::proxy_envs().into_iter().map(|(k, v)| (k.to_string(), v)));
+
+    pm.spawn_with_env(
+        OPENCLAW_PROCESS_NAME,
+        "openclaw",
+        &["gateway".to_string()],
+        Some(&envs),
+        None,
+        None,
+    ).map_err(|e| format!("Failed to start OpenClaw: {}", e))
+}
+
+#[tauri::command]
+async fn check_openclaw_prerequisites() -> Result<PrerequisiteStatus, String> {
+    let path = extended_path();
+
+    let node_output = std::process::Command::new("node")
+        .arg("--version")
+        .env("PATH", &path)
+        .output();
+    let (node_installed, node_version) = match node_output {
+        Ok(out) if out.status.success() => {
+            let ver = String::from_utf8_lossy(&out.stdout).trim().to_string();
+            // Check >= 18: parse "v18.x.y"
+            let major: u32 = ver
+                .trim_start_matches('v')
                 .split('.')
                 .next()
                 .and_then(|s| s.parse().ok())
@@ -303,16 +1044,603 @@ async fn check_openclaw_prerequisites() -> Result<PrerequisiteStatus, String> {
     })
 }
 
+/// Maximum attempts for npm/pip commands that hit the network.
+const NETWORK_RETRY_ATTEMPTS: u32 = 3;
+
+/// Upper bound for a single npm/pip/venv run during install.
+const INSTALL_COMMAND_TIMEOUT_SECS: u64 = 900;
+
+/// Error returned by install steps aborted through `cancel_install`.
+const INSTALL_CANCELLED: &str = "Install was cancelled";
+
+/// Cancellation flag for the running install. The flag is sticky until the
+/// next install starts, so a cancel that lands between two steps is still
+/// seen by the next one.
+#[derive(Default)]
+struct InstallCancel {
+    requested: AtomicBool,
+    notify: tokio::sync::Notify,
+}
+
+impl InstallCancel {
+    /// Clear a cancel left over from an earlier install.
+    fn reset(&self) {
+        self.requested.store(false, Ordering::SeqCst);
+    }
+
+    fn cancel(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once the install is cancelled, immediately if it already was.
+    async fn cancelled(&self) {
+        loop {
+            // Register before checking the flag so a cancel in between is not missed
+            let notified = self.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Run one install command, killing the child if `cancel` fires first.
+async fn run_install_command(
+    program: &str,
+    args: &[String],
+    path: &str,
+    cancel: &InstallCancel,
+) -> Result<std::process::Output, String> {
+    // Dropping the command future drops the child, which kills it
+    tokio::select! {
+        result = run_command_with_timeout(program, args, path, INSTALL_COMMAND_TIMEOUT_SECS, None) => result,
+        _ = cancel.cancelled() => Err(INSTALL_CANCELLED.to_string()),
+    }
+}
+
+/// Progress event emitted during install steps.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InstallProgress {
+    step: String,
+    attempt: u32,
+    max_attempts: u32,
+    message: String,
+}
+
+/// Whether a failed npm/pip run looks like a transient network problem worth retrying.
+fn is_network_error(stderr: &str) -> bool {
+    let lower = stderr.to_lowercase();
+    if lower.contains("eacces") || lower.contains("permission denied") || lower.contains("eperm") || lower.contains("e404") {
+        return false;
+    }
+    [
+        "etimedout",
+        "econnreset",
+        "econnrefused",
+        "eai_again",
+        "enotfound",
+        "err_socket_timeout",
+        "socket hang up",
+        "network",
+        "fetch failed",
+        "registry error",
+        "err_registry",
+        "timed out",
+        "connection error",
+        "connection aborted",
+        "temporary failure in name resolution",
+        "max retries exceeded",
+    ]
+    .iter()
+    .any(|pat| lower.contains(pat))
+}
+
+/// Run a network-bound command (npm/pip), retrying with exponential backoff on
+/// network failures. Emits `install-progress` events with the attempt count.
+/// Returns `INSTALL_CANCELLED` if `cancel` fires, even during a backoff wait.
+async fn run_with_network_retry(
+    app_handle: &tauri::AppHandle,
+    step: &str,
+    program: &str,
+    args: &[String],
+    path: &str,
+    cancel: &InstallCancel,
+) -> Result<std::process::Output, String> {
+    let mut attempt = 1;
+    loop {
+        let _ = app_handle.emit("install-progress", InstallProgress {
+            step: step.to_string(),
+            attempt,
+            max_attempts: NETWORK_RETRY_ATTEMPTS,
+            message: format!("{} {}", program, args.join(" ")),
+        });
+
+        let output = run_install_command(program, args, path, cancel).await?;
+        if output.status.success() || attempt >= NETWORK_RETRY_ATTEMPTS {
+            return Ok(output);
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !is_network_error(&stderr) {
+            return Ok(output);
+        }
+
+        let delay = 2u64.pow(attempt);
+        println!("[{}] attempt {}/{} failed with network error, retrying in {}s", step, attempt, NETWORK_RETRY_ATTEMPTS, delay);
+        tokio::select! {
+            _ = tokio::time::sleep(std::time::Duration::from_secs(delay)) => {}
+            _ = cancel.cancelled() => return Err(INSTALL_CANCELLED.to_string()),
+        }
+        attempt += 1;
+    }
+}
+
+/// Resolved model endpoint for a provider, shared by OpenClaw and CoPaw config generation.
+struct ProviderEndpoint {
+    base_url: String,
+    api_type: String,
+    model: String,
+    /// Extra request headers (Azure authenticates with `api-key` instead of a bearer token).
+    headers: Option<Value>,
+    /// Azure `api-version` query parameter, when one was requested.
+    api_version: Option<String>,
+}
+
+fn provider_default_base_url(provider: &str) -> Option<&'static str> {
+    Some(match provider {
+        "deepseek" => "https://api.deepseek.com/v1",
+        "openai" => "https://api.openai.com/v1",
+        "anthropic" => "https://api.anthropic.com",
+        "gemini" => "https://generativelanguage.googleapis.com/v1beta/openai",
+        "moonshot" => "https://api.moonshot.cn/v1",
+        "qwen" => "https://dashscope.aliyuncs.com/compatible-mode/v1",
+        "zhipu" => "https://open.bigmodel.cn/api/paas/v4",
+        "openrouter" => "https://openrouter.ai/api/v1",
+        _ => return None,
+    })
+}
+
+fn provider_default_model(provider: &str) -> Option<&'static str> {
+    Some(match provider {
+        "deepseek" => "deepseek-chat",
+        "openai" => "gpt-4o",
+        "anthropic" => "claude-sonnet-4-20250514",
+        "gemini" => "gemini-2.5-flash",
+        "moonshot" => "kimi-k2.5",
+        "qwen" => "qwen-max",
+        "zhipu" => "glm-4",
+        "openrouter" => "auto",
+        _ => return None,
+    })
+}
+
+/// Work out base URL, API style and model for a provider.
+///
+/// - Built-in providers fall back to their default base URL and model.
+/// - `custom` (vLLM, Ollama, any OpenAI-compatible server) requires `base_url`
+///   and `model`; `api_type` defaults to `openai-completions`.
+/// - `azure` takes the resource endpoint as `base_url` plus a deployment name.
+///   It targets the OpenAI-compatible `/openai/v1` route, where the deployment
+///   name is the model, and authenticates with an `api-key` header.
+///   `api-version` is optional on that route. CoPaw and `benchmark_model`
+///   send it as a query parameter; openclaw.json has no place for query
+///   parameters, so OpenClaw config generation rejects it (see
+///   `openclaw_provider_entry`).
+fn resolve_provider_endpoint(
+    provider: &str,
+    model: &str,
+    base_url: Option<&str>,
+    api_type: Option<&str>,
+    azure_deployment: Option<&str>,
+    azure_api_version: Option<&str>,
+    api_key: &str,
+) -> Result<ProviderEndpoint, String> {
+    let base_url = base_url.map(|u| u.trim().trim_end_matches('/')).filter(|u| !u.is_empty());
+    match provider {
+        "azure" => {
+            let endpoint = base_url.ok_or("Azure OpenAI requires base_url (e.g. https://<resource>.openai.azure.com)")?;
+            let deployment = azure_deployment
+                .filter(|d| !d.is_empty())
+                .ok_or("Azure OpenAI requires a deployment name")?;
+            Ok(ProviderEndpoint {
+                base_url: format!("{}/openai/v1", endpoint),
+                api_type: "openai-completions".to_string(),
+                model: deployment.to_string(),
+                headers: Some(serde_json::json!({ "api-key": api_key })),
+                api_version: azure_api_version.filter(|v| !v.is_empty()).map(String::from),
+            })
+        }
+        "custom" => {
+            let base_url = base_url.ok_or("Custom provider requires base_url")?;
+            if model.is_empty() {
+                return Err("Custom provider requires a model".to_string());
+            }
+            Ok(ProviderEndpoint {
+                base_url: base_url.to_string(),
+                api_type: api_type.unwrap_or("openai-completions").to_string(),
+                model: model.to_string(),
+                headers: None,
+                api_version: None,
+            })
+        }
+        _ => {
+            let default_base_url = provider_default_base_url(provider);
+            let base_url = base_url
+                .or(default_base_url)
+                .ok_or_else(|| format!("Unknown provider '{}': pass base_url or use the custom provider", provider))?;
+            let model = if model.is_empty() {
+                provider_default_model(provider)
+                    .ok_or_else(|| format!("Unknown provider '{}': a model is required", provider))?
+            } else {
+                model
+            };
+            let default_api_type = if provider == "anthropic" { "anthropic" } else { "openai-completions" };
+            Ok(ProviderEndpoint {
+                base_url: base_url.to_string(),
+                api_type: api_type.unwrap_or(default_api_type).to_string(),
+                model: model.to_string(),
+                headers: None,
+                api_version: None,
+            })
+        }
+    }
+}
+
+/// Provider settings for `benchmark_model`, resolved like the OpenClaw config.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BenchmarkConfig {
+    provider: String,
+    api_key: String,
+    #[serde(default)]
+    model: String,
+    base_url: Option<String>,
+    api_type: Option<String>,
+    azure_deployment: Option<String>,
+    azure_api_version: Option<String>,
+}
+
+const BENCHMARK_DEFAULT_PROMPT: &str = "Write a short paragraph explaining what a large language model is.";
+const BENCHMARK_MAX_RUNS: u32 = 10;
+const BENCHMARK_MAX_TOKENS: u32 = 256;
+const BENCHMARK_REQUEST_TIMEOUT_SECS: u64 = 120;
+
+#[derive(Serialize)]
+struct ModelBenchmark {
+    model: String,
+    /// Runs that completed; failed runs are listed in `errors`.
+    runs: u32,
+    avg_first_token_ms: f64,
+    avg_total_ms: f64,
+    /// Output tokens per second after the first token.
+    avg_tokens_per_sec: f64,
+    errors: Vec<String>,
+}
+
+struct BenchmarkRun {
+    first_token_ms: f64,
+    total_ms: f64,
+    tokens: u64,
+}
+
+/// One streaming completion, timing the first content token and the end of
+/// the stream. Token counts come from the usage the provider reports, or
+/// the number of content deltas when it reports none.
+async fn benchmark_run(endpoint: &ProviderEndpoint, api_key: &str, prompt: &str) -> Result<BenchmarkRun, String> {
+    let client = http::client();
+    let anthropic = endpoint.api_type == "anthropic";
+    let messages = serde_json::json!([{ "role": "user", "content": prompt }]);
+    let mut req = if anthropic {
+        client
+            .post(format!("{}/v1/messages", endpoint.base_url))
+            .header("x-api-key", api_key)
+            .header("anthropic-version", "2023-06-01")
+            .json(&serde_json::json!({
+                "model": endpoint.model,
+                "max_tokens": BENCHMARK_MAX_TOKENS,
+                "stream": true,
+                "messages": messages,
+            }))
+    } else {
+        let mut req = client
+            .post(format!("{}/chat/completions", endpoint.base_url))
+            .json(&serde_json::json!({
+                "model": endpoint.model,
+                "max_tokens": BENCHMARK_MAX_TOKENS,
+                "stream": true,
+                "stream_options": { "include_usage": true },
+                "messages": messages,
+            }));
+        if let Some(ref version) = endpoint.api_version {
+            req = req.query(&[("api-version", version)]);
+        }
+        match endpoint.headers {
+            Some(ref headers) => {
+                for (name, value) in headers.as_object().into_iter().flatten() {
+                    req = req.header(name.as_str(), value.as_str().unwrap_or_default());
+                }
+                req
+            }
+            None => req.bearer_auth(api_key),
+        }
+    };
+    req = req.timeout(std::time::Duration::from_secs(BENCHMARK_REQUEST_TIMEOUT_SECS));
+
+    let started = std::time::Instant::now();
+    let mut resp = req.send().await.map_err(|e| format!("Request failed: {}", e))?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+        return Err(format!("{}: {}", status, text.chars().take(300).collect::<String>()));
+    }
+
+    let mut first_token_ms = None;
+    let mut deltas: u64 = 0;
+    let mut usage_tokens: Option<u64> = None;
+    let mut buffer = String::new();
+    while let Some(bytes) = resp.chunk().await.map_err(|e| format!("Stream failed: {}", e))? {
+        buffer.push_str(&String::from_utf8_lossy(&bytes));
+        while let Some(newline) = buffer.find('\n') {
+            let line: String = buffer.drain(..=newline).collect();
+            let Some(data) = line.trim().strip_prefix("data:").map(str::trim) else { continue };
+            let Ok(event) = serde_json::from_str::<Value>(data) else { continue };
+            let text = if anthropic {
+                event["delta"]["text"].as_str()
+            } else {
+                event["choices"][0]["delta"]["content"]
+                    .as_str()
+                    .or_else(|| event["choices"][0]["delta"]["reasoning_content"].as_str())
+            };
+            if text.is_some_and(|t| !t.is_empty()) {
+                deltas += 1;
+                first_token_ms.get_or_insert_with(|| started.elapsed().as_secs_f64() * 1000.0);
+            }
+            let usage = if anthropic {
+                event["usage"]["output_tokens"].as_u64()
+            } else {
+                event["usage"]["completion_tokens"].as_u64()
+            };
+            if usage.is_some() {
+                usage_tokens = usage;
+            }
+        }
+    }
+    let total_ms = started.elapsed().as_secs_f64() * 1000.0;
+    let first_token_ms = first_token_ms.ok_or("The model returned no content")?;
+    Ok(BenchmarkRun { first_token_ms, total_ms, tokens: usage_tokens.unwrap_or(deltas) })
+}
+
+/// Time streaming completions straight against a provider (not through the
+/// gateway): time to first token, total time and output throughput,
+/// averaged over `runs` sequential requests (default 3, at most 10).
+#[tauri::command]
+async fn benchmark_model(
+    config: BenchmarkConfig,
+    prompt: Option<String>,
+    runs: Option<u32>,
+) -> Result<ModelBenchmark, String> {
+    let endpoint = resolve_provider_endpoint(
+        &config.provider,
+        &config.model,
+        config.base_url.as_deref(),
+        config.api_type.as_deref(),
+        config.azure_deployment.as_deref(),
+        config.azure_api_version.as_deref(),
+        &config.api_key,
+    )?;
+    let prompt = prompt.filter(|p| !p.trim().is_empty()).unwrap_or_else(|| BENCHMARK_DEFAULT_PROMPT.to_string());
+    let runs = runs.unwrap_or(3).clamp(1, BENCHMARK_MAX_RUNS);
+
+    let mut results = Vec::new();
+    let mut errors = Vec::new();
+    for i in 0..runs {
+        match benchmark_run(&endpoint, &config.api_key, &prompt).await {
+            Ok(run) => {
+                println!(
+                    "[Tauri] benchmark_model: {} run {}: first token {:.0}ms, total {:.0}ms, {} tokens",
+                    endpoint.model, i + 1, run.first_token_ms, run.total_ms, run.tokens
+                );
+                results.push(run);
+            }
+            Err(e) => errors.push(format!("Run {}: {}", i + 1, e)),
+        }
+    }
+    if results.is_empty() {
+        return Err(format!("All benchmark runs failed: {}", errors.join("; ")));
+    }
+
+    let n = results.len() as f64;
+    let avg = |f: fn(&BenchmarkRun) -> f64| results.iter().map(f).sum::<f64>() / n;
+    Ok(ModelBenchmark {
+        model: endpoint.model.clone(),
+        runs: results.len() as u32,
+        avg_first_token_ms: avg(|r| r.first_token_ms),
+        avg_total_ms: avg(|r| r.total_ms),
+        avg_tokens_per_sec: avg(|r| {
+            // Generation time after the first token; single-chunk replies fall back to the total
+            let generating_ms = r.total_ms - r.first_token_ms;
+            let ms = if generating_ms > 0.0 { generating_ms } else { r.total_ms };
+            r.tokens as f64 * 1000.0 / ms
+        }),
+        errors,
+    })
+}
+
+/// Guess whether a model is a reasoning model from its id, e.g.
+/// `deepseek-reasoner`, DeepSeek R1, OpenAI o-series and GPT-5, QwQ and
+/// `*-thinking` variants. Router prefixes like `openai/` are ignored.
+fn model_supports_reasoning(provider: &str, model: &str) -> bool {
+    let id = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+    let o_series = ["o1", "o3", "o4"]
+        .iter()
+        .any(|p| id == *p || id.starts_with(&format!("{}-", p)));
+    match provider {
+        "deepseek" => id.contains("reasoner") || id.contains("r1"),
+        "openai" | "azure" => o_series || id.starts_with("gpt-5"),
+        _ => {
+            o_series
+                || id.starts_with("gpt-5")
+                || id.contains("reasoner")
+                || id.contains("deepseek-r1")
+                || id.starts_with("qwq")
+                || id.contains("thinking")
+        }
+    }
+}
+
+/// Build the `models.providers.<provider>` entry of openclaw.json.
+/// Fails if the endpoint needs an `api-version` query parameter, which
+/// OpenClaw's provider config cannot express.
+fn openclaw_provider_entry(endpoint: &ProviderEndpoint, reasoning: bool) -> Result<Value, String> {
+    if let Some(ref version) = endpoint.api_version {
+        return Err(format!(
+            "OpenClaw cannot send an Azure api-version ('{}'); leave it empty to use the /openai/v1 route without one",
+            version
+        ));
+    }
+    let mut entry = serde_json::json!({
+        "baseUrl": endpoint.base_url,
+        "api": endpoint.api_type,
+        "models": [{
+            "id": endpoint.model,
+            "name": endpoint.model,
+            "reasoning": reasoning,
+            "input": ["text"],
+            "cost": { "input": 0, "output": 0, "cacheRead": 0, "cacheWrite": 0 },
+            "contextWindow": 128000,
+            "maxTokens": 8192,
+        }]
+    });
+    if let Some(ref headers) = endpoint.headers {
+        entry["headers"] = headers.clone();
+    }
+    Ok(entry)
+}
+
+#[derive(Serialize)]
+struct InstallStep {
+    name: String,
+    status: String, // "done", "skipped", "failed"
+    detail: String,
+}
+
+impl InstallStep {
+    fn new(name: &str, status: &str, detail: impl Into<String>) -> Self {
+        Self { name: name.to_string(), status: status.to_string(), detail: detail.into() }
+    }
+}
+
 #[derive(Serialize)]
 struct InstallResult {
     success: bool,
     token: String,
     config_dir: String,
     error: String,
+    steps: Vec<InstallStep>,
+    /// Where the `openclaw` executable was found after install.
+    openclaw_path: Option<String>,
+}
+
+/// Stable 64-bit FNV-1a hash, hex encoded (used to detect edited config files).
+fn fnv1a_hex(data: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in data {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Parts of openclaw.json that `install_openclaw` owns. Other writers
+/// (port, token, concurrency, workspace, agent defaults) never touch these,
+/// so they alone decide whether the user hand-edited the install's config.
+const INSTALL_OWNED_KEYS: &[&str] = &["/meta", "/auth", "/models", "/agents/defaults/model", "/commands", "/skills"];
+
+/// Hash of the install-owned parts of an openclaw.json.
+fn install_owned_hash(config: &Value) -> String {
+    let owned: Vec<&Value> = INSTALL_OWNED_KEYS
+        .iter()
+        .map(|key| config.pointer(key).unwrap_or(&Value::Null))
+        .collect();
+    fnv1a_hex(serde_json::to_string(&owned).unwrap().as_bytes())
+}
+
+/// Copy the install-owned parts of `generated` into `existing`, keeping every
+/// other key (gateway port and token, concurrency, workspace) as it is.
+fn merge_install_owned(existing: &mut Value, generated: &Value) {
+    for key in INSTALL_OWNED_KEYS {
+        let Some(value) = generated.pointer(key) else { continue };
+        let mut target = &mut *existing;
+        for part in key.trim_start_matches('/').split('/') {
+            if !target.is_object() {
+                *target = serde_json::json!({});
+            }
+            target = target
+                .as_object_mut()
+                .unwrap()
+                .entry(part.to_string())
+                .or_insert(Value::Null);
+        }
+        *target = value.clone();
+    }
+}
+
+/// Record the install-owned hash of a config AgentOS itself changed (model
+/// switches, agent defaults), so the next install doesn't mistake it for a
+/// hand edit. A no-op if the config was never installed by AgentOS.
+fn refresh_install_hash(config_dir: &std::path::Path, config: &Value) {
+    let path = config_dir.join("install-state.json");
+    let Some(mut state) = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|s| serde_json::from_str::<Value>(&s).ok())
+    else {
+        return;
+    };
+    state["configHash"] = serde_json::json!(install_owned_hash(config));
+    if let Err(e) = std::fs::write(&path, serde_json::to_string_pretty(&state).unwrap()) {
+        println!("[Tauri] Failed to update install-state.json: {}", e);
+    }
+}
+
+/// Abort a running `install_openclaw`/`install_copaw`: the npm/pip child is
+/// killed and anything the install created is removed so a retry starts fresh.
+#[tauri::command]
+async fn cancel_install(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    println!("[Tauri] cancel_install requested");
+    state.install_cancel.cancel();
+    Ok(())
+}
+
+/// Result for an install aborted by `cancel_install`. When the install created
+/// `config_dir` itself, the partial directory is removed.
+fn cancelled_openclaw_install(config_dir: &std::path::Path, created: bool, mut steps: Vec<InstallStep>, step: &str) -> InstallResult {
+    if created && config_dir.exists() {
+        if let Err(e) = std::fs::remove_dir_all(config_dir) {
+            println!("[install_openclaw] Failed to clean up {}: {}", config_dir.display(), e);
+        }
+    }
+    steps.push(InstallStep::new(step, "failed", INSTALL_CANCELLED));
+    InstallResult {
+        success: false,
+        token: String::new(),
+        config_dir: String::new(),
+        error: INSTALL_CANCELLED.to_string(),
+        steps,
+        openclaw_path: None,
+    }
 }
 
 #[tauri::command]
 async fn install_openclaw(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
     provider: String,
     api_key: String,
     model: String,
@@ -320,8 +1648,16 @@ async fn install_openclaw(
     registry: Option<String>,
     base_url: Option<String>,
     user_id: Option<String>,
+    api_type: Option<String>,
+    azure_deployment: Option<String>,
+    azure_api_version: Option<String>,
+    force: Option<bool>,
+    reasoning: Option<bool>,
 ) -> Result<InstallResult, String> {
-    let port = port.unwrap_or(18789);
+    state.install_cancel.reset();
+    let port = port.unwrap_or_else(settings::openclaw_port);
+    let force = force.unwrap_or(false);
+    let mut steps = Vec::new();
     let home = dirs_next::home_dir().ok_or("Cannot find home directory")?;
     let config_dir = if let Some(ref uid) = user_id {
         home.join(".agentos").join("openclaw").join("users").join(uid)
@@ -329,6 +1665,19 @@ async fn install_openclaw(
         home.join(".agentos").join("openclaw")
     };
     let path = extended_path();
+    let endpoint = resolve_provider_endpoint(
+        &provider,
+        &model,
+        base_url.as_deref(),
+        api_type.as_deref(),
+        azure_deployment.as_deref(),
+        azure_api_version.as_deref(),
+        &api_key,
+    )?;
+    let reasoning =
+        reasoning.unwrap_or_else(|| model_supports_reasoning(&provider, &endpoint.model));
+    let provider_entry = openclaw_provider_entry(&endpoint, reasoning)?;
+    let created_config_dir = !config_dir.exists();
 
     // Step 1: npm install -g openclaw (skip if already installed)
     let oc_check = std::process::Command::new("openclaw")
@@ -342,20 +1691,36 @@ async fn install_openclaw(
         if let Some(ref reg) = registry {
             npm_args.push(format!("--registry={}", reg));
         }
-        let npm_result = std::process::Command::new("npm")
-            .args(&npm_args)
-            .env("PATH", &path)
-            .output()
-            .map_err(|e| format!("Failed to run npm: {}", e))?;
+        let npm_result = match run_with_network_retry(&app_handle, "install_openclaw", "npm", &npm_args, &path, &state.install_cancel).await {
+            Ok(out) => out,
+            Err(e) if e == INSTALL_CANCELLED => {
+                return Ok(cancelled_openclaw_install(&config_dir, created_config_dir, steps, "openclaw"));
+            }
+            Err(e) => return Err(format!("Failed to run npm: {}", e)),
+        };
         if !npm_result.status.success() {
             let stderr = String::from_utf8_lossy(&npm_result.stderr);
+            steps.push(InstallStep::new("openclaw", "failed", stderr.to_string()));
             return Ok(InstallResult {
                 success: false,
                 token: String::new(),
                 config_dir: String::new(),
                 error: format!("npm install failed: {}", stderr),
+                steps,
+                openclaw_path: None,
             });
         }
+        steps.push(InstallStep::new("openclaw", "done", "npm install -g openclaw"));
+        if find_in_path("openclaw", &path).is_none() {
+            let bin = npm_global_bin(&path).unwrap_or_else(|| "npm's global bin directory".to_string());
+            steps.push(InstallStep::new(
+                "path",
+                "failed",
+                format!("openclaw was installed but is not on PATH; add {} to PATH", bin),
+            ));
+        }
+    } else {
+        steps.push(InstallStep::new("openclaw", "skipped", "already installed"));
     }
 
     // Step 1b: npm install -g clawhub (skip if already installed)
@@ -369,84 +1734,58 @@ async fn install_openclaw(
         if let Some(ref reg) = registry {
             ch_args.push(format!("--registry={}", reg));
         }
-        let ch_result = std::process::Command::new("npm")
-            .args(&ch_args)
-            .env("PATH", &path)
-            .output();
-        if let Ok(ref out) = ch_result {
-            if !out.status.success() {
-                println!("[install_openclaw] clawhub install failed (non-fatal): {}", String::from_utf8_lossy(&out.stderr));
+        let ch_result = run_with_network_retry(&app_handle, "install_clawhub", "npm", &ch_args, &path, &state.install_cancel).await;
+        match ch_result {
+            Err(e) if e == INSTALL_CANCELLED => {
+                return Ok(cancelled_openclaw_install(&config_dir, created_config_dir, steps, "clawhub"));
             }
+            Ok(ref out) if !out.status.success() => {
+                let stderr = String::from_utf8_lossy(&out.stderr).to_string();
+                println!("[install_openclaw] clawhub install failed (non-fatal): {}", stderr);
+                steps.push(InstallStep::new("clawhub", "failed", stderr));
+            }
+            Ok(_) => steps.push(InstallStep::new("clawhub", "done", "npm install -g clawhub")),
+            Err(e) => steps.push(InstallStep::new("clawhub", "failed", e)),
         }
+    } else {
+        steps.push(InstallStep::new("clawhub", "skipped", "already installed"));
+    }
+
+    if state.install_cancel.is_cancelled() {
+        return Ok(cancelled_openclaw_install(&config_dir, created_config_dir, steps, "directories"));
     }
 
     // Step 2: Create directory structure
     let state_dir = config_dir.join("state");
     let agent_auth_dir = state_dir.join("agents").join("main").join("agent");
-    let workspace_dir = config_dir.join("workspace");
-    std::fs::create_dir_all(&agent_auth_dir)
-        .map_err(|e| format!("Failed to create config dirs: {}", e))?;
-    std::fs::create_dir_all(&workspace_dir)
-        .map_err(|e| format!("Failed to create workspace: {}", e))?;
-
-    // Step 3: Generate random token
-    let token: String = (0..48)
-        .map(|_| {
-            let idx = (rand::random::<u8>() % 16) as usize;
-            "0123456789abcdef".chars().nth(idx).unwrap()
-        })
-        .collect();
-
-    // Step 4: Write auth-profiles.json
-    let auth_profile_key = format!("{}:default", provider);
-    let auth_profiles = serde_json::json!({
-        "version": 1,
-        "profiles": {
-            &auth_profile_key: {
-                "type": "api_key",
-                "provider": &provider,
-                "key": &api_key,
-            }
-        },
-        "lastGood": {
-            &provider: &auth_profile_key,
-        }
-    });
-    std::fs::write(
-        agent_auth_dir.join("auth-profiles.json"),
-        serde_json::to_string_pretty(&auth_profiles).unwrap(),
-    ).map_err(|e| format!("Failed to write auth-profiles: {}", e))?;
-
-    // Step 5: Write openclaw.json
-    let default_base_url = match provider.as_str() {
-        "deepseek" => "https://api.deepseek.com/v1",
-        "openai" => "https://api.openai.com/v1",
-        "anthropic" => "https://api.anthropic.com",
-        "gemini" => "https://generativelanguage.googleapis.com/v1beta/openai",
-        "moonshot" => "https://api.moonshot.cn/v1",
-        "qwen" => "https://dashscope.aliyuncs.com/compatible-mode/v1",
-        "zhipu" => "https://open.bigmodel.cn/api/paas/v4",
-        "openrouter" => "https://openrouter.ai/api/v1",
-        _ => "https://api.deepseek.com/v1",
-    };
-    let effective_base_url = base_url.as_deref().unwrap_or(default_base_url);
-    let api_type = if provider == "anthropic" { "anthropic" } else { "openai-completions" };
-    let model_id = if model.is_empty() {
-        match provider.as_str() {
-            "deepseek" => "deepseek-chat",
-            "openai" => "gpt-4o",
-            "anthropic" => "claude-sonnet-4-20250514",
-            "gemini" => "gemini-2.5-flash",
-            "moonshot" => "kimi-k2.5",
-            "qwen" => "qwen-max",
-            "zhipu" => "glm-4",
-            "openrouter" => "auto",
-            _ => "deepseek-chat",
-        }
-    } else {
-        &model
+    let workspace_dir = match user_id {
+        Some(ref uid) => user_workspace_dir(uid)?,
+        None => config_dir.join("workspace"),
     };
+    if agent_auth_dir.is_dir() && workspace_dir.is_dir() {
+        steps.push(InstallStep::new("directories", "skipped", "already present"));
+    } else {
+        std::fs::create_dir_all(&agent_auth_dir)
+            .map_err(|e| format!("Failed to create config dirs: {}", e))?;
+        std::fs::create_dir_all(&workspace_dir)
+            .map_err(|e| format!("Failed to create workspace: {}", e))?;
+        steps.push(InstallStep::new("directories", "done", config_dir.to_string_lossy()));
+    }
 
+    // Step 3: Reuse the existing gateway token, or generate one
+    let config_path = config_dir.join("openclaw.json");
+    let install_state_path = config_dir.join("install-state.json");
+    let existing_config = std::fs::read_to_string(&config_path).ok();
+    let existing_token = existing_config
+        .as_deref()
+        .and_then(|c| serde_json::from_str::<Value>(c).ok())
+        .and_then(|c| c["gateway"]["auth"]["token"].as_str().map(String::from))
+        .filter(|t| !t.is_empty());
+    let token = existing_token.unwrap_or_else(generate_gateway_token);
+
+    // Step 4: Decide how openclaw.json is written. Only the install-owned keys
+    // are compared and replaced; a hand edit to them is kept unless `force`.
+    let auth_profile_key = format!("{}:default", provider);
     let config = serde_json::json!({
         "meta": { "lastTouchedVersion": "agentos-local-install" },
         "auth": {
@@ -457,24 +1796,12 @@ async fn install_openclaw(
         "models": {
             "mode": "merge",
             "providers": {
-                &provider: {
-                    "baseUrl": effective_base_url,
-                    "api": api_type,
-                    "models": [{
-                        "id": model_id,
-                        "name": model_id,
-                        "reasoning": false,
-                        "input": ["text"],
-                        "cost": { "input": 0, "output": 0, "cacheRead": 0, "cacheWrite": 0 },
-                        "contextWindow": 128000,
-                        "maxTokens": 8192,
-                    }]
-                }
+                &provider: provider_entry
             }
         },
         "agents": {
             "defaults": {
-                "model": { "primary": format!("{}/{}", provider, model_id) },
+                "model": { "primary": format!("{}/{}", provider, endpoint.model) },
                 "workspace": workspace_dir.to_string_lossy(),
                 "maxConcurrent": 2,
                 "subagents": { "maxConcurrent": 4 },
@@ -489,31 +1816,331 @@ async fn install_openclaw(
         },
         "skills": { "install": { "nodeManager": "npm" } },
     });
-    std::fs::write(
-        config_dir.join("openclaw.json"),
-        serde_json::to_string_pretty(&config).unwrap(),
-    ).map_err(|e| format!("Failed to write openclaw.json: {}", e))?;
-
-    Ok(InstallResult {
-        success: true,
-        token: token.clone(),
-        config_dir: config_dir.to_string_lossy().to_string(),
-        error: String::new(),
-    })
-}
+    let existing_value = existing_config
+        .as_deref()
+        .and_then(|c| serde_json::from_str::<Value>(c).ok());
+    let recorded_hash = std::fs::read_to_string(&install_state_path)
+        .ok()
+        .and_then(|s| serde_json::from_str::<Value>(&s).ok())
+        .and_then(|s| s["configHash"].as_str().map(String::from));
+    let new_config = match existing_value {
+        Some(ref existing) if !force => {
+            // Without install-state.json the config predates AgentOS managing it; take it over.
+            // Older install states hashed the whole file.
+            let whole_file_hash = existing_config.as_deref().map(|c| fnv1a_hex(c.as_bytes()));
+            let edited = recorded_hash
+                .as_deref()
+                .is_some_and(|hash| hash != install_owned_hash(existing) && Some(hash) != whole_file_hash.as_deref());
+            if edited {
+                steps.push(InstallStep::new("openclaw.json", "skipped", "model settings edited since install; pass force to overwrite"));
+                return Ok(InstallResult {
+                    success: false,
+                    token: token.clone(),
+                    config_dir: config_dir.to_string_lossy().to_string(),
+                    error: "openclaw.json was edited since install; nothing was changed. Pass force to overwrite".to_string(),
+                    steps,
+                    openclaw_path: find_in_path("openclaw", &path).map(|p| p.to_string_lossy().to_string()),
+                });
+            }
+            let mut merged = existing.clone();
+            merge_install_owned(&mut merged, &config);
+            merged
+        }
+        _ => config,
+    };
+    let config_hash = install_owned_hash(&new_config);
 
-#[tauri::command]
+    // Step 5: Write auth-profiles.json
+    let auth_profiles = serde_json::json!({
+        "version": 1,
+        "profiles": {
+            &auth_profile_key: {
+                "type": "api_key",
+                "provider": &provider,
+                "key": &api_key,
+            }
+        },
+        "lastGood": {
+            &provider: &auth_profile_key,
+        }
+    });
+    let auth_profiles_str = serde_json::to_string_pretty(&auth_profiles).unwrap();
+    let auth_path = agent_auth_dir.join("auth-profiles.json");
+    if std::fs::read_to_string(&auth_path).ok().as_deref() == Some(auth_profiles_str.as_str()) {
+        steps.push(InstallStep::new("auth-profiles", "skipped", "unchanged"));
+    } else {
+        write_config_file(&auth_path, auth_profiles_str, "auth-profiles").await?;
+        steps.push(InstallStep::new("auth-profiles", "done", ""));
+    }
+
+    // Step 6: Write openclaw.json
+    let config_str = serde_json::to_string_pretty(&new_config).unwrap();
+    if existing_config.as_deref() == Some(config_str.as_str()) {
+        steps.push(InstallStep::new("openclaw.json", "skipped", "unchanged"));
+    } else {
+        write_config_file(&config_path, config_str, "openclaw.json").await?;
+        steps.push(InstallStep::new("openclaw.json", "done", ""));
+    }
+
+    // Step 7: Record install state
+    let version = std::process::Command::new("openclaw")
+        .arg("--version")
+        .env("PATH", &path)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_default();
+    let install_state = serde_json::json!({
+        "version": version,
+        "installedAt": std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64,
+        "configHash": config_hash,
+        "provider": &provider,
+        "model": &endpoint.model,
+    });
+    write_config_file(
+        &install_state_path,
+        serde_json::to_string_pretty(&install_state).unwrap(),
+        "install-state.json",
+    ).await?;
+
+    Ok(InstallResult {
+        success: true,
+        token: token.clone(),
+        config_dir: config_dir.to_string_lossy().to_string(),
+        error: String::new(),
+        steps,
+        openclaw_path: find_in_path("openclaw", &path).map(|p| p.to_string_lossy().to_string()),
+    })
+}
+
+#[derive(Serialize)]
+struct PortStatus {
+    available: bool,
+    occupied_by_pid: Option<u32>,
+    process_name: Option<String>,
+}
+
+fn port_status(port: u16) -> PortStatus {
+    let pid = process_manager::listening_pids(port).into_iter().next();
+    // Port inspection tools may be missing; a failed bind still means "in use"
+    let bindable = std::net::TcpListener::bind(("127.0.0.1", port)).is_ok();
+    PortStatus {
+        available: pid.is_none() && bindable,
+        occupied_by_pid: pid,
+        process_name: pid.and_then(process_manager::process_name),
+    }
+}
+
+#[tauri::command]
+async fn check_port_available(port: u16) -> Result<PortStatus, String> {
+    Ok(port_status(port))
+}
+
+#[derive(Serialize)]
+struct NetworkInfo {
+    hostname: Option<String>,
+    /// Non-loopback, non-link-local interface addresses.
+    local_ips: Vec<String>,
+    gateway_port: u16,
+    /// `gateway.bind` from openclaw.json; "loopback" refuses other devices.
+    gateway_bind: Option<String>,
+    /// Candidate URLs another device on the network can use for the gateway.
+    reachable_urls: Vec<String>,
+    /// Every address is private (RFC 1918, CGNAT or IPv6 ULA), so devices
+    /// outside this network need port forwarding or a tunnel.
+    behind_nat: bool,
+}
+
+/// Whether an address is only routable inside a private network.
+fn is_private_ip(ip: &std::net::IpAddr) -> bool {
+    match ip {
+        std::net::IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            // 100.64.0.0/10 is carrier-grade NAT
+            v4.is_private() || (a == 100 && (64..128).contains(&b))
+        }
+        // fc00::/7 unique local addresses
+        std::net::IpAddr::V6(v6) => (v6.segments()[0] & 0xfe00) == 0xfc00,
+    }
+}
+
+/// Local addresses and gateway URLs for pointing another device (e.g. the
+/// phone app) at this desktop's gateway, and whether it sits behind NAT.
+#[tauri::command]
+async fn get_network_info(user_id: Option<String>) -> Result<NetworkInfo, String> {
+    let interfaces = if_addrs::get_if_addrs().map_err(|e| format!("Failed to list network interfaces: {}", e))?;
+    let mut ips: Vec<std::net::IpAddr> = interfaces
+        .iter()
+        .map(|iface| iface.ip())
+        .filter(|ip| {
+            !ip.is_loopback()
+                && match ip {
+                    std::net::IpAddr::V4(v4) => !v4.is_link_local(),
+                    // fe80::/10
+                    std::net::IpAddr::V6(v6) => (v6.segments()[0] & 0xffc0) != 0xfe80,
+                }
+        })
+        .collect();
+    // IPv4 first; it is what most users type into another device
+    let mut seen = std::collections::HashSet::new();
+    ips.retain(|ip| seen.insert(*ip));
+    ips.sort_by_key(|ip| ip.is_ipv6());
+
+    let config = read_openclaw_config(user_id.as_deref()).ok().map(|(_, c)| c);
+    let gateway_port = config
+        .as_ref()
+        .and_then(|c| c["gateway"]["port"].as_u64())
+        .map(|p| p as u16)
+        .unwrap_or_else(settings::openclaw_port);
+    let gateway_bind = config
+        .as_ref()
+        .and_then(|c| c["gateway"]["bind"].as_str().map(String::from));
+
+    let reachable_urls = ips
+        .iter()
+        .map(|ip| match ip {
+            std::net::IpAddr::V4(v4) => format!("ws://{}:{}", v4, gateway_port),
+            std::net::IpAddr::V6(v6) => format!("ws://[{}]:{}", v6, gateway_port),
+        })
+        .collect();
+
+    Ok(NetworkInfo {
+        hostname: hostname::get().ok().map(|h| h.to_string_lossy().to_string()),
+        behind_nat: !ips.is_empty() && ips.iter().all(is_private_ip),
+        local_ips: ips.iter().map(|ip| ip.to_string()).collect(),
+        gateway_port,
+        gateway_bind,
+        reachable_urls,
+    })
+}
+
+/// Make sure nothing else owns `port` before starting a local agent on it.
+/// With `kill_conflicting` the owner is killed; otherwise a descriptive error is returned.
+async fn ensure_port_free(pm: &mut ProcessManager, port: u16, kill_conflicting: bool) -> Result<(), String> {
+    let status = port_status(port);
+    if status.available {
+        return Ok(());
+    }
+    let owner = match (status.occupied_by_pid, status.process_name) {
+        (Some(pid), Some(name)) => format!("{} (pid {})", name, pid),
+        (Some(pid), None) => format!("pid {}", pid),
+        _ => "another process".to_string(),
+    };
+    if kill_conflicting && status.occupied_by_pid.is_some() {
+        let killed = pm.kill_by_port(port);
+        println!("[Tauri] Killed {:?} holding port {}", killed, port);
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        if port_status(port).available {
+            return Ok(());
+        }
+    }
+    Err(format!(
+        "Port {} is in use by {}. Stop it (retry with killConflicting) or choose another port.",
+        port, owner
+    ))
+}
+
+/// Number of trailing log lines included when a local agent fails to start.
+const STARTUP_LOG_TAIL_LINES: usize = 20;
+
+/// Lines `process_name` logged since the last call, read from a log event
+/// subscription taken before the process was spawned. Unlike indexing into
+/// the log buffer this is unaffected by the buffer dropping its oldest lines.
+fn take_new_log_lines(
+    log_rx: &mut tokio::sync::broadcast::Receiver<process_manager::LogEvent>,
+    process_name: &str,
+) -> Vec<String> {
+    use tokio::sync::broadcast::error::TryRecvError;
+    let mut lines = Vec::new();
+    loop {
+        match log_rx.try_recv() {
+            Ok(event) if event.source == process_name => lines.push(event.line),
+            Ok(_) => {}
+            Err(TryRecvError::Lagged(skipped)) => {
+                println!("[Tauri] {} log follower lagged, skipped {} lines", process_name, skipped);
+            }
+            Err(TryRecvError::Empty) | Err(TryRecvError::Closed) => break,
+        }
+    }
+    lines
+}
+
+/// Poll a freshly spawned local agent's `/health` endpoint for up to 30s.
+/// New stdout/stderr lines are forwarded to `on_log` as they arrive so the UI
+/// can show startup progress. Returns true once the endpoint responds OK.
+async fn wait_for_health(
+    mut log_rx: tokio::sync::broadcast::Receiver<process_manager::LogEvent>,
+    process_name: &str,
+    port: u16,
+    on_log: Option<&Channel<Value>>,
+) -> bool {
+    let stream = on_log.map(|_| streams::register("startup_logs"));
+    let mut forward_new_lines = || {
+        let Some(channel) = on_log else { return };
+        if stream.as_ref().is_some_and(|s| s.is_closed()) {
+            return;
+        }
+        for line in take_new_log_lines(&mut log_rx, process_name) {
+            let _ = channel.send(serde_json::json!({ "process": process_name, "line": line }));
+        }
+    };
+
+    let url = format!("http://127.0.0.1:{}/health", port);
+    let client = http::client();
+    for _ in 0..30 {
+        tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
+        forward_new_lines();
+        if let Ok(resp) = client.get(&url)
+            .timeout(std::time::Duration::from_secs(2))
+            .send()
+            .await
+        {
+            if resp.status().is_success() {
+                forward_new_lines();
+                return true;
+            }
+        }
+    }
+    forward_new_lines();
+    false
+}
+
+/// Last few captured log lines of a process, joined for error messages.
+async fn startup_log_tail(process_manager: &Arc<Mutex<ProcessManager>>, process_name: &str) -> String {
+    process_manager
+        .lock()
+        .await
+        .get_logs(process_name, STARTUP_LOG_TAIL_LINES)
+        .map(|lines| lines.join("\n"))
+        .unwrap_or_default()
+}
+
+#[tauri::command]
 async fn start_local_openclaw(
     state: tauri::State<'_, AppState>,
+    webview: tauri::Webview,
     port: Option<u16>,
     user_id: Option<String>,
+    on_log: Option<JavaScriptChannelId>,
+    kill_conflicting: Option<bool>,
 ) -> Result<String, String> {
-    let port = port.unwrap_or(18789);
+    // An optional Channel isn't a command argument, so take its id and bind it here
+    let on_log: Option<Channel<Value>> = on_log.map(|id| id.channel_on(webview));
+    let port = port.unwrap_or_else(settings::openclaw_port);
+    let _guard = match StartGuard::acquire(&OPENCLAW_STARTING) {
+        Some(guard) => guard,
+        None => return Ok("already_starting".to_string()),
+    };
     let mut pm = state.process_manager.lock().await;
 
     if pm.is_running(OPENCLAW_PROCESS_NAME) {
         return Ok("already_running".to_string());
     }
+    ensure_port_free(&mut pm, port, kill_conflicting.unwrap_or(false)).await?;
 
     let home = dirs_next::home_dir().ok_or("Cannot find home directory")?;
     let config_dir = if let Some(ref uid) = user_id {
@@ -528,71 +2155,47 @@ async fn start_local_openclaw(
         return Err("OpenClaw not installed. Run install first.".to_string());
     }
 
-    let mut envs = HashMap::new();
-    envs.insert("OPENCLAW_CONFIG_PATH".to_string(), config_path.to_string_lossy().to_string());
-    envs.insert("OPENCLAW_STATE_DIR".to_string(), state_dir.to_string_lossy().to_string());
-    envs.insert("PATH".to_string(), extended_path());
-
-    let _pid = pm.spawn_with_env(
-        OPENCLAW_PROCESS_NAME,
-        "openclaw",
-        &["gateway".to_string()],
-        Some(&envs),
-    ).map_err(|e| format!("Failed to start OpenClaw: {}", e))?;
+    // Subscribe before spawning so no startup line is missed
+    let log_rx = pm.subscribe_logs();
+    spawn_openclaw_gateway(&mut pm, &config_path, &state_dir)?;
 
     // Drop the lock before polling
     drop(pm);
 
     // Health check: poll until ready
-    let url = format!("http://127.0.0.1:{}/health", port);
-    let client = reqwest::Client::new();
-    for _ in 0..30 {
-        tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
-        if let Ok(resp) = client.get(&url)
-            .timeout(std::time::Duration::from_secs(2))
-            .send()
-            .await
-        {
-            if resp.status().is_success() {
-                println!("[Tauri] Local OpenClaw started on port {}", port);
-                return Ok("started".to_string());
-            }
-        }
+    if wait_for_health(log_rx, OPENCLAW_PROCESS_NAME, port, on_log.as_ref()).await {
+        println!("[Tauri] Local OpenClaw started on port {}", port);
+        return Ok("started".to_string());
     }
 
     // Timed out — check if process still alive
-    let pm = state.process_manager.lock().await;
-    if pm.is_running(OPENCLAW_PROCESS_NAME) {
+    let tail = startup_log_tail(&state.process_manager, OPENCLAW_PROCESS_NAME).await;
+    let running = state.process_manager.lock().await.is_running(OPENCLAW_PROCESS_NAME);
+    if running {
         // Process alive but health check failed
+        println!("[Tauri] OpenClaw health check timed out, recent logs:\n{}", tail);
+        if let Some(ref channel) = on_log {
+            let _ = channel.send(serde_json::json!({ "process": OPENCLAW_PROCESS_NAME, "timeout": true, "recentLogs": tail }));
+        }
         Ok("started_no_health".to_string())
     } else {
-        Err("OpenClaw process exited before becoming ready".to_string())
+        Err(format!("OpenClaw process exited before becoming ready. Recent logs:\n{}", tail))
     }
 }
 
 #[tauri::command]
 async fn stop_local_openclaw(
     state: tauri::State<'_, AppState>,
+    port: Option<u16>,
 ) -> Result<(), String> {
-    // First try to kill via process manager (app-managed process)
+    let port = port.unwrap_or_else(settings::openclaw_port);
+    // First try to kill via process manager (app-managed process), then any
+    // listener on the port (handles externally-started gateway processes)
     let mut pm = state.process_manager.lock().await;
     let _ = pm.kill(OPENCLAW_PROCESS_NAME);
-    drop(pm);
-
-    // Also find and kill any process listening on port 18789 (handles
-    // externally-started gateway processes not tracked by process manager)
-    if let Ok(output) = std::process::Command::new("lsof")
-        .args(&["-ti", ":18789"])
-        .output()
-    {
-        let pids = String::from_utf8_lossy(&output.stdout);
-        for pid_str in pids.split_whitespace() {
-            if let Ok(_pid) = pid_str.parse::<u32>() {
-                let _ = std::process::Command::new("kill")
-                    .arg(pid_str.trim())
-                    .output();
-            }
-        }
+    let killed = pm.kill_by_port(port);
+    if !killed.is_empty() {
+        println!("[Tauri] stop_local_openclaw: killed {:?} on port {}", killed, port);
     }
 
     Ok(())
@@ -611,7 +2214,7 @@ async fn get_local_openclaw_status(
     state: tauri::State<'_, AppState>,
     port: Option<u16>,
 ) -> Result<LocalOpenclawStatus, String> {
-    let port = port.unwrap_or(18789);
+    let port = port.unwrap_or_else(settings::openclaw_port);
     let pm = state.process_manager.lock().await;
     let mut running = pm.is_running(OPENCLAW_PROCESS_NAME);
     let mut pid = if running {
@@ -648,6 +2251,331 @@ async fn get_local_openclaw_status(
     Ok(LocalOpenclawStatus { running, pid, port, version })
 }
 
+#[derive(Serialize)]
+struct OpenclawHealthProbe {
+    reachable: bool,
+    healthy: bool,
+    model_ok: Option<bool>,
+    latency_ms: u64,
+    detail: String,
+}
+
+/// Probe the gateway beyond "is something listening": hit `/health`, then
+/// (when `check_model` is set) do a one-token authenticated chat completion.
+/// The token defaults to `gateway.auth.token` from openclaw.json.
+#[tauri::command]
+async fn probe_openclaw_health(
+    port: Option<u16>,
+    token: Option<String>,
+    user_id: Option<String>,
+    check_model: Option<bool>,
+) -> Result<OpenclawHealthProbe, String> {
+    let port = port.unwrap_or_else(settings::openclaw_port);
+    let client = http::client();
+    let started = std::time::Instant::now();
+
+    let health = client
+        .get(format!("http://127.0.0.1:{}/health", port))
+        .timeout(std::time::Duration::from_secs(3))
+        .send()
+        .await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    let resp = match health {
+        Ok(resp) => resp,
+        Err(e) => {
+            return Ok(OpenclawHealthProbe {
+                reachable: false,
+                healthy: false,
+                model_ok: None,
+                latency_ms,
+                detail: format!("Gateway unreachable: {}", e),
+            });
+        }
+    };
+    if !resp.status().is_success() {
+        return Ok(OpenclawHealthProbe {
+            reachable: true,
+            healthy: false,
+            model_ok: None,
+            latency_ms,
+            detail: format!("Health check returned {}", resp.status()),
+        });
+    }
+
+    if !check_model.unwrap_or(false) {
+        return Ok(OpenclawHealthProbe {
+            reachable: true,
+            healthy: true,
+            model_ok: None,
+            latency_ms,
+            detail: "ok".to_string(),
+        });
+    }
+
+    let token = match token {
+        Some(t) => t,
+        None => {
+            let config_path = openclaw_config_dir(user_id.as_deref())?.join("openclaw.json");
+            std::fs::read_to_string(&config_path)
+                .ok()
+                .and_then(|s| serde_json::from_str::<Value>(&s).ok())
+                .and_then(|c| c["gateway"]["auth"]["token"].as_str().map(String::from))
+                .ok_or("No gateway token provided and none found in openclaw.json")?
+        }
+    };
+
+    let body = serde_json::json!({
+        "model": "openclaw",
+        "messages": [{ "role": "user", "content": "ping" }],
+        "max_tokens": 1,
+    });
+    let (model_ok, detail) = match client
+        .post(format!("http://127.0.0.1:{}/v1/chat/completions", port))
+        .bearer_auth(&token)
+        .json(&body)
+        .timeout(std::time::Duration::from_secs(30))
+        .send()
+        .await
+    {
+        Ok(resp) if resp.status().is_success() => (Some(true), "ok".to_string()),
+        Ok(resp) if resp.status() == reqwest::StatusCode::NOT_FOUND => {
+            (None, "Gateway healthy; chat completions endpoint not enabled".to_string())
+        }
+        Ok(resp) => {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            (Some(false), format!("Model round-trip failed ({}): {}", status, text.chars().take(300).collect::<String>()))
+        }
+        Err(e) => (Some(false), format!("Model round-trip failed: {}", e)),
+    };
+
+    Ok(OpenclawHealthProbe {
+        reachable: true,
+        healthy: true,
+        model_ok,
+        latency_ms,
+        detail,
+    })
+}
+
+/// Gateway paths probed by `describe_gateway`, with the method each serves.
+/// OpenClaw has no route listing, so these are the ones it is known to expose.
+const GATEWAY_KNOWN_ENDPOINTS: &[(&str, &str)] = &[
+    ("GET", "/health"),
+    ("GET", "/v1/models"),
+    ("POST", "/v1/chat/completions"),
+    ("POST", "/v1/responses"),
+    ("POST", "/tools/invoke"),
+];
+
+#[derive(Serialize)]
+struct GatewayDescription {
+    /// "METHOD /path" for every known endpoint the gateway answers.
+    endpoints: Vec<String>,
+    version: Option<String>,
+    models_loaded: Vec<String>,
+}
+
+/// Report which of the gateway's known endpoints are enabled, its version and
+/// the models it serves. Endpoints are probed with a GET: anything other than
+/// 404 means the route exists (POST-only routes answer 405). The token
+/// defaults to `gateway.auth.token` from openclaw.json.
+#[tauri::command]
+async fn describe_gateway(
+    port: Option<u16>,
+    token: Option<String>,
+    user_id: Option<String>,
+) -> Result<GatewayDescription, String> {
+    let port = port.unwrap_or_else(settings::openclaw_port);
+    let token = match token {
+        Some(t) => Some(t),
+        None => openclaw_config_dir(user_id.as_deref())
+            .ok()
+            .and_then(|dir| std::fs::read_to_string(dir.join("openclaw.json")).ok())
+            .and_then(|s| serde_json::from_str::<Value>(&s).ok())
+            .and_then(|c| c["gateway"]["auth"]["token"].as_str().map(String::from)),
+    };
+    let client = http::client();
+    let base = format!("http://127.0.0.1:{}", port);
+
+    let mut endpoints = Vec::new();
+    let mut version = None;
+    let mut models_loaded = Vec::new();
+    for (method, path) in GATEWAY_KNOWN_ENDPOINTS {
+        let mut req = client
+            .get(format!("{}{}", base, path))
+            .timeout(std::time::Duration::from_secs(3));
+        if let Some(ref t) = token {
+            req = req.bearer_auth(t);
+        }
+        let resp = match req.send().await {
+            Ok(resp) => resp,
+            Err(e) if *path == "/health" => return Err(format!("Gateway unreachable: {}", e)),
+            Err(_) => continue,
+        };
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            continue;
+        }
+        endpoints.push(format!("{} {}", method, path));
+
+        if !resp.status().is_success() {
+            continue;
+        }
+        let body: Value = resp.json().await.unwrap_or(Value::Null);
+        match *path {
+            "/health" => version = body["version"].as_str().map(String::from),
+            "/v1/models" => {
+                models_loaded = body["data"]
+                    .as_array()
+                    .map(|models| {
+                        models
+                            .iter()
+                            .filter_map(|m| m["id"].as_str().map(String::from))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+            }
+            _ => {}
+        }
+    }
+
+    // Fall back to the installed CLI's version when /health doesn't report one
+    if version.is_none() {
+        let oc_output = std::process::Command::new("openclaw")
+            .arg("--version")
+            .env("PATH", extended_path())
+            .output();
+        if let Ok(out) = oc_output {
+            if out.status.success() {
+                version = Some(String::from_utf8_lossy(&out.stdout).trim().to_string());
+            }
+        }
+    }
+
+    Ok(GatewayDescription { endpoints, version, models_loaded })
+}
+
+/// Options for `run_self_test`; everything defaults to the local gateway.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct SelfTestConfig {
+    port: Option<u16>,
+    token: Option<String>,
+    user_id: Option<String>,
+    /// WebSocket URL for the handshake step; defaults to the gateway's port.
+    ws_url: Option<String>,
+    /// Start the gateway if it isn't running instead of failing the step.
+    start_gateway: bool,
+}
+
+#[derive(Serialize)]
+struct SelfTestStep {
+    step: String,
+    ok: bool,
+    detail: String,
+}
+
+/// Handshake timeout for the self-test's WebSocket step.
+const SELF_TEST_WS_TIMEOUT_SECS: u64 = 10;
+
+/// Walk the local stack one layer at a time — prerequisites, port, gateway,
+/// authenticated model round-trip, WebSocket handshake — and report each
+/// step, so a failure points at network, auth, the gateway or the model.
+/// Steps that depend on a failed one are reported as skipped.
+#[tauri::command]
+async fn run_self_test(
+    state: tauri::State<'_, AppState>,
+    webview: tauri::Webview,
+    config: Option<SelfTestConfig>,
+) -> Result<Vec<SelfTestStep>, String> {
+    let config = config.unwrap_or_default();
+    let port = config.port.unwrap_or_else(settings::openclaw_port);
+    let mut steps = Vec::new();
+    let mut push = |step: &str, ok: bool, detail: String| {
+        println!("[Tauri] run_self_test: {} {} — {}", step, if ok { "ok" } else { "FAILED" }, detail);
+        steps.push(SelfTestStep { step: step.to_string(), ok, detail });
+    };
+
+    // 1. Prerequisites
+    let prereqs = check_openclaw_prerequisites().await?;
+    let prereqs_ok = prereqs.node_installed && prereqs.openclaw_installed;
+    push(
+        "prerequisites",
+        prereqs_ok,
+        if prereqs_ok {
+            format!("node {}, openclaw {}", prereqs.node_version, prereqs.openclaw_version)
+        } else if !prereqs.node_installed {
+            format!("Node.js 18+ not found (got '{}')", prereqs.node_version)
+        } else {
+            "openclaw not found on PATH".to_string()
+        },
+    );
+
+    // 2. Port: taken by our gateway, or free for it to start on
+    let managed = state.process_manager.lock().await.is_running(OPENCLAW_PROCESS_NAME);
+    let status = port_status(port);
+    let owner = status.process_name.clone().unwrap_or_else(|| "another process".to_string());
+    let (port_ok, port_detail) = match (managed, status.available) {
+        (true, false) => (true, format!("Port {} held by the managed gateway", port)),
+        (true, true) => (false, format!("Gateway is running but nothing listens on port {}", port)),
+        (false, true) => (true, format!("Port {} is free", port)),
+        // An externally started gateway still passes if it answers /health below
+        (false, false) => (true, format!("Port {} in use by {}", port, owner)),
+    };
+    push("port", port_ok, port_detail);
+
+    // 3. Gateway reachable (optionally started)
+    let mut probe = probe_openclaw_health(Some(port), None, None, Some(false)).await?;
+    if !probe.reachable && config.start_gateway && prereqs_ok && status.available {
+        match start_local_openclaw(state.clone(), webview, Some(port), config.user_id.clone(), None, None).await {
+            Ok(result) => {
+                println!("[Tauri] run_self_test: start gateway: {}", result);
+                probe = probe_openclaw_health(Some(port), None, None, Some(false)).await?;
+            }
+            Err(e) => probe.detail = format!("Failed to start gateway: {}", e),
+        }
+    }
+    let gateway_ok = probe.reachable && probe.healthy;
+    push(
+        "gateway",
+        gateway_ok,
+        if gateway_ok { format!("Healthy in {}ms", probe.latency_ms) } else { probe.detail },
+    );
+
+    // 4. Authenticated model round-trip
+    if gateway_ok {
+        match probe_openclaw_health(Some(port), config.token.clone(), config.user_id.clone(), Some(true)).await {
+            Ok(model) => push("model", model.model_ok.unwrap_or(false), model.detail),
+            Err(e) => push("model", false, e),
+        }
+    } else {
+        push("model", false, "Skipped: gateway not reachable".to_string());
+    }
+
+    // 5. WebSocket handshake, closed right away
+    let ws_url = config.ws_url.unwrap_or_else(|| format!("ws://127.0.0.1:{}", port));
+    let handshake = tokio::time::timeout(
+        std::time::Duration::from_secs(SELF_TEST_WS_TIMEOUT_SECS),
+        tokio_tungstenite::connect_async(ws_url.as_str()),
+    )
+    .await;
+    match handshake {
+        Ok(Ok((mut ws, _))) => {
+            let _ = ws.close(None).await;
+            push("websocket", true, format!("Handshake with {} succeeded", ws_url));
+        }
+        Ok(Err(e)) => push("websocket", false, format!("Handshake with {} failed: {}", ws_url, e)),
+        Err(_) => push(
+            "websocket",
+            false,
+            format!("Handshake with {} timed out after {}s", ws_url, SELF_TEST_WS_TIMEOUT_SECS),
+        ),
+    }
+
+    Ok(steps)
+}
+
 #[tauri::command]
 async fn update_local_openclaw_config(
     provider: String,
@@ -655,6 +2583,10 @@ async fn update_local_openclaw_config(
     model: String,
     base_url: Option<String>,
     user_id: Option<String>,
+    api_type: Option<String>,
+    azure_deployment: Option<String>,
+    azure_api_version: Option<String>,
+    reasoning: Option<bool>,
 ) -> Result<(), String> {
     let home = dirs_next::home_dir().ok_or("Cannot find home directory")?;
     let config_dir = if let Some(ref uid) = user_id {
@@ -676,60 +2608,34 @@ async fn update_local_openclaw_config(
         .map_err(|e| format!("Failed to parse config: {}", e))?;
 
     // Update model/provider in config
-    let default_base_url = match provider.as_str() {
-        "deepseek" => "https://api.deepseek.com/v1",
-        "openai" => "https://api.openai.com/v1",
-        "anthropic" => "https://api.anthropic.com",
-        "gemini" => "https://generativelanguage.googleapis.com/v1beta/openai",
-        "moonshot" => "https://api.moonshot.cn/v1",
-        "qwen" => "https://dashscope.aliyuncs.com/compatible-mode/v1",
-        "zhipu" => "https://open.bigmodel.cn/api/paas/v4",
-        "openrouter" => "https://openrouter.ai/api/v1",
-        _ => "https://api.deepseek.com/v1",
-    };
-    let effective_base_url = base_url.as_deref().unwrap_or(default_base_url);
-    let api_type = if provider == "anthropic" { "anthropic" } else { "openai-completions" };
-    let model_id = if model.is_empty() {
-        match provider.as_str() {
-            "deepseek" => "deepseek-chat",
-            "openai" => "gpt-4o",
-            "anthropic" => "claude-sonnet-4-20250514",
-            "gemini" => "gemini-2.5-flash",
-            "moonshot" => "kimi-k2.5",
-            "qwen" => "qwen-max",
-            "zhipu" => "glm-4",
-            "openrouter" => "auto",
-            _ => "deepseek-chat",
-        }
-    } else {
-        &model
-    };
+    let endpoint = resolve_provider_endpoint(
+        &provider,
+        &model,
+        base_url.as_deref(),
+        api_type.as_deref(),
+        azure_deployment.as_deref(),
+        azure_api_version.as_deref(),
+        &api_key,
+    )?;
+    let reasoning =
+        reasoning.unwrap_or_else(|| model_supports_reasoning(&provider, &endpoint.model));
+    let provider_entry = openclaw_provider_entry(&endpoint, reasoning)?;
     let auth_profile_key = format!("{}:default", provider);
 
     config["auth"]["profiles"] = serde_json::json!({
         &auth_profile_key: { "provider": &provider, "mode": "api_key" }
     });
     config["models"]["providers"] = serde_json::json!({
-        &provider: {
-            "baseUrl": effective_base_url,
-            "api": api_type,
-            "models": [{
-                "id": model_id,
-                "name": model_id,
-                "reasoning": false,
-                "input": ["text"],
-                "cost": { "input": 0, "output": 0, "cacheRead": 0, "cacheWrite": 0 },
-                "contextWindow": 128000,
-                "maxTokens": 8192,
-            }]
-        }
+        &provider: provider_entry
     });
-    config["agents"]["defaults"]["model"]["primary"] = serde_json::json!(format!("{}/{}", provider, model_id));
+    config["agents"]["defaults"]["model"]["primary"] = serde_json::json!(format!("{}/{}", provider, endpoint.model));
 
-    std::fs::write(
+    write_config_file(
         &config_path,
         serde_json::to_string_pretty(&config).unwrap(),
-    ).map_err(|e| format!("Failed to write config: {}", e))?;
+        "config",
+    ).await?;
+    refresh_install_hash(&config_dir, &config);
 
     // Update auth-profiles.json
     let auth_profiles = serde_json::json!({
@@ -745,668 +2651,4172 @@ async fn update_local_openclaw_config(
             &provider: &auth_profile_key,
         }
     });
-    std::fs::write(
-        agent_auth_dir.join("auth-profiles.json"),
+    write_config_file(
+        &agent_auth_dir.join("auth-profiles.json"),
         serde_json::to_string_pretty(&auth_profiles).unwrap(),
-    ).map_err(|e| format!("Failed to write auth-profiles: {}", e))?;
+        "auth-profiles",
+    ).await?;
 
     Ok(())
 }
 
+/// Generate a new gateway auth token, write it to openclaw.json, and restart
+/// the gateway if it is running under the process manager. Returns the new token.
 #[tauri::command]
-async fn check_local_openclaw_installed(user_id: String) -> Result<bool, String> {
-    let home = dirs_next::home_dir().ok_or("Cannot find home directory")?;
-    let config_path = home.join(".agentos").join("openclaw").join("users").join(&user_id).join("openclaw.json");
-    Ok(config_path.exists())
-}
+async fn rotate_openclaw_token(
+    state: tauri::State<'_, AppState>,
+    user_id: Option<String>,
+) -> Result<String, String> {
+    let config_dir = openclaw_config_dir(user_id.as_deref())?;
+    let config_path = config_dir.join("openclaw.json");
 
-#[tauri::command]
-async fn upgrade_openclaw(registry: Option<String>) -> Result<String, String> {
-    let path = extended_path();
-    let mut args = vec!["update".to_string(), "-g".to_string(), "openclaw".to_string()];
-    if let Some(ref reg) = registry {
-        args.push(format!("--registry={}", reg));
+    if !config_path.exists() {
+        return Err("OpenClaw not installed".to_string());
     }
-    let output = std::process::Command::new("npm")
-        .args(&args)
-        .env("PATH", &path)
-        .output()
-        .map_err(|e| format!("Failed to run npm: {}", e))?;
 
-    if output.status.success() {
-        // Get new version
-        let ver_output = std::process::Command::new("openclaw")
-            .arg("--version")
-            .env("PATH", &path)
-            .output();
-        let ver = match ver_output {
-            Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout).trim().to_string(),
-            _ => "unknown".to_string(),
-        };
-        Ok(ver)
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(format!("npm update failed: {}", stderr))
+    let existing_str = std::fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read config: {}", e))?;
+    let mut config: serde_json::Value = serde_json::from_str(&existing_str)
+        .map_err(|e| format!("Failed to parse config: {}", e))?;
+
+    let token = generate_gateway_token();
+    config["gateway"]["auth"]["token"] = serde_json::json!(&token);
+
+    write_config_file(
+        &config_path,
+        serde_json::to_string_pretty(&config).unwrap(),
+        "config",
+    ).await?;
+
+    // Restart the gateway so the new token takes effect
+    let mut pm = state.process_manager.lock().await;
+    if pm.is_running(OPENCLAW_PROCESS_NAME) {
+        spawn_openclaw_gateway(&mut pm, &config_path, &config_dir.join("state"))?;
+        println!("[Tauri] rotate_openclaw_token: gateway restarted with new token");
     }
+
+    Ok(token)
 }
 
-// ── Local CoPaw management commands ──
+#[derive(Serialize)]
+struct OrphanedProcess {
+    pid: u32,
+    name: String,
+    cmdline: String,
+    port: Option<u16>,
+}
 
-const COPAW_PROCESS_NAME: &str = "local-copaw";
+/// Command-line markers of long-running processes AgentOS starts, with a
+/// label for each. Short-lived CLI runs (clawhub, npm) are left out: they are
+/// not tracked, so a live one started by this app would look orphaned.
+const AGENTOS_PROCESS_MARKERS: &[(&str, &str)] = &[
+    ("mcp-bridge.mjs", "mcp-bridge"),
+    ("openclaw gateway", "openclaw"),
+];
+
+/// Label an AgentOS-related command line, or None for unrelated processes.
+fn agentos_process_label(cmdline: &str) -> Option<&'static str> {
+    let normalized = cmdline.replace('\\', "/");
+    if normalized.contains(".agentos/copaw/server.py") {
+        return Some("copaw");
+    }
+    AGENTOS_PROCESS_MARKERS
+        .iter()
+        .find(|(marker, _)| normalized.contains(marker))
+        .map(|(_, label)| *label)
+}
 
-/// Build an extended PATH for Python (Homebrew, conda, pyenv, system).
-fn python_extended_path() -> String {
-    let home = std::env::var("HOME").unwrap_or_default();
-    let mut path = extended_path(); // start with Node paths too
-    let extra = [
-        &format!("{}/miniconda3/bin", home),
-        &format!("{}/anaconda3/bin", home),
-        &format!("{}/.pyenv/shims", home),
-        &format!("{}/.local/bin", home),
-        "/usr/local/bin",
-        "/opt/homebrew/bin",
-    ];
-    for p in extra {
-        if !path.contains(p) {
-            path = format!("{}:{}", p, path);
-        }
+fn orphaned_processes(pm: &ProcessManager) -> Vec<OrphanedProcess> {
+    let tracked = pm.tracked_pids();
+    let own_pid = std::process::id();
+    process_manager::system_processes()
+        .into_iter()
+        .filter(|(pid, _)| *pid != own_pid && !tracked.contains(pid))
+        .filter_map(|(pid, cmdline)| {
+            let name = agentos_process_label(&cmdline)?;
+            Some(OrphanedProcess {
+                pid,
+                name: name.to_string(),
+                port: process_manager::listening_port(pid),
+                cmdline,
+            })
+        })
+        .collect()
+}
+
+/// AgentOS-related processes (gateway, CoPaw, MCP bridge) that are
+/// running but not tracked by the process manager, e.g. left over from a crash.
+#[tauri::command]
+async fn find_orphaned_processes(state: tauri::State<'_, AppState>) -> Result<Vec<OrphanedProcess>, String> {
+    let pm = state.process_manager.lock().await;
+    Ok(orphaned_processes(&pm))
+}
+
+/// Kill an orphaned AgentOS process. Only pids reported by
+/// `find_orphaned_processes` can be killed this way.
+#[tauri::command]
+async fn kill_orphaned_process(state: tauri::State<'_, AppState>, pid: u32) -> Result<(), String> {
+    let pm = state.process_manager.lock().await;
+    if !orphaned_processes(&pm).iter().any(|p| p.pid == pid) {
+        return Err(format!("Process {} is not an orphaned AgentOS process", pid));
     }
-    path
+    if !process_manager::kill_pid(pid) {
+        return Err(format!("Failed to kill process {}", pid));
+    }
+    println!("[Tauri] kill_orphaned_process: killed {}", pid);
+    Ok(())
 }
 
 #[derive(Serialize)]
-struct CopawPrerequisiteStatus {
-    python_installed: bool,
-    python_version: String,
-    pip_installed: bool,
+struct PortChange {
+    old_port: u16,
+    new_port: u16,
+    restarted: bool,
+}
+
+/// Wait up to 5s for a port to stop being listened on.
+async fn wait_for_port_release(port: u16) -> bool {
+    for _ in 0..10 {
+        if process_manager::listening_pids(port).is_empty() {
+            return true;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    }
+    false
 }
 
+/// Move the local OpenClaw gateway to a new port: updates `gateway.port` in
+/// openclaw.json and restarts the gateway if it is running.
 #[tauri::command]
-async fn check_copaw_prerequisites() -> Result<CopawPrerequisiteStatus, String> {
-    let path = python_extended_path();
+async fn set_openclaw_port(
+    state: tauri::State<'_, AppState>,
+    user_id: Option<String>,
+    port: u16,
+) -> Result<PortChange, String> {
+    let config_dir = openclaw_config_dir(user_id.as_deref())?;
+    let config_path = config_dir.join("openclaw.json");
+    if !config_path.exists() {
+        return Err("OpenClaw not installed".to_string());
+    }
 
-    let python_output = std::process::Command::new("python3")
-        .arg("--version")
-        .env("PATH", &path)
-        .output();
-    let (python_installed, python_version) = match python_output {
-        Ok(out) if out.status.success() => {
-            // "Python 3.x.y"
-            let ver = String::from_utf8_lossy(&out.stdout).trim().to_string();
-            let ver_str = ver.strip_prefix("Python ").unwrap_or(&ver);
-            let major_minor: Vec<u32> = ver_str
-                .split('.')
-                .take(2)
-                .filter_map(|s| s.parse().ok())
-                .collect();
-            let ok = major_minor.len() == 2 && (major_minor[0] > 3 || (major_minor[0] == 3 && major_minor[1] >= 8));
-            (ok, ver_str.to_string())
+    let existing_str = std::fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read config: {}", e))?;
+    let mut config: serde_json::Value = serde_json::from_str(&existing_str)
+        .map_err(|e| format!("Failed to parse config: {}", e))?;
+    let old_port = config["gateway"]["port"].as_u64().unwrap_or(settings::DEFAULT_OPENCLAW_PORT as u64) as u16;
+    if old_port == port {
+        return Ok(PortChange { old_port, new_port: port, restarted: false });
+    }
+
+    let status = port_status(port);
+    if !status.available {
+        return Err(format!(
+            "Port {} is in use{}",
+            port,
+            status.occupied_by_pid.map(|pid| format!(" by pid {}", pid)).unwrap_or_default()
+        ));
+    }
+
+    config["gateway"]["port"] = serde_json::json!(port);
+    write_config_file(
+        &config_path,
+        serde_json::to_string_pretty(&config).unwrap(),
+        "config",
+    ).await?;
+
+    let mut pm = state.process_manager.lock().await;
+    let restarted = pm.is_running(OPENCLAW_PROCESS_NAME);
+    if restarted {
+        pm.kill(OPENCLAW_PROCESS_NAME).map_err(|e| e.to_string())?;
+        // Don't hold the process manager while the old port drains
+        drop(pm);
+        if !wait_for_port_release(old_port).await {
+            return Err(format!("Gateway stopped but port {} is still in use", old_port));
         }
-        _ => (false, String::new()),
-    };
+        let mut pm = state.process_manager.lock().await;
+        spawn_openclaw_gateway(&mut pm, &config_path, &config_dir.join("state"))?;
+        println!("[Tauri] set_openclaw_port: gateway restarted on port {}", port);
+    }
+    settings::update(|s| s.openclaw_port = port)?;
 
-    let pip_output = std::process::Command::new("pip3")
-        .arg("--version")
-        .env("PATH", &path)
-        .output();
-    let pip_installed = pip_output.map(|o| o.status.success()).unwrap_or(false);
+    Ok(PortChange { old_port, new_port: port, restarted })
+}
 
-    Ok(CopawPrerequisiteStatus {
-        python_installed,
-        python_version,
-        pip_installed,
+/// Move the local CoPaw runtime to a new port via `COPAW_PORT` in its .env,
+/// restarting it if it is running.
+#[tauri::command]
+async fn set_copaw_port(state: tauri::State<'_, AppState>, port: u16) -> Result<PortChange, String> {
+    let home = dirs_next::home_dir().ok_or("Cannot find home directory")?;
+    let config_dir = home.join(".agentos").join("copaw");
+    let env_path = config_dir.join(".env");
+    let env_content = std::fs::read_to_string(&env_path)
+        .map_err(|_| "CoPaw not installed".to_string())?;
+
+    let old_port = env_content
+        .lines()
+        .find_map(|l| l.strip_prefix("COPAW_PORT="))
+        .and_then(|v| v.trim().parse::<u16>().ok())
+        .unwrap_or(settings::DEFAULT_COPAW_PORT);
+    if old_port == port {
+        return Ok(PortChange { old_port, new_port: port, restarted: false });
+    }
+
+    let status = port_status(port);
+    if !status.available {
+        return Err(format!(
+            "Port {} is in use{}",
+            port,
+            status.occupied_by_pid.map(|pid| format!(" by pid {}", pid)).unwrap_or_default()
+        ));
+    }
+
+    let mut replaced = false;
+    let mut lines: Vec<String> = env_content
+        .lines()
+        .map(|l| {
+            if l.starts_with("COPAW_PORT=") {
+                replaced = true;
+                format!("COPAW_PORT={}", port)
+            } else {
+                l.to_string()
+            }
+        })
+        .collect();
+    if !replaced {
+        lines.push(format!("COPAW_PORT={}", port));
+    }
+    write_config_file(&env_path, lines.join("\n") + "\n", ".env").await?;
+
+    let mut pm = state.process_manager.lock().await;
+    let restarted = pm.is_running(COPAW_PROCESS_NAME);
+    if restarted {
+        pm.kill(COPAW_PROCESS_NAME).map_err(|e| e.to_string())?;
+        // Don't hold the process manager while the old port drains
+        drop(pm);
+        if !wait_for_port_release(old_port).await {
+            return Err(format!("CoPaw stopped but port {} is still in use", old_port));
+        }
+        let mut pm = state.process_manager.lock().await;
+        spawn_copaw_server(&mut pm, &config_dir.join("server.py"))?;
+        println!("[Tauri] set_copaw_port: CoPaw restarted on port {}", port);
+    }
+    settings::update(|s| s.copaw_port = port)?;
+
+    Ok(PortChange { old_port, new_port: port, restarted })
+}
+
+/// Value of `key` in a dotenv-style file, ignoring comments.
+fn env_file_value(content: &str, key: &str) -> Option<String> {
+    content.lines().find_map(|l| {
+        let (k, v) = l.split_once('=')?;
+        (k.trim() == key).then(|| v.trim().trim_matches('"').to_string())
     })
 }
 
+/// Rewrite `key=value` pairs in a dotenv-style file in place, appending keys
+/// that were not present. Unknown keys, comments and ordering are preserved.
+fn env_file_set(content: &str, updates: &[(&str, String)]) -> String {
+    let mut seen = std::collections::HashSet::new();
+    let mut lines: Vec<String> = content
+        .lines()
+        .map(|l| {
+            let key = l.split_once('=').map(|(k, _)| k.trim());
+            match key.and_then(|k| updates.iter().find(|(u, _)| *u == k)) {
+                Some((k, v)) => {
+                    seen.insert(*k);
+                    format!("{}={}", k, v)
+                }
+                None => l.to_string(),
+            }
+        })
+        .collect();
+    for (k, v) in updates {
+        if !seen.contains(k) {
+            lines.push(format!("{}={}", k, v));
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
 #[derive(Serialize)]
-struct CopawInstallResult {
-    success: bool,
-    config_dir: String,
-    error: String,
+struct CopawConfig {
+    base_url: String,
+    model: String,
+    port: u16,
+    host: String,
+    has_api_key: bool,
+}
+
+fn copaw_config_from_env(content: &str) -> CopawConfig {
+    CopawConfig {
+        base_url: env_file_value(content, "LLM_BASE_URL")
+            .unwrap_or_else(|| "https://api.deepseek.com/v1".to_string()),
+        model: env_file_value(content, "LLM_MODEL").unwrap_or_else(|| "deepseek-chat".to_string()),
+        port: env_file_value(content, "COPAW_PORT")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(settings::DEFAULT_COPAW_PORT),
+        host: env_file_value(content, "COPAW_HOST").unwrap_or_else(|| "0.0.0.0".to_string()),
+        has_api_key: env_file_value(content, "LLM_API_KEY").is_some_and(|v| !v.is_empty()),
+    }
 }
 
+/// Read the local CoPaw .env without exposing the API key.
 #[tauri::command]
-async fn install_copaw(
-    app_handle: tauri::AppHandle,
-    provider: String,
-    api_key: String,
-    model: String,
-    port: Option<u16>,
+fn get_copaw_config() -> Result<CopawConfig, String> {
+    let home = dirs_next::home_dir().ok_or("Cannot find home directory")?;
+    let env_path = home.join(".agentos").join("copaw").join(".env");
+    let env_content = std::fs::read_to_string(&env_path)
+        .map_err(|_| "CoPaw not installed".to_string())?;
+    Ok(copaw_config_from_env(&env_content))
+}
+
+#[derive(Serialize)]
+struct CopawConfigUpdate {
+    config: CopawConfig,
+    restarted: bool,
+}
+
+/// Update the local CoPaw .env, keeping keys this command doesn't manage.
+/// Passing `api_key` rotates the key; there is no keychain integration in
+/// the desktop app yet, so it is stored in .env like the installer does.
+/// CoPaw only reads .env at startup, so a running instance is restarted.
+/// Use `set_copaw_port` to change the port.
+#[tauri::command]
+async fn update_copaw_config(
+    state: tauri::State<'_, AppState>,
     base_url: Option<String>,
-) -> Result<CopawInstallResult, String> {
-    let port = port.unwrap_or(8088);
+    model: Option<String>,
+    host: Option<String>,
+    api_key: Option<String>,
+) -> Result<CopawConfigUpdate, String> {
     let home = dirs_next::home_dir().ok_or("Cannot find home directory")?;
     let config_dir = home.join(".agentos").join("copaw");
-    let path = python_extended_path();
+    let env_path = config_dir.join(".env");
+    let env_content = std::fs::read_to_string(&env_path)
+        .map_err(|_| "CoPaw not installed".to_string())?;
+
+    let mut updates: Vec<(&str, String)> = Vec::new();
+    for (key, value) in [
+        ("LLM_BASE_URL", base_url),
+        ("LLM_MODEL", model),
+        ("COPAW_HOST", host),
+        ("LLM_API_KEY", api_key),
+    ] {
+        let Some(value) = value.map(|v| v.trim().to_string()) else { continue };
+        if value.is_empty() || value.contains('\n') {
+            return Err(format!("Invalid value for {}", key));
+        }
+        if env_file_value(&env_content, key).as_deref() != Some(value.as_str()) {
+            updates.push((key, value));
+        }
+    }
 
-    // Step 1: Create directory
-    std::fs::create_dir_all(&config_dir)
-        .map_err(|e| format!("Failed to create config dir: {}", e))?;
+    if updates.is_empty() {
+        return Ok(CopawConfigUpdate { config: copaw_config_from_env(&env_content), restarted: false });
+    }
+    let rotated = updates.iter().any(|(k, _)| *k == "LLM_API_KEY");
+    let new_content = env_file_set(&env_content, &updates);
+    write_config_file(&env_path, new_content.clone(), ".env").await?;
+    let config = copaw_config_from_env(&new_content);
+    println!(
+        "[Tauri] update_copaw_config: updated {}{}",
+        updates.iter().map(|(k, _)| *k).collect::<Vec<_>>().join(", "),
+        if rotated { " (API key rotated)" } else { "" }
+    );
 
-    // Step 2: Copy server.py from Tauri resources
-    let resource_path = app_handle.path()
-        .resource_dir()
-        .map_err(|e| format!("Failed to get resource dir: {}", e))?;
-    let src_server = resource_path.join("copaw").join("server.py");
-    let src_reqs = resource_path.join("copaw").join("requirements.txt");
+    let mut pm = state.process_manager.lock().await;
+    let restarted = pm.is_running(COPAW_PROCESS_NAME);
+    if restarted {
+        pm.kill(COPAW_PROCESS_NAME).map_err(|e| e.to_string())?;
+        // Don't hold the process manager while the old port drains
+        drop(pm);
+        if !wait_for_port_release(config.port).await {
+            return Err(format!("CoPaw stopped but port {} is still in use", config.port));
+        }
+        let mut pm = state.process_manager.lock().await;
+        spawn_copaw_server(&mut pm, &config_dir.join("server.py"))?;
+        println!("[Tauri] update_copaw_config: CoPaw restarted");
+    }
 
-    // Fallback: check in src-tauri/resources for dev mode
-    let src_server = if src_server.exists() {
-        src_server
-    } else {
-        let dev_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-            .join("resources")
-            .join("copaw")
-            .join("server.py");
-        if dev_path.exists() { dev_path } else {
-            return Ok(CopawInstallResult {
-                success: false,
-                config_dir: String::new(),
-                error: "CoPaw server.py not found in resources".to_string(),
+    Ok(CopawConfigUpdate { config, restarted })
+}
+
+/// Upper bound accepted for agent and subagent concurrency settings.
+const MAX_CONCURRENCY_CAP: u32 = 32;
+
+/// Read and parse a user's openclaw.json.
+fn read_openclaw_config(user_id: Option<&str>) -> Result<(std::path::PathBuf, Value), String> {
+    let config_dir = openclaw_config_dir(user_id)?;
+    let config_path = config_dir.join("openclaw.json");
+    if !config_path.exists() {
+        return Err("OpenClaw not installed".to_string());
+    }
+    let existing_str = std::fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read config: {}", e))?;
+    let config = serde_json::from_str(&existing_str)
+        .map_err(|e| format!("Failed to parse config: {}", e))?;
+    Ok((config_dir, config))
+}
+
+/// Secret-free view of openclaw.json for settings screens.
+#[derive(Serialize)]
+struct OpenclawConfigSummary {
+    provider: Option<String>,
+    model: Option<String>,
+    base_url: Option<String>,
+    port: Option<u64>,
+    bind: Option<String>,
+    max_concurrent: Option<u64>,
+    subagent_max_concurrent: Option<u64>,
+    workspace: Option<String>,
+    has_token: bool,
+}
+
+impl OpenclawConfigSummary {
+    fn from_config(config: &Value) -> Self {
+        let defaults = &config["agents"]["defaults"];
+        // The primary model is stored as "<provider>/<model>"
+        let primary = defaults["model"]["primary"].as_str().unwrap_or("");
+        let (provider, model) = match primary.split_once('/') {
+            Some((p, m)) => (Some(p.to_string()), Some(m.to_string())),
+            None if !primary.is_empty() => (None, Some(primary.to_string())),
+            None => (None, None),
+        };
+        let base_url = provider
+            .as_deref()
+            .and_then(|p| config["models"]["providers"][p]["baseUrl"].as_str())
+            .map(String::from);
+        Self {
+            provider,
+            model,
+            base_url,
+            port: config["gateway"]["port"].as_u64(),
+            bind: config["gateway"]["bind"].as_str().map(String::from),
+            max_concurrent: defaults["maxConcurrent"].as_u64(),
+            subagent_max_concurrent: defaults["subagents"]["maxConcurrent"].as_u64(),
+            workspace: defaults["workspace"].as_str().map(String::from),
+            has_token: config["gateway"]["auth"]["token"]
+                .as_str()
+                .map(|t| !t.is_empty())
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// Provider, model, port and concurrency from openclaw.json, without the
+/// gateway token or any API keys.
+#[tauri::command]
+async fn get_openclaw_config_summary(user_id: Option<String>) -> Result<OpenclawConfigSummary, String> {
+    let (_, config) = read_openclaw_config(user_id.as_deref())?;
+    Ok(OpenclawConfigSummary::from_config(&config))
+}
+
+/// Update `agents.defaults.maxConcurrent` and `agents.defaults.subagents.maxConcurrent`
+/// in openclaw.json. The gateway is restarted when `restart` is set and it is running.
+#[tauri::command]
+async fn set_openclaw_concurrency(
+    state: tauri::State<'_, AppState>,
+    user_id: Option<String>,
+    max_concurrent: Option<u32>,
+    subagent_max_concurrent: Option<u32>,
+    restart: Option<bool>,
+) -> Result<OpenclawConfigSummary, String> {
+    for (label, value) in [("maxConcurrent", max_concurrent), ("subagents.maxConcurrent", subagent_max_concurrent)] {
+        if let Some(v) = value {
+            if v == 0 || v > MAX_CONCURRENCY_CAP {
+                return Err(format!("{} must be between 1 and {}", label, MAX_CONCURRENCY_CAP));
+            }
+        }
+    }
+
+    let (config_dir, mut config) = read_openclaw_config(user_id.as_deref())?;
+    if let Some(v) = max_concurrent {
+        config["agents"]["defaults"]["maxConcurrent"] = serde_json::json!(v);
+    }
+    if let Some(v) = subagent_max_concurrent {
+        config["agents"]["defaults"]["subagents"]["maxConcurrent"] = serde_json::json!(v);
+    }
+    let config_path = config_dir.join("openclaw.json");
+    write_config_file(
+        &config_path,
+        serde_json::to_string_pretty(&config).unwrap(),
+        "config",
+    ).await?;
+
+    if restart.unwrap_or(false) {
+        let mut pm = state.process_manager.lock().await;
+        if pm.is_running(OPENCLAW_PROCESS_NAME) {
+            spawn_openclaw_gateway(&mut pm, &config_path, &config_dir.join("state"))?;
+            println!("[Tauri] set_openclaw_concurrency: gateway restarted");
+        }
+    }
+
+    Ok(OpenclawConfigSummary::from_config(&config))
+}
+
+#[derive(Serialize)]
+struct ConfigReload {
+    /// "endpoint" (gateway reloaded in place), "restart" or "not_running".
+    method: String,
+    healthy: bool,
+    detail: String,
+}
+
+/// Make a running gateway pick up openclaw.json changes. The gateway's
+/// `/reload` endpoint is tried first; if it is missing or fails, the gateway is
+/// stopped and started again. No signal is used: Node exits on an unhandled
+/// SIGHUP, which would look like a crash.
+#[tauri::command]
+async fn reload_openclaw_config(
+    state: tauri::State<'_, AppState>,
+    user_id: Option<String>,
+) -> Result<ConfigReload, String> {
+    let (config_dir, config) = read_openclaw_config(user_id.as_deref())?;
+    if !state.process_manager.lock().await.is_running(OPENCLAW_PROCESS_NAME) {
+        return Ok(ConfigReload {
+            method: "not_running".to_string(),
+            healthy: false,
+            detail: "Gateway is not running; the new config applies on next start".to_string(),
+        });
+    }
+    let port = config["gateway"]["port"]
+        .as_u64()
+        .map(|p| p as u16)
+        .unwrap_or_else(settings::openclaw_port);
+
+    let mut req = http::client()
+        .post(format!("http://127.0.0.1:{}/reload", port))
+        .timeout(std::time::Duration::from_secs(5));
+    if let Some(token) = config["gateway"]["auth"]["token"].as_str() {
+        req = req.bearer_auth(token);
+    }
+    let endpoint_detail = match req.send().await {
+        Ok(resp) if resp.status().is_success() => {
+            println!("[Tauri] reload_openclaw_config: reloaded via /reload");
+            return Ok(ConfigReload {
+                method: "endpoint".to_string(),
+                healthy: true,
+                detail: "Gateway reloaded its config".to_string(),
             });
         }
-    };
-    let src_reqs = if src_reqs.exists() {
-        src_reqs
-    } else {
-        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-            .join("resources")
-            .join("copaw")
-            .join("requirements.txt")
-    };
+        Ok(resp) => format!("/reload returned {}", resp.status()),
+        Err(e) => format!("/reload failed: {}", e),
+    };
+
+    println!("[Tauri] reload_openclaw_config: {}, restarting gateway", endpoint_detail);
+    let log_rx = {
+        let mut pm = state.process_manager.lock().await;
+        let log_rx = pm.subscribe_logs();
+        spawn_openclaw_gateway(&mut pm, &config_dir.join("openclaw.json"), &config_dir.join("state"))?;
+        log_rx
+    };
+    let healthy = wait_for_health(log_rx, OPENCLAW_PROCESS_NAME, port, None).await;
+    Ok(ConfigReload {
+        method: "restart".to_string(),
+        healthy,
+        detail: format!("{}; gateway restarted", endpoint_detail),
+    })
+}
+
+/// Markers around the system prompt AgentOS manages in the workspace's
+/// AGENTS.md, which OpenClaw loads as agent instructions. Text outside them
+/// belongs to the user and is left alone.
+const AGENTS_MD_PROMPT_START: &str = "<!-- agentos:system-prompt -->";
+const AGENTS_MD_PROMPT_END: &str = "<!-- /agentos:system-prompt -->";
+
+/// The agent workspace an openclaw.json points at.
+fn configured_workspace(config_dir: &std::path::Path, config: &Value) -> std::path::PathBuf {
+    config["agents"]["defaults"]["workspace"]
+        .as_str()
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|| config_dir.join("workspace"))
+}
+
+/// The AgentOS-managed prompt in an AGENTS.md, if any.
+fn managed_system_prompt(agents_md: &str) -> Option<String> {
+    let start = agents_md.find(AGENTS_MD_PROMPT_START)? + AGENTS_MD_PROMPT_START.len();
+    let end = start + agents_md[start..].find(AGENTS_MD_PROMPT_END)?;
+    Some(agents_md[start..end].trim().to_string())
+}
+
+/// Replace (or with `None`, remove) the managed prompt block in AGENTS.md.
+fn write_managed_system_prompt(workspace: &std::path::Path, prompt: Option<&str>) -> Result<(), String> {
+    let path = workspace.join("AGENTS.md");
+    let existing = std::fs::read_to_string(&path).unwrap_or_default();
+    let block_range = existing.find(AGENTS_MD_PROMPT_START).and_then(|start| {
+        let end = start + existing[start..].find(AGENTS_MD_PROMPT_END)? + AGENTS_MD_PROMPT_END.len();
+        Some(start..end)
+    });
+    let rest = match block_range {
+        Some(ref range) => format!("{}{}", &existing[..range.start], &existing[range.end..]),
+        None => existing.clone(),
+    };
+    let rest = rest.trim_start_matches('\n');
+    let updated = match prompt {
+        Some(prompt) => format!("{}\n{}\n{}\n\n{}", AGENTS_MD_PROMPT_START, prompt.trim(), AGENTS_MD_PROMPT_END, rest),
+        None => rest.to_string(),
+    };
+    if updated == existing {
+        return Ok(());
+    }
+    std::fs::create_dir_all(workspace)
+        .map_err(|e| format!("Failed to create workspace {}: {}", workspace.display(), e))?;
+    std::fs::write(&path, updated).map_err(|e| format!("Failed to write AGENTS.md: {}", e))
+}
+
+/// The `agents.defaults` section of openclaw.json, plus the system prompt
+/// kept in the workspace's AGENTS.md.
+#[derive(Serialize)]
+struct AgentDefaults {
+    system_prompt: Option<String>,
+    /// Primary model as "<provider>/<model>".
+    model: Option<String>,
+    max_concurrent: Option<u64>,
+    subagent_max_concurrent: Option<u64>,
+    workspace: Option<String>,
+    /// The whole section, including keys not listed above.
+    raw: Value,
+}
+
+impl AgentDefaults {
+    fn from_config(config_dir: &std::path::Path, config: &Value) -> Self {
+        let defaults = &config["agents"]["defaults"];
+        let agents_md = std::fs::read_to_string(configured_workspace(config_dir, config).join("AGENTS.md"));
+        Self {
+            system_prompt: agents_md.ok().and_then(|md| managed_system_prompt(&md)),
+            model: defaults["model"]["primary"].as_str().map(String::from),
+            max_concurrent: defaults["maxConcurrent"].as_u64(),
+            subagent_max_concurrent: defaults["subagents"]["maxConcurrent"].as_u64(),
+            workspace: defaults["workspace"].as_str().map(String::from),
+            raw: defaults.clone(),
+        }
+    }
+}
+
+/// Fields accepted by `set_agent_defaults`; omitted fields are left as they are.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct AgentDefaultsUpdate {
+    /// Written to the workspace's AGENTS.md; an empty string removes it.
+    system_prompt: Option<String>,
+    model: Option<String>,
+    max_concurrent: Option<u32>,
+    subagent_max_concurrent: Option<u32>,
+    workspace: Option<String>,
+}
+
+#[derive(Serialize)]
+struct AgentDefaultsChange {
+    defaults: AgentDefaults,
+    /// Present when a reload was requested.
+    reload: Option<ConfigReload>,
+}
+
+/// Read `agents.defaults` (model, concurrency, workspace) from a user's
+/// openclaw.json, and the system prompt from the workspace's AGENTS.md.
+#[tauri::command]
+async fn get_agent_defaults(user_id: Option<String>) -> Result<AgentDefaults, String> {
+    let (config_dir, config) = read_openclaw_config(user_id.as_deref())?;
+    Ok(AgentDefaults::from_config(&config_dir, &config))
+}
+
+/// Update `agents.defaults` in a user's openclaw.json. Values are checked
+/// before anything is written: the model must name a configured provider,
+/// concurrency must be within bounds and the workspace an absolute path.
+/// openclaw.json has no system prompt key, so the prompt goes into a marked
+/// block of the workspace's AGENTS.md. With `reload`, a running gateway picks
+/// the change up via `reload_openclaw_config`.
+#[tauri::command]
+async fn set_agent_defaults(
+    state: tauri::State<'_, AppState>,
+    user_id: Option<String>,
+    defaults: AgentDefaultsUpdate,
+    reload: Option<bool>,
+) -> Result<AgentDefaultsChange, String> {
+    let (config_dir, mut config) = read_openclaw_config(user_id.as_deref())?;
+
+    for (label, value) in [("maxConcurrent", defaults.max_concurrent), ("subagents.maxConcurrent", defaults.subagent_max_concurrent)] {
+        if let Some(v) = value {
+            if v == 0 || v > MAX_CONCURRENCY_CAP {
+                return Err(format!("{} must be between 1 and {}", label, MAX_CONCURRENCY_CAP));
+            }
+        }
+    }
+    if let Some(ref model) = defaults.model {
+        let (provider, name) = model
+            .split_once('/')
+            .filter(|(p, m)| !p.is_empty() && !m.is_empty())
+            .ok_or_else(|| format!("Model must be '<provider>/<model>', got '{}'", model))?;
+        if config["models"]["providers"][provider].is_null() {
+            return Err(format!("Provider '{}' is not configured (model '{}')", provider, name));
+        }
+    }
+    if let Some(ref workspace) = defaults.workspace {
+        if !std::path::Path::new(workspace).is_absolute() {
+            return Err(format!("Workspace must be an absolute path: {}", workspace));
+        }
+        // Skill installs and usage reports find the workspace by user id
+        if user_id.is_none() {
+            return Err("Changing the workspace requires a user id".to_string());
+        }
+    }
+
+    let section = &mut config["agents"]["defaults"];
+    if let Some(model) = defaults.model {
+        section["model"]["primary"] = serde_json::json!(model);
+    }
+    if let Some(v) = defaults.max_concurrent {
+        section["maxConcurrent"] = serde_json::json!(v);
+    }
+    if let Some(v) = defaults.subagent_max_concurrent {
+        section["subagents"]["maxConcurrent"] = serde_json::json!(v);
+    }
+    if let Some(ref workspace) = defaults.workspace {
+        std::fs::create_dir_all(workspace)
+            .map_err(|e| format!("Failed to create workspace {}: {}", workspace, e))?;
+        section["workspace"] = serde_json::json!(workspace);
+        // Keep skill installs and usage reports pointed at the same directory
+        if let Some(ref uid) = user_id {
+            settings::update(|s| {
+                s.workspace_roots.insert(uid.clone(), workspace.clone());
+            })?;
+        }
+    }
+
+    write_config_file(
+        &config_dir.join("openclaw.json"),
+        serde_json::to_string_pretty(&config).unwrap(),
+        "config",
+    ).await?;
+    refresh_install_hash(&config_dir, &config);
+    if let Some(ref prompt) = defaults.system_prompt {
+        let prompt = Some(prompt.as_str()).filter(|p| !p.trim().is_empty());
+        write_managed_system_prompt(&configured_workspace(&config_dir, &config), prompt)?;
+    }
+    println!("[Tauri] set_agent_defaults: updated agents.defaults for {:?}", user_id);
+
+    let reload = if reload.unwrap_or(false) {
+        Some(reload_openclaw_config(state, user_id).await?)
+    } else {
+        None
+    };
+    Ok(AgentDefaultsChange { defaults: AgentDefaults::from_config(&config_dir, &config), reload })
+}
+
+#[derive(Serialize)]
+struct RepairReport {
+    /// Things that were missing or broken and have been fixed.
+    fixed: Vec<String>,
+    /// Problems that could not be fixed automatically.
+    problems: Vec<String>,
+}
+
+/// Check a local OpenClaw install and repair what can be repaired in place:
+/// missing directories, a missing gateway token, and a missing
+/// auth-profiles.json (rebuilt from openclaw.json; the API key must be
+/// passed in since openclaw.json does not store it).
+#[tauri::command]
+async fn repair_openclaw_install(
+    state: tauri::State<'_, AppState>,
+    user_id: Option<String>,
+    api_key: Option<String>,
+) -> Result<RepairReport, String> {
+    let config_dir = openclaw_config_dir(user_id.as_deref())?;
+    let config_path = config_dir.join("openclaw.json");
+    let mut fixed = Vec::new();
+    let mut problems = Vec::new();
+
+    if !config_path.exists() {
+        problems.push("openclaw.json is missing; a full install is required".to_string());
+        return Ok(RepairReport { fixed, problems });
+    }
+    let mut config: Value = match std::fs::read_to_string(&config_path)
+        .map_err(|e| e.to_string())
+        .and_then(|s| serde_json::from_str(&s).map_err(|e| e.to_string()))
+    {
+        Ok(config) => config,
+        Err(e) => {
+            problems.push(format!("openclaw.json is unreadable ({}); a full install is required", e));
+            return Ok(RepairReport { fixed, problems });
+        }
+    };
+
+    // Directory structure
+    let agent_auth_dir = config_dir.join("state").join("agents").join("main").join("agent");
+    let workspace_dir = configured_workspace(&config_dir, &config);
+    for dir in [&agent_auth_dir, &workspace_dir] {
+        if !dir.is_dir() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+            fixed.push(format!("Recreated directory {}", dir.display()));
+        }
+    }
+
+    // Gateway token
+    let token_missing = config["gateway"]["auth"]["token"]
+        .as_str()
+        .map(|t| t.is_empty())
+        .unwrap_or(true);
+    if token_missing {
+        config["gateway"]["auth"]["mode"] = serde_json::json!("token");
+        config["gateway"]["auth"]["token"] = serde_json::json!(generate_gateway_token());
+        write_config_file(
+            &config_path,
+            serde_json::to_string_pretty(&config).unwrap(),
+            "openclaw.json",
+        ).await?;
+        fixed.push("Generated a new gateway token".to_string());
+
+        // Restart the gateway so the new token takes effect
+        let mut pm = state.process_manager.lock().await;
+        if pm.is_running(OPENCLAW_PROCESS_NAME) {
+            spawn_openclaw_gateway(&mut pm, &config_path, &config_dir.join("state"))?;
+            fixed.push("Restarted the gateway with the new token".to_string());
+        }
+    }
+
+    // auth-profiles.json
+    let auth_path = agent_auth_dir.join("auth-profiles.json");
+    if !auth_path.exists() {
+        let profile = config["auth"]["profiles"]
+            .as_object()
+            .and_then(|profiles| profiles.iter().next())
+            .map(|(key, p)| (key.clone(), p["provider"].as_str().unwrap_or("").to_string()));
+        match profile {
+            Some((auth_profile_key, provider)) if !provider.is_empty() => {
+                let auth_profiles = serde_json::json!({
+                    "version": 1,
+                    "profiles": {
+                        &auth_profile_key: {
+                            "type": "api_key",
+                            "provider": &provider,
+                            "key": api_key.as_deref().unwrap_or(""),
+                        }
+                    },
+                    "lastGood": {
+                        &provider: &auth_profile_key,
+                    }
+                });
+                write_config_file(
+                    &auth_path,
+                    serde_json::to_string_pretty(&auth_profiles).unwrap(),
+                    "auth-profiles",
+                ).await?;
+                fixed.push("Regenerated auth-profiles.json".to_string());
+                if api_key.is_none() {
+                    problems.push("auth-profiles.json has no API key; update the model config to set it".to_string());
+                }
+            }
+            _ => problems.push("auth-profiles.json is missing and openclaw.json has no auth profile to rebuild it from".to_string()),
+        }
+    }
+
+    println!("[Tauri] repair_openclaw_install: fixed={:?}, problems={:?}", fixed, problems);
+    Ok(RepairReport { fixed, problems })
+}
+
+#[tauri::command]
+async fn check_local_openclaw_installed(user_id: String) -> Result<bool, String> {
+    let home = dirs_next::home_dir().ok_or("Cannot find home directory")?;
+    let config_path = home.join(".agentos").join("openclaw").join("users").join(&user_id).join("openclaw.json");
+    Ok(config_path.exists())
+}
+
+#[tauri::command]
+async fn upgrade_openclaw(
+    state: tauri::State<'_, AppState>,
+    registry: Option<String>,
+    timeout_secs: Option<u64>,
+) -> Result<String, String> {
+    let path = extended_path();
+    let mut args = vec!["update".to_string(), "-g".to_string(), "openclaw".to_string()];
+    if let Some(ref reg) = registry {
+        args.push(format!("--registry={}", reg));
+    }
+    let output = run_command_with_timeout(
+        "npm",
+        &args,
+        &path,
+        timeout_secs.unwrap_or(NPM_UPGRADE_TIMEOUT_SECS),
+        Some(&state.clawhub_cancel),
+    ).await?;
+
+    if output.status.success() {
+        // Get new version
+        let ver_output = std::process::Command::new("openclaw")
+            .arg("--version")
+            .env("PATH", &path)
+            .output();
+        let ver = match ver_output {
+            Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout).trim().to_string(),
+            _ => "unknown".to_string(),
+        };
+        Ok(ver)
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!("npm update failed: {}", stderr))
+    }
+}
+
+#[derive(Serialize)]
+struct OpenclawUpdateStatus {
+    current: Option<String>,
+    latest: String,
+    update_available: bool,
+}
+
+/// Pull the first dotted version number out of CLI output such as "openclaw 2026.1.5".
+fn extract_version(output: &str) -> Option<String> {
+    let re = regex::Regex::new(r"\d+(?:\.\d+)+(?:-[0-9A-Za-z.]+)?").unwrap();
+    re.find(output).map(|m| m.as_str().to_string())
+}
+
+/// Compare the installed OpenClaw version with the latest one published on npm.
+#[tauri::command]
+async fn check_openclaw_update(
+    registry: Option<String>,
+    timeout_secs: Option<u64>,
+) -> Result<OpenclawUpdateStatus, String> {
+    let path = extended_path();
+
+    let current = std::process::Command::new("openclaw")
+        .arg("--version")
+        .env("PATH", &path)
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .and_then(|out| extract_version(&String::from_utf8_lossy(&out.stdout)));
+
+    let mut args = vec!["view".to_string(), "openclaw".to_string(), "version".to_string()];
+    if let Some(ref reg) = registry {
+        args.push(format!("--registry={}", reg));
+    }
+    let output = run_command_with_timeout(
+        "npm",
+        &args,
+        &path,
+        timeout_secs.unwrap_or(CLAWHUB_SEARCH_TIMEOUT_SECS),
+        None,
+    ).await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("npm view failed: {}", stderr));
+    }
+    let latest = extract_version(&String::from_utf8_lossy(&output.stdout))
+        .ok_or("npm did not return a version for openclaw")?;
+
+    let update_available = match current {
+        Some(ref cur) => ws_client::compare_versions(cur, &latest) == std::cmp::Ordering::Less,
+        None => false,
+    };
+    Ok(OpenclawUpdateStatus { current, latest, update_available })
+}
+
+// ── Local CoPaw management commands ──
+
+const COPAW_PROCESS_NAME: &str = "local-copaw";
+
+static COPAW_STARTING: AtomicBool = AtomicBool::new(false);
+
+/// Build an extended PATH for Python (Homebrew, conda, pyenv, system).
+fn python_extended_path() -> String {
+    let home = std::env::var("HOME").unwrap_or_default();
+    let mut path = extended_path(); // start with Node paths too
+    let extra = [
+        &format!("{}/miniconda3/bin", home),
+        &format!("{}/anaconda3/bin", home),
+        &format!("{}/.pyenv/shims", home),
+        &format!("{}/.local/bin", home),
+        "/usr/local/bin",
+        "/opt/homebrew/bin",
+    ];
+    for p in extra {
+        if !path.contains(p) {
+            path = format!("{}:{}", p, path);
+        }
+    }
+    path
+}
+
+#[derive(Serialize)]
+struct CopawPrerequisiteStatus {
+    python_installed: bool,
+    python_version: String,
+    pip_installed: bool,
+}
+
+#[tauri::command]
+async fn check_copaw_prerequisites() -> Result<CopawPrerequisiteStatus, String> {
+    let path = python_extended_path();
+
+    let python_output = std::process::Command::new("python3")
+        .arg("--version")
+        .env("PATH", &path)
+        .output();
+    let (python_installed, python_version) = match python_output {
+        Ok(out) if out.status.success() => {
+            // "Python 3.x.y"
+            let ver = String::from_utf8_lossy(&out.stdout).trim().to_string();
+            let ver_str = ver.strip_prefix("Python ").unwrap_or(&ver);
+            let major_minor: Vec<u32> = ver_str
+                .split('.')
+                .take(2)
+                .filter_map(|s| s.parse().ok())
+                .collect();
+            let ok = major_minor.len() == 2 && (major_minor[0] > 3 || (major_minor[0] == 3 && major_minor[1] >= 8));
+            (ok, ver_str.to_string())
+        }
+        _ => (false, String::new()),
+    };
+
+    let pip_output = std::process::Command::new("pip3")
+        .arg("--version")
+        .env("PATH", &path)
+        .output();
+    let pip_installed = pip_output.map(|o| o.status.success()).unwrap_or(false);
+
+    Ok(CopawPrerequisiteStatus {
+        python_installed,
+        python_version,
+        pip_installed,
+    })
+}
+
+#[derive(Serialize)]
+struct ClaudeCodeStatus {
+    installed: bool,
+    version: String,
+}
+
+/// Look for the `claude` CLI on the extended PATH.
+fn detect_claude_code() -> ClaudeCodeStatus {
+    let output = std::process::Command::new("claude")
+        .arg("--version")
+        .env("PATH", extended_path())
+        .output();
+    match output {
+        Ok(out) if out.status.success() => ClaudeCodeStatus {
+            installed: true,
+            version: String::from_utf8_lossy(&out.stdout).trim().to_string(),
+        },
+        _ => ClaudeCodeStatus { installed: false, version: String::new() },
+    }
+}
+
+/// Detect Claude Code and report it to the server as a local agent,
+/// re-registering when availability changed.
+async fn refresh_claude_code(ws_client: &Arc<Mutex<WsClient>>) -> ClaudeCodeStatus {
+    let status = tauri::async_runtime::spawn_blocking(detect_claude_code)
+        .await
+        .unwrap_or(ClaudeCodeStatus { installed: false, version: String::new() });
+    if skill_executor::set_claude_code_available(status.installed) {
+        println!("[Tauri] Claude Code {}", if status.installed { "detected" } else { "not found" });
+        let client = ws_client.lock().await;
+        if client.is_connected() {
+            if let Err(e) = client.send_desktop_register().await {
+                println!("[Tauri] Failed to re-register after Claude Code change: {}", e);
+            }
+        }
+    }
+    status
+}
+
+#[tauri::command]
+async fn check_claude_code_prerequisites(
+    state: tauri::State<'_, AppState>,
+) -> Result<ClaudeCodeStatus, String> {
+    Ok(refresh_claude_code(&state.ws_client).await)
+}
+
+#[derive(Serialize)]
+struct CopawInstallResult {
+    success: bool,
+    config_dir: String,
+    error: String,
+}
+
+/// Result for a CoPaw install aborted by `cancel_install`: removes the config
+/// dir if this install created it, otherwise just a virtualenv it created.
+fn cancelled_copaw_install(config_dir: &std::path::Path, created_config_dir: bool, created_venv: bool) -> CopawInstallResult {
+    let partial = if created_config_dir {
+        Some(config_dir.to_path_buf())
+    } else if created_venv {
+        Some(config_dir.join("venv"))
+    } else {
+        None
+    };
+    if let Some(dir) = partial.filter(|d| d.exists()) {
+        if let Err(e) = std::fs::remove_dir_all(&dir) {
+            println!("[install_copaw] Failed to clean up {}: {}", dir.display(), e);
+        }
+    }
+    CopawInstallResult {
+        success: false,
+        config_dir: String::new(),
+        error: INSTALL_CANCELLED.to_string(),
+    }
+}
+
+#[tauri::command]
+async fn install_copaw(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    provider: String,
+    api_key: String,
+    model: String,
+    port: Option<u16>,
+    base_url: Option<String>,
+    api_type: Option<String>,
+    azure_deployment: Option<String>,
+    azure_api_version: Option<String>,
+    use_venv: Option<bool>,
+) -> Result<CopawInstallResult, String> {
+    state.install_cancel.reset();
+    let port = port.unwrap_or_else(settings::copaw_port);
+    let home = dirs_next::home_dir().ok_or("Cannot find home directory")?;
+    let config_dir = home.join(".agentos").join("copaw");
+    let path = python_extended_path();
+
+    // Step 0: Resolve the model endpoint (CoPaw only speaks the OpenAI chat API)
+    let endpoint = resolve_provider_endpoint(
+        &provider,
+        &model,
+        base_url.as_deref(),
+        api_type.as_deref(),
+        azure_deployment.as_deref(),
+        azure_api_version.as_deref(),
+        &api_key,
+    )?;
+    if endpoint.api_type != "openai-completions" {
+        return Err(format!("CoPaw requires an OpenAI-compatible provider, got '{}'", provider));
+    }
+
+    // Step 1: Create directory
+    let created_config_dir = !config_dir.exists();
+    std::fs::create_dir_all(&config_dir)
+        .map_err(|e| format!("Failed to create config dir: {}", e))?;
+
+    // Step 2: Copy server.py from Tauri resources
+    let resource_path = app_handle.path()
+        .resource_dir()
+        .map_err(|e| format!("Failed to get resource dir: {}", e))?;
+    let src_server = resource_path.join("copaw").join("server.py");
+    let src_reqs = resource_path.join("copaw").join("requirements.txt");
+
+    // Fallback: check in src-tauri/resources for dev mode
+    let src_server = if src_server.exists() {
+        src_server
+    } else {
+        let dev_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources")
+            .join("copaw")
+            .join("server.py");
+        if dev_path.exists() { dev_path } else {
+            return Ok(CopawInstallResult {
+                success: false,
+                config_dir: String::new(),
+                error: "CoPaw server.py not found in resources".to_string(),
+            });
+        }
+    };
+    let src_reqs = if src_reqs.exists() {
+        src_reqs
+    } else {
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources")
+            .join("copaw")
+            .join("requirements.txt")
+    };
+
+    std::fs::copy(&src_server, config_dir.join("server.py"))
+        .map_err(|e| format!("Failed to copy server.py: {}", e))?;
+    if src_reqs.exists() {
+        std::fs::copy(&src_reqs, config_dir.join("requirements.txt"))
+            .map_err(|e| format!("Failed to copy requirements.txt: {}", e))?;
+    }
+
+    // Step 3: Create the virtualenv (default) or drop a stale one so CoPaw
+    // runs on the global interpreter
+    let venv_dir = config_dir.join("venv");
+    let venv_python = copaw_venv_python(&config_dir);
+    let created_venv = use_venv.unwrap_or(true) && !venv_python.exists();
+    let (pip_program, mut pip_args) = if use_venv.unwrap_or(true) {
+        if created_venv {
+            let venv_args = ["-m".to_string(), "venv".to_string(), venv_dir.to_string_lossy().to_string()];
+            let venv = match run_install_command("python3", &venv_args, &path, &state.install_cancel).await {
+                Ok(out) => out,
+                Err(e) if e == INSTALL_CANCELLED => {
+                    return Ok(cancelled_copaw_install(&config_dir, created_config_dir, created_venv));
+                }
+                Err(e) => return Err(format!("Failed to run python3 -m venv: {}", e)),
+            };
+            if !venv.status.success() {
+                return Ok(CopawInstallResult {
+                    success: false,
+                    config_dir: String::new(),
+                    error: format!("Failed to create virtualenv: {}", String::from_utf8_lossy(&venv.stderr)),
+                });
+            }
+            println!("[Tauri] install_copaw: created virtualenv at {}", venv_dir.display());
+        }
+        (venv_python.to_string_lossy().to_string(), vec!["-m".to_string(), "pip".to_string()])
+    } else {
+        if venv_dir.exists() {
+            std::fs::remove_dir_all(&venv_dir)
+                .map_err(|e| format!("Failed to remove virtualenv: {}", e))?;
+            println!("[Tauri] install_copaw: removed virtualenv, using global python3");
+        }
+        ("pip3".to_string(), vec![])
+    };
+
+    // Step 4: pip install requirements
+    let reqs_path = config_dir.join("requirements.txt");
+    if reqs_path.exists() {
+        pip_args.extend(["install".to_string(), "-r".to_string(), reqs_path.to_string_lossy().to_string()]);
+        let pip_result = match run_with_network_retry(&app_handle, "install_copaw", &pip_program, &pip_args, &path, &state.install_cancel).await {
+            Ok(out) => out,
+            Err(e) if e == INSTALL_CANCELLED => {
+                return Ok(cancelled_copaw_install(&config_dir, created_config_dir, created_venv));
+            }
+            Err(e) => return Err(format!("Failed to run pip: {}", e)),
+        };
+        if !pip_result.status.success() {
+            let stderr = String::from_utf8_lossy(&pip_result.stderr);
+            return Ok(CopawInstallResult {
+                success: false,
+                config_dir: String::new(),
+                error: format!("pip install failed: {}", stderr),
+            });
+        }
+    }
+
+    if state.install_cancel.is_cancelled() {
+        return Ok(cancelled_copaw_install(&config_dir, created_config_dir, created_venv));
+    }
+
+    // Step 5: Write .env file
+    let mut env_content = format!(
+        "LLM_API_KEY={}\nLLM_BASE_URL={}\nLLM_MODEL={}\nCOPAW_PORT={}\nCOPAW_HOST=127.0.0.1\n",
+        api_key, endpoint.base_url, endpoint.model, port
+    );
+    if provider == "azure" {
+        env_content.push_str("LLM_AUTH_STYLE=azure\n");
+    }
+    if let Some(ref v) = endpoint.api_version {
+        env_content.push_str(&format!("LLM_API_VERSION={}\n", v));
+    }
+    std::fs::write(config_dir.join(".env"), &env_content)
+        .map_err(|e| format!("Failed to write .env: {}", e))?;
+
+    Ok(CopawInstallResult {
+        success: true,
+        config_dir: config_dir.to_string_lossy().to_string(),
+        error: String::new(),
+    })
+}
+
+/// Python interpreter inside CoPaw's virtualenv (`<config_dir>/venv`).
+fn copaw_venv_python(config_dir: &std::path::Path) -> std::path::PathBuf {
+    if cfg!(windows) {
+        config_dir.join("venv").join("Scripts").join("python.exe")
+    } else {
+        config_dir.join("venv").join("bin").join("python3")
+    }
+}
+
+/// Spawn the CoPaw runtime (`python3 server.py`) under the process manager,
+/// using the virtualenv's interpreter when CoPaw was installed into one.
+fn spawn_copaw_server(pm: &mut ProcessManager, server_path: &std::path::Path) -> Result<u32, String> {
+    let mut envs = HashMap::new();
+    envs.insert("PATH".to_string(), python_extended_path());
+    envs.extend(http::proxy_envs().into_iter().map(|(k, v)| (k.to_string(), v)));
+
+    let venv_python = server_path.parent().map(copaw_venv_python).filter(|p| p.exists());
+    let python = venv_python
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| "python3".to_string());
+
+    pm.spawn_with_env(
+        COPAW_PROCESS_NAME,
+        &python,
+        &[server_path.to_string_lossy().to_string()],
+        Some(&envs),
+        None,
+        None,
+    ).map_err(|e| format!("Failed to start CoPaw: {}", e))
+}
+
+#[tauri::command]
+async fn start_local_copaw(
+    state: tauri::State<'_, AppState>,
+    webview: tauri::Webview,
+    port: Option<u16>,
+    on_log: Option<JavaScriptChannelId>,
+    kill_conflicting: Option<bool>,
+) -> Result<String, String> {
+    let on_log: Option<Channel<Value>> = on_log.map(|id| id.channel_on(webview));
+    let port = port.unwrap_or_else(settings::copaw_port);
+    let _guard = match StartGuard::acquire(&COPAW_STARTING) {
+        Some(guard) => guard,
+        None => return Ok("already_starting".to_string()),
+    };
+    let mut pm = state.process_manager.lock().await;
+
+    if pm.is_running(COPAW_PROCESS_NAME) {
+        return Ok("already_running".to_string());
+    }
+    ensure_port_free(&mut pm, port, kill_conflicting.unwrap_or(false)).await?;
+
+    let home = dirs_next::home_dir().ok_or("Cannot find home directory")?;
+    let config_dir = home.join(".agentos").join("copaw");
+    let server_path = config_dir.join("server.py");
+
+    if !server_path.exists() {
+        return Err("CoPaw not installed. Run install first.".to_string());
+    }
+
+    // Subscribe before spawning so no startup line is missed
+    let log_rx = pm.subscribe_logs();
+    spawn_copaw_server(&mut pm, &server_path)?;
+
+    // Drop the lock before polling
+    drop(pm);
+
+    // Health check: poll until ready
+    if wait_for_health(log_rx, COPAW_PROCESS_NAME, port, on_log.as_ref()).await {
+        println!("[Tauri] Local CoPaw started on port {}", port);
+        return Ok("started".to_string());
+    }
+
+    // Timed out — check if process still alive
+    let tail = startup_log_tail(&state.process_manager, COPAW_PROCESS_NAME).await;
+    let running = state.process_manager.lock().await.is_running(COPAW_PROCESS_NAME);
+    if running {
+        println!("[Tauri] CoPaw health check timed out, recent logs:\n{}", tail);
+        if let Some(ref channel) = on_log {
+            let _ = channel.send(serde_json::json!({ "process": COPAW_PROCESS_NAME, "timeout": true, "recentLogs": tail }));
+        }
+        Ok("started_no_health".to_string())
+    } else {
+        Err(format!("CoPaw process exited before becoming ready. Recent logs:\n{}", tail))
+    }
+}
+
+#[tauri::command]
+async fn stop_local_copaw(
+    state: tauri::State<'_, AppState>,
+    port: Option<u16>,
+) -> Result<(), String> {
+    let port = port.unwrap_or_else(settings::copaw_port);
+    let mut pm = state.process_manager.lock().await;
+    let _ = pm.kill(COPAW_PROCESS_NAME);
+
+    // Also kill any process listening on the port
+    let killed = pm.kill_by_port(port);
+    if !killed.is_empty() {
+        println!("[Tauri] stop_local_copaw: killed {:?} on port {}", killed, port);
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct CopawLogLine {
+    kind: String, // "request", "error", "info"
+    line: String,
+}
+
+/// Classify a CoPaw output line. uvicorn access lines look like
+/// `INFO:     127.0.0.1:5000 - "POST /process HTTP/1.1" 200 OK`.
+fn classify_copaw_line(line: &str) -> &'static str {
+    let lower = line.to_lowercase();
+    let is_request = line.contains(" HTTP/");
+    let server_error = is_request
+        && line
+            .rsplit('"')
+            .next()
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(|code| code.parse::<u16>().ok())
+            .map(|code| code >= 500)
+            .unwrap_or(false);
+    if server_error
+        || lower.contains("error")
+        || lower.contains("traceback")
+        || lower.contains("exception")
+    {
+        "error"
+    } else if is_request {
+        "request"
+    } else {
+        "info"
+    }
+}
+
+/// CoPaw-focused view of the `local-copaw` process logs, with each line
+/// classified as a request, error, or informational line.
+#[tauri::command]
+async fn get_copaw_logs(
+    state: tauri::State<'_, AppState>,
+    lines: Option<usize>,
+) -> Result<Vec<CopawLogLine>, String> {
+    let pm = state.process_manager.lock().await;
+    let logs = pm
+        .get_logs(COPAW_PROCESS_NAME, lines.unwrap_or(100))
+        .map_err(|_| "CoPaw has not been started".to_string())?;
+    Ok(logs
+        .into_iter()
+        .map(|line| CopawLogLine {
+            kind: classify_copaw_line(&line).to_string(),
+            line,
+        })
+        .collect())
+}
+
+#[derive(Serialize)]
+struct LocalCopawStatus {
+    running: bool,
+    pid: Option<u32>,
+    port: u16,
+}
+
+#[tauri::command]
+async fn get_local_copaw_status(
+    state: tauri::State<'_, AppState>,
+    port: Option<u16>,
+) -> Result<LocalCopawStatus, String> {
+    let port = port.unwrap_or_else(settings::copaw_port);
+    let pm = state.process_manager.lock().await;
+    let mut running = pm.is_running(COPAW_PROCESS_NAME);
+    let mut pid = if running {
+        pm.list().into_iter().find(|(n, _)| n == COPAW_PROCESS_NAME).and_then(|(_, info)| info.1)
+    } else {
+        None
+    };
+
+    // Also check if any process is listening on the port
+    if !running {
+        if let Ok(output) = std::process::Command::new("lsof")
+            .args(&["-ti", &format!(":{}", port)])
+            .output()
+        {
+            let pids_str = String::from_utf8_lossy(&output.stdout);
+            if let Some(first_pid) = pids_str.split_whitespace().next() {
+                if let Ok(p) = first_pid.parse::<u32>() {
+                    running = true;
+                    pid = Some(p);
+                }
+            }
+        }
+    }
+
+    Ok(LocalCopawStatus { running, pid, port })
+}
+
+#[tauri::command]
+async fn check_local_copaw_installed() -> Result<bool, String> {
+    let home = dirs_next::home_dir().ok_or("Cannot find home directory")?;
+    let env_path = home.join(".agentos").join("copaw").join(".env");
+    Ok(env_path.exists())
+}
+
+// ── ClawHub skill management commands (desktop deploy mode) ──
+
+const CLAWHUB_SEARCH_TIMEOUT_SECS: u64 = 60;
+const CLAWHUB_INSTALL_TIMEOUT_SECS: u64 = 300;
+const NPM_UPGRADE_TIMEOUT_SECS: u64 = 600;
+
+/// Run an external command asynchronously with a timeout. The child is killed
+/// when the timeout elapses or when `cancel` is notified.
+async fn run_command_with_timeout(
+    program: &str,
+    args: &[String],
+    path: &str,
+    timeout_secs: u64,
+    cancel: Option<&tokio::sync::Notify>,
+) -> Result<std::process::Output, String> {
+    let child = tokio::process::Command::new(program)
+        .args(args)
+        .env("PATH", path)
+        .envs(http::proxy_envs())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+
+    let cancelled = async {
+        match cancel {
+            Some(notify) => notify.notified().await,
+            None => std::future::pending::<()>().await,
+        }
+    };
+
+    // Dropping the wait future drops the child, which kills it (kill_on_drop)
+    tokio::select! {
+        result = tokio::time::timeout(
+            std::time::Duration::from_secs(timeout_secs),
+            child.wait_with_output(),
+        ) => match result {
+            Ok(output) => output.map_err(|e| format!("{} failed: {}", program, e)),
+            Err(_) => Err(format!("{} timed out after {}s", program, timeout_secs)),
+        },
+        _ = cancelled => Err(format!("{} was cancelled", program)),
+    }
+}
+
+/// Abort any in-flight clawhub search/install or npm upgrade.
+#[tauri::command]
+async fn cancel_clawhub_operation(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    state.clawhub_cancel.notify_waiters();
+    Ok(())
+}
+
+/// Ensure clawhub CLI is installed. Auto-installs if missing.
+/// Returns true if clawhub is available after the check.
+#[tauri::command]
+async fn ensure_clawhub() -> Result<bool, String> {
+    let path = extended_path();
+    let check = std::process::Command::new("clawhub")
+        .arg("--cli-version")
+        .env("PATH", &path)
+        .output();
+    if check.map(|o| o.status.success()).unwrap_or(false) {
+        return Ok(true);
+    }
+    // Not installed — try to install
+    println!("[ensure_clawhub] clawhub not found, installing...");
+    let args = vec!["install".to_string(), "-g".to_string(), "clawhub".to_string()];
+    let result = run_command_with_timeout("npm", &args, &path, CLAWHUB_INSTALL_TIMEOUT_SECS, None).await?;
+    if result.status.success() {
+        println!("[ensure_clawhub] clawhub installed successfully");
+        Ok(true)
+    } else {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        println!("[ensure_clawhub] clawhub install failed: {}", stderr);
+        Ok(false)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ClawHubSkill {
+    name: String,
+    slug: String,
+    description: String,
+    author: String,
+    version: String,
+}
+
+/// Search or explore ClawHub marketplace skills via the clawhub CLI.
+/// Empty query = explore (list all); non-empty = search.
+#[tauri::command]
+async fn clawhub_search(
+    state: tauri::State<'_, AppState>,
+    query: String,
+    _user_id: String,
+    timeout_secs: Option<u64>,
+) -> Result<Vec<ClawHubSkill>, String> {
+    let path = extended_path();
+
+    let mut args: Vec<String> = if query.trim().is_empty() {
+        vec!["explore".into(), "--limit".into(), "100".into()]
+    } else {
+        vec!["search".into(), query.clone(), "--limit".into(), "30".into()]
+    };
+    let timeout = timeout_secs.unwrap_or(CLAWHUB_SEARCH_TIMEOUT_SECS);
+
+    // Ask for JSON; older clawhub versions without --json fall back to text output
+    args.push("--json".into());
+    let mut output = run_command_with_timeout("clawhub", &args, &path, timeout, Some(&state.clawhub_cancel)).await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).to_lowercase();
+        if stderr.contains("unknown option") || stderr.contains("unknown argument") {
+            args.pop();
+            output = run_command_with_timeout("clawhub", &args, &path, timeout, Some(&state.clawhub_cancel)).await?;
+        }
+    }
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("clawhub failed: {}", stderr));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let skills = parse_clawhub_output(&stdout);
+    Ok(skills)
+}
+
+/// Turn one JSON skill record from clawhub into a ClawHubSkill. Tolerates
+/// both flat fields and nested `owner`/`latestVersion` objects.
+fn clawhub_skill_from_json(item: &Value) -> Option<ClawHubSkill> {
+    let slug = item["slug"]
+        .as_str()
+        .or_else(|| item["name"].as_str())?
+        .to_string();
+    let author = item["author"]
+        .as_str()
+        .or_else(|| item["author"]["handle"].as_str())
+        .or_else(|| item["author"]["name"].as_str())
+        .or_else(|| item["owner"].as_str())
+        .or_else(|| item["owner"]["handle"].as_str())
+        .unwrap_or("")
+        .to_string();
+    let version = item["version"]
+        .as_str()
+        .or_else(|| item["latestVersion"].as_str())
+        .or_else(|| item["latestVersion"]["version"].as_str())
+        .unwrap_or("1.0.0")
+        .trim_start_matches('v')
+        .to_string();
+    Some(ClawHubSkill {
+        name: item["displayName"]
+            .as_str()
+            .or_else(|| item["name"].as_str())
+            .unwrap_or(&slug)
+            .to_string(),
+        slug: slug.clone(),
+        description: item["description"]
+            .as_str()
+            .or_else(|| item["summary"].as_str())
+            .unwrap_or("")
+            .to_string(),
+        author,
+        version,
+    })
+}
+
+/// Parse clawhub CLI output into structured skill list.
+fn parse_clawhub_output(output: &str) -> Vec<ClawHubSkill> {
+    if output.trim().is_empty() {
+        return vec![];
+    }
+
+    // Try JSON first: a bare array, a `{ "skills": [...] }`-style wrapper, or a single object
+    if let Ok(parsed) = serde_json::from_str::<Value>(output.trim()) {
+        let items: Vec<Value> = match parsed {
+            Value::Array(items) => items,
+            Value::Object(ref obj) => ["skills", "results", "items"]
+                .iter()
+                .find_map(|key| obj.get(*key).and_then(|v| v.as_array()).cloned())
+                .unwrap_or_else(|| vec![parsed.clone()]),
+            _ => vec![],
+        };
+        return items.iter().filter_map(clawhub_skill_from_json).collect();
+    }
+
+    // Parse line-by-line text output
+    let slug_re = regex::Regex::new(r"^[a-z0-9][a-z0-9-]*$").unwrap();
+    let mut skills = vec![];
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('-') {
+            continue;
+        }
+
+        // Split on 2+ whitespace
+        let parts: Vec<&str> = trimmed.split("  ").filter(|s| !s.is_empty()).map(|s| s.trim()).collect();
+        if parts.len() < 2 {
+            continue;
+        }
+
+        let first = parts[0];
+        // Try "slug vX.Y.Z" format
+        let slug_version_re = regex::Regex::new(r"^([a-z0-9][a-z0-9-]*)\s+v(\d+\.\d+\.\d+)$").unwrap();
+        let (slug, version) = if let Some(caps) = slug_version_re.captures(first) {
+            (caps[1].to_string(), caps[2].to_string())
+        } else if slug_re.is_match(first) {
+            let ver = parts.get(1)
+                .and_then(|p| p.strip_prefix('v'))
+                .and_then(|v| if v.contains('.') { Some(v.to_string()) } else { None })
+                .unwrap_or_else(|| "1.0.0".to_string());
+            (first.to_string(), ver)
+        } else {
+            continue;
+        };
+
+        // Remaining columns: version/time fields are skipped, "by <author>" or
+        // "@handle" is the author, everything else is description
+        let mut author = String::new();
+        let mut desc_parts: Vec<&str> = Vec::new();
+        for p in parts[1..].iter().map(|p| p.trim()) {
+            if p.starts_with('v') && p[1..].contains('.') { continue; }
+            if p.ends_with(" ago") || p == "just now" { continue; }
+            if let Some(a) = p.strip_prefix("by ").or_else(|| p.strip_prefix('@').filter(|a| !a.contains(' '))) {
+                if author.is_empty() {
+                    author = a.trim_start_matches('@').to_string();
+                    continue;
+                }
+            }
+            desc_parts.push(p);
+        }
+
+        skills.push(ClawHubSkill {
+            name: slug.clone(),
+            slug,
+            description: desc_parts.join("  "),
+            author,
+            version,
+        });
+    }
+
+    skills
+}
+
+/// Files a `clawhub_install` of `slug` may add: the skill's own directory and
+/// clawhub's `.clawhub` metadata. Nothing else in the workspace is touched.
+fn install_scope(slug: &str) -> [std::path::PathBuf; 2] {
+    [std::path::Path::new("skills").join(slug), std::path::PathBuf::from(".clawhub")]
+}
+
+/// clawhub's lock file, restored rather than deleted on rollback since it is
+/// shared by every installed skill.
+const CLAWHUB_LOCK: &str = ".clawhub/lock.json";
+
+/// What a failed `clawhub_install` added, so `rollback_skill_install` can
+/// remove exactly that.
+struct InstallRollback {
+    id: String,
+    workspace: std::path::PathBuf,
+    /// Entries (relative to the workspace) that did not exist before the install.
+    added: Vec<std::path::PathBuf>,
+    /// Lock file contents before and right after the failed attempt.
+    lock_before: Option<Vec<u8>>,
+    lock_after: Option<Vec<u8>>,
+}
+
+/// Rollback record of the latest failed install per (user id, slug).
+static SKILL_INSTALL_SNAPSHOTS: std::sync::Mutex<Option<HashMap<(String, String), InstallRollback>>> =
+    std::sync::Mutex::new(None);
+
+/// Relative paths of `slug`'s install scope under `workspace` (the scope roots
+/// included when present). Symlinks are recorded but not followed.
+fn snapshot_entries(workspace: &std::path::Path, slug: &str) -> std::collections::HashSet<std::path::PathBuf> {
+    let mut entries = std::collections::HashSet::new();
+    let mut stack = Vec::new();
+    for rel in install_scope(slug) {
+        if std::fs::symlink_metadata(workspace.join(&rel)).is_ok() {
+            entries.insert(rel.clone());
+            stack.push(workspace.join(rel));
+        }
+    }
+    while let Some(dir) = stack.pop() {
+        let Ok(read) = std::fs::read_dir(&dir) else { continue };
+        for entry in read.flatten() {
+            let path = entry.path();
+            if let Ok(rel) = path.strip_prefix(workspace) {
+                entries.insert(rel.to_path_buf());
+            }
+            if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                stack.push(path);
+            }
+        }
+    }
+    entries
+}
+
+/// Install a ClawHub skill into the user's local workspace. If the install
+/// fails, the error names a snapshot that `rollback_skill_install` reverts.
+#[tauri::command]
+async fn clawhub_install(
+    state: tauri::State<'_, AppState>,
+    slug: String,
+    user_id: String,
+    timeout_secs: Option<u64>,
+) -> Result<(), String> {
+    let workspace = user_workspace_dir(&user_id)?;
+    let path = extended_path();
+
+    let snapshot_id = format!(
+        "{}-{}",
+        slug,
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0)
+    );
+    let before = snapshot_entries(&workspace, &slug);
+    let lock_before = std::fs::read(workspace.join(CLAWHUB_LOCK)).ok();
+    let key = (user_id.clone(), slug.clone());
+
+    // Record what this attempt added while it is still the only change
+    let record_failure = |e: String| {
+        let mut added: Vec<_> = snapshot_entries(&workspace, &slug)
+            .difference(&before)
+            .cloned()
+            .collect();
+        added.retain(|rel| rel != std::path::Path::new(CLAWHUB_LOCK));
+        SKILL_INSTALL_SNAPSHOTS.lock().unwrap().get_or_insert_with(HashMap::new).insert(
+            key.clone(),
+            InstallRollback {
+                id: snapshot_id.clone(),
+                workspace: workspace.clone(),
+                added,
+                lock_before: lock_before.clone(),
+                lock_after: std::fs::read(workspace.join(CLAWHUB_LOCK)).ok(),
+            },
+        );
+        format!("{} (rollback snapshot: {})", e, snapshot_id)
+    };
+
+    let args = vec![
+        "install".to_string(),
+        slug.clone(),
+        "--workdir".to_string(),
+        workspace.to_str().ok_or("Invalid workspace path")?.to_string(),
+        "--force".to_string(),
+        "--no-input".to_string(),
+    ];
+    let output = run_command_with_timeout(
+        "clawhub",
+        &args,
+        &path,
+        timeout_secs.unwrap_or(CLAWHUB_INSTALL_TIMEOUT_SECS),
+        Some(&state.clawhub_cancel),
+    ).await
+    .map_err(record_failure)?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(record_failure(format!("clawhub install failed: {}", stderr)));
+    }
+
+    // A successful install supersedes any earlier failed attempt
+    if let Some(snapshots) = SKILL_INSTALL_SNAPSHOTS.lock().unwrap().as_mut() {
+        snapshots.remove(&key);
+    }
+
+    if let Ok(skill_dir) = workspace.join("skills").join(&slug).canonicalize() {
+        mark_skill_scripts_executable(&skill_dir);
+    }
+
+    println!("[Tauri] clawhub_install: installed '{}' for user '{}'", slug, user_id);
+    Ok(())
+}
+
+/// Undo a failed `clawhub_install` of `slug`: remove the files it added under
+/// `skills/<slug>` and `.clawhub`, and restore clawhub's lock file unless it
+/// has changed since. Files overwritten by `--force` are left as they are.
+/// When `snapshot_id` is given it must match the latest failed install.
+/// Returns the removed (or restored) paths, relative to the workspace.
+#[tauri::command]
+async fn rollback_skill_install(
+    slug: String,
+    user_id: String,
+    snapshot_id: Option<String>,
+) -> Result<Vec<String>, String> {
+    let snapshot = {
+        let mut snapshots = SKILL_INSTALL_SNAPSHOTS.lock().unwrap();
+        let snapshots = snapshots.get_or_insert_with(HashMap::new);
+        let key = (user_id.clone(), slug.clone());
+        match snapshots.get(&key) {
+            None => return Err(format!("No failed install of '{}' to roll back", slug)),
+            Some(snap) if snapshot_id.as_ref().is_some_and(|id| *id != snap.id) => {
+                return Err(format!("Snapshot {} is not the latest install of '{}'", snapshot_id.unwrap(), slug));
+            }
+            Some(_) => snapshots.remove(&key).unwrap(),
+        }
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let mut removed = Vec::new();
+        let lock_path = snapshot.workspace.join(CLAWHUB_LOCK);
+        if std::fs::read(&lock_path).ok() == snapshot.lock_after && snapshot.lock_before != snapshot.lock_after {
+            let result = match snapshot.lock_before {
+                Some(ref contents) => std::fs::write(&lock_path, contents),
+                None => std::fs::remove_file(&lock_path),
+            };
+            match result {
+                Ok(()) => removed.push(CLAWHUB_LOCK.to_string()),
+                Err(e) => println!("[Tauri] rollback_skill_install: failed to restore {}: {}", lock_path.display(), e),
+            }
+        }
+
+        let mut added = snapshot.added;
+        // Deepest first, so directories are emptied before they are removed
+        added.sort_by_key(|p| std::cmp::Reverse(p.components().count()));
+
+        for rel in added {
+            let path = snapshot.workspace.join(&rel);
+            let Ok(meta) = std::fs::symlink_metadata(&path) else { continue };
+            // Directories are only removed once empty, keeping anything written there since
+            let result = if meta.is_dir() { std::fs::remove_dir(&path) } else { std::fs::remove_file(&path) };
+            match result {
+                Ok(()) => removed.push(rel.to_string_lossy().to_string()),
+                Err(e) => println!("[Tauri] rollback_skill_install: failed to remove {}: {}", path.display(), e),
+            }
+        }
+
+        println!(
+            "[Tauri] rollback_skill_install: removed {} entries for '{}' (user '{}')",
+            removed.len(),
+            slug,
+            user_id
+        );
+        removed
+    })
+    .await
+    .map_err(|e| format!("Rollback task failed: {}", e))
+}
+
+/// Workspace directory of a user's OpenClaw agent: the root configured with
+/// `set_workspace_root`, or `~/.agentos/openclaw/users/<uid>/workspace`.
+fn user_workspace_dir(user_id: &str) -> Result<std::path::PathBuf, String> {
+    if let Some(root) = settings::workspace_root(user_id) {
+        return Ok(std::path::PathBuf::from(root));
+    }
+    default_workspace_dir(user_id)
+}
+
+fn default_workspace_dir(user_id: &str) -> Result<std::path::PathBuf, String> {
+    Ok(openclaw_config_dir(Some(user_id))?.join("workspace"))
+}
+
+/// Move every entry of `from` into `to`, renaming where possible and copying
+/// across filesystems. Refuses up front if any name already exists in `to`.
+fn move_dir_contents(from: &std::path::Path, to: &std::path::Path) -> Result<usize, String> {
+    let entries: Vec<_> = std::fs::read_dir(from)
+        .map_err(|e| format!("Failed to read {}: {}", from.display(), e))?
+        .flatten()
+        .collect();
+    if let Some(conflict) = entries.iter().find(|e| to.join(e.file_name()).exists()) {
+        return Err(format!(
+            "Cannot move workspace: {} already exists",
+            to.join(conflict.file_name()).display()
+        ));
+    }
+    for entry in &entries {
+        let src = entry.path();
+        let dest = to.join(entry.file_name());
+        if std::fs::rename(&src, &dest).is_ok() {
+            continue;
+        }
+        let copied = if src.is_dir() {
+            copy_dir_recursive(&src, &dest).and_then(|_| std::fs::remove_dir_all(&src))
+        } else {
+            std::fs::copy(&src, &dest).and_then(|_| std::fs::remove_file(&src))
+        };
+        copied.map_err(|e| format!("Failed to move {}: {}", src.display(), e))?;
+    }
+    Ok(entries.len())
+}
+
+/// Current workspace directory of a user.
+#[tauri::command]
+fn get_workspace_root(user_id: String) -> Result<String, String> {
+    Ok(user_workspace_dir(&user_id)?.to_string_lossy().to_string())
+}
+
+/// Relocate a user's workspace (skills, agent files), e.g. to an external
+/// disk. `None` restores the default location. With `move_contents` the
+/// existing workspace is moved over; otherwise the new directory is used as
+/// is. The user's openclaw.json is updated, so a running gateway needs a
+/// restart to pick it up. Returns the new workspace path.
+#[tauri::command]
+fn set_workspace_root(user_id: String, path: Option<String>, move_contents: Option<bool>) -> Result<String, String> {
+    let old = user_workspace_dir(&user_id)?;
+    let custom = path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    let new = match custom {
+        Some(ref p) => {
+            let p = std::path::PathBuf::from(p);
+            if !p.is_absolute() {
+                return Err(format!("Workspace root must be an absolute path: {}", p.display()));
+            }
+            p
+        }
+        None => default_workspace_dir(&user_id)?,
+    };
+
+    std::fs::create_dir_all(&new)
+        .map_err(|e| format!("Failed to create workspace {}: {}", new.display(), e))?;
+    let new = new
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace {}: {}", new.display(), e))?;
+    let old_canonical = old.canonicalize().ok();
+
+    if let Some(ref old_dir) = old_canonical {
+        if *old_dir != new && (new.starts_with(old_dir) || old_dir.starts_with(&new)) {
+            return Err("The new workspace cannot be inside the current one, or contain it".to_string());
+        }
+        if *old_dir != new && move_contents.unwrap_or(false) {
+            let moved = move_dir_contents(old_dir, &new)?;
+            println!("[Tauri] set_workspace_root: moved {} entries from {}", moved, old_dir.display());
+        }
+    }
+
+    let stored = custom.map(|_| new.to_string_lossy().to_string());
+    settings::update(|s| match stored {
+        Some(ref root) => {
+            s.workspace_roots.insert(user_id.clone(), root.clone());
+        }
+        None => {
+            s.workspace_roots.remove(&user_id);
+        }
+    })?;
+
+    // Point the user's gateway at the new workspace
+    let config_path = openclaw_config_dir(Some(&user_id))?.join("openclaw.json");
+    if let Ok(content) = std::fs::read_to_string(&config_path) {
+        if let Ok(mut config) = serde_json::from_str::<Value>(&content) {
+            config["agents"]["defaults"]["workspace"] = serde_json::json!(new.to_string_lossy());
+            std::fs::write(&config_path, serde_json::to_string_pretty(&config).unwrap())
+                .map_err(|e| format!("Failed to update openclaw.json: {}", e))?;
+        }
+    }
+
+    println!("[Tauri] set_workspace_root: '{}' -> {}", user_id, new.display());
+    Ok(new.to_string_lossy().to_string())
+}
+
+#[derive(Serialize)]
+struct ProfileClone {
+    config_dir: String,
+    workspace: String,
+    token: String,
+    gateway_port: u16,
+}
+
+/// Replace the leading path `from` with `to` in every string in a JSON value.
+/// Matching is by path component, so `/users/abc` leaves `/users/abcdef` alone.
+fn rewrite_path_prefix(value: &mut Value, from: &std::path::Path, to: &std::path::Path) {
+    match value {
+        Value::String(s) => {
+            if let Ok(rest) = std::path::Path::new(s.as_str()).strip_prefix(from) {
+                let rewritten = if rest.as_os_str().is_empty() { to.to_path_buf() } else { to.join(rest) };
+                *s = rewritten.to_string_lossy().to_string();
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| rewrite_path_prefix(v, from, to)),
+        Value::Object(map) => map.values_mut().for_each(|v| rewrite_path_prefix(v, from, to)),
+        _ => {}
+    }
+}
+
+/// First port above `after` that is free and not configured as the gateway
+/// port of another OpenClaw profile.
+fn free_gateway_port(users_dir: &std::path::Path, after: u16) -> Result<u16, String> {
+    let taken: std::collections::HashSet<u16> = std::fs::read_dir(users_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| std::fs::read_to_string(e.path().join("openclaw.json")).ok())
+                .filter_map(|c| serde_json::from_str::<Value>(&c).ok())
+                .filter_map(|c| c["gateway"]["port"].as_u64().map(|p| p as u16))
+                .collect()
+        })
+        .unwrap_or_default();
+    (after.saturating_add(1)..=u16::MAX)
+        .find(|p| !taken.contains(p) && port_status(*p).available)
+        .ok_or_else(|| "No free port for the cloned gateway".to_string())
+}
+
+/// Copy a user's OpenClaw profile (config, auth profiles, state) to a new
+/// user id so a different provider or model can be tried without touching
+/// the original. The workspace is copied too unless `copy_workspace` is
+/// false, in which case the clone starts with an empty one at the default
+/// location. Paths in openclaw.json are rewritten to the clone's directories
+/// and it gets its own gateway token and port.
+#[tauri::command]
+fn clone_openclaw_profile(
+    source_user_id: String,
+    new_user_id: String,
+    copy_workspace: Option<bool>,
+) -> Result<ProfileClone, String> {
+    if new_user_id.is_empty() || new_user_id.contains(['/', '\\']) || new_user_id.starts_with('.') {
+        return Err(format!("Invalid user id '{}'", new_user_id));
+    }
+    let src_dir = openclaw_config_dir(Some(&source_user_id))?;
+    if !src_dir.join("openclaw.json").exists() {
+        return Err(format!("No OpenClaw profile for '{}'", source_user_id));
+    }
+    let dest_dir = openclaw_config_dir(Some(&new_user_id))?;
+    if dest_dir.exists() {
+        return Err(format!("Profile '{}' already exists", new_user_id));
+    }
+    let src_workspace = user_workspace_dir(&source_user_id)?;
+    let dest_workspace = default_workspace_dir(&new_user_id)?;
+
+    let copy = || -> Result<(String, u16), String> {
+        std::fs::create_dir_all(&dest_dir)
+            .map_err(|e| format!("Failed to create {}: {}", dest_dir.display(), e))?;
+        // The workspace is handled separately, wherever it lives
+        let entries = std::fs::read_dir(&src_dir)
+            .map_err(|e| format!("Failed to read {}: {}", src_dir.display(), e))?;
+        for entry in entries.flatten().filter(|e| e.file_name() != "workspace") {
+            let dest = dest_dir.join(entry.file_name());
+            let copied = if entry.path().is_dir() {
+                copy_dir_recursive(&entry.path(), &dest)
+            } else {
+                std::fs::copy(entry.path(), &dest).map(|_| ())
+            };
+            copied.map_err(|e| format!("Failed to copy {}: {}", entry.path().display(), e))?;
+        }
+        if copy_workspace.unwrap_or(true) && src_workspace.is_dir() {
+            copy_dir_recursive(&src_workspace, &dest_workspace)
+                .map_err(|e| format!("Failed to copy workspace: {}", e))?;
+        } else {
+            std::fs::create_dir_all(&dest_workspace)
+                .map_err(|e| format!("Failed to create workspace: {}", e))?;
+        }
+
+        let config_path = dest_dir.join("openclaw.json");
+        let content = std::fs::read_to_string(&config_path)
+            .map_err(|e| format!("Failed to read config: {}", e))?;
+        let mut config: Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse config: {}", e))?;
+        rewrite_path_prefix(&mut config, &src_workspace, &dest_workspace);
+        rewrite_path_prefix(&mut config, &src_dir, &dest_dir);
+        config["agents"]["defaults"]["workspace"] = serde_json::json!(dest_workspace.to_string_lossy());
+        let token = generate_gateway_token();
+        config["gateway"]["auth"]["token"] = serde_json::json!(&token);
+        // Give the clone its own port so both profiles can run at once
+        let src_port = config["gateway"]["port"].as_u64().unwrap_or(settings::DEFAULT_OPENCLAW_PORT as u64) as u16;
+        let users_dir = dest_dir.parent().ok_or("Invalid profile directory")?;
+        let port = free_gateway_port(users_dir, src_port)?;
+        config["gateway"]["port"] = serde_json::json!(port);
+        std::fs::write(&config_path, serde_json::to_string_pretty(&config).unwrap())
+            .map_err(|e| format!("Failed to write config: {}", e))?;
+        Ok((token, port))
+    };
+
+    // Don't leave a half-copied profile behind
+    let (token, gateway_port) = copy().inspect_err(|_| {
+        let _ = std::fs::remove_dir_all(&dest_dir);
+    })?;
+
+    println!("[Tauri] clone_openclaw_profile: '{}' -> '{}'", source_user_id, new_user_id);
+    Ok(ProfileClone {
+        config_dir: dest_dir.to_string_lossy().to_string(),
+        workspace: dest_workspace.to_string_lossy().to_string(),
+        token,
+        gateway_port,
+    })
+}
+
+/// Parse the `key: value` lines of a SKILL.md YAML frontmatter block.
+/// Only flat scalar keys are read; quotes around values are stripped.
+fn parse_skill_frontmatter(content: &str) -> HashMap<String, String> {
+    let mut fields = HashMap::new();
+    let mut lines = content.lines();
+    if lines.next().map(|l| l.trim()) != Some("---") {
+        return fields;
+    }
+    for line in lines {
+        if line.trim() == "---" {
+            break;
+        }
+        if line.starts_with(' ') || line.starts_with('\t') {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            let value = value.trim().trim_matches('"').trim_matches('\'').to_string();
+            if !value.is_empty() {
+                fields.insert(key.trim().to_string(), value);
+            }
+        }
+    }
+    fields
+}
+
+#[derive(Serialize)]
+struct InstalledSkill {
+    name: String,
+    version: Option<String>,
+    source: String, // "clawhub" or "local"
+    path: String,
+    description: Option<String>,
+}
+
+/// List the skills installed in a user's workspace by reading each skill's
+/// SKILL.md. Works offline; ClawHub installs are recognised from clawhub's lockfile.
+#[tauri::command]
+async fn list_installed_skills(user_id: String) -> Result<Vec<InstalledSkill>, String> {
+    let workspace = user_workspace_dir(&user_id)?;
+    let skills_dir = workspace.join("skills");
+    if !skills_dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let lock: Value = std::fs::read_to_string(workspace.join(".clawhub").join("lock.json"))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or(Value::Null);
+
+    let mut skills = Vec::new();
+    let entries = std::fs::read_dir(&skills_dir)
+        .map_err(|e| format!("Failed to read skills directory: {}", e))?;
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        let skill_md = dir.join("SKILL.md");
+        if !dir.is_dir() || !skill_md.is_file() {
+            continue;
+        }
+        let slug = entry.file_name().to_string_lossy().to_string();
+        let fields = std::fs::read_to_string(&skill_md)
+            .map(|c| parse_skill_frontmatter(&c))
+            .unwrap_or_default();
+
+        let locked = &lock["skills"][&slug];
+        let from_clawhub = !locked.is_null() || dir.join(".clawhub").exists();
+        let version = fields
+            .get("version")
+            .cloned()
+            .or_else(|| locked["version"].as_str().map(String::from));
+
+        skills.push(InstalledSkill {
+            name: fields.get("name").cloned().unwrap_or_else(|| slug.clone()),
+            version,
+            source: if from_clawhub { "clawhub" } else { "local" }.to_string(),
+            path: dir.to_string_lossy().to_string(),
+            description: fields.get("description").cloned(),
+        });
+    }
+    skills.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(skills)
+}
+
+#[derive(Serialize)]
+struct SkillUsage {
+    name: String,
+    bytes: u64,
+}
+
+#[derive(Serialize)]
+struct WorkspaceUsage {
+    total_bytes: u64,
+    /// Largest first.
+    skills: Vec<SkillUsage>,
+}
+
+/// Sum the sizes of regular files under `dir`. Symlinks are never followed,
+/// so nothing is counted twice or outside the tree.
+fn dir_usage(dir: &std::path::Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else { return 0 };
+    entries
+        .flatten()
+        .map(|entry| match std::fs::symlink_metadata(entry.path()) {
+            Ok(meta) if meta.is_dir() => dir_usage(&entry.path()),
+            Ok(meta) if meta.is_file() => meta.len(),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Disk usage of a user's workspace and of each installed skill, so the UI
+/// can point at the largest skills. A symlinked skill is measured at its
+/// target only when that lies inside the workspace.
+#[tauri::command]
+async fn get_workspace_usage(user_id: String) -> Result<WorkspaceUsage, String> {
+    let workspace = user_workspace_dir(&user_id)?;
+    if !workspace.is_dir() {
+        return Ok(WorkspaceUsage { total_bytes: 0, skills: vec![] });
+    }
+    let workspace_root = workspace
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut skills = Vec::new();
+        let mut measured = std::collections::HashSet::new();
+        if let Ok(entries) = std::fs::read_dir(workspace_root.join("skills")) {
+            for entry in entries.flatten() {
+                let Ok(target) = entry.path().canonicalize() else { continue };
+                if !target.is_dir() || !target.starts_with(&workspace_root) || !measured.insert(target.clone()) {
+                    continue;
+                }
+                skills.push(SkillUsage {
+                    name: entry.file_name().to_string_lossy().to_string(),
+                    bytes: dir_usage(&target),
+                });
+            }
+        }
+        skills.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+        WorkspaceUsage { total_bytes: dir_usage(&workspace_root), skills }
+    })
+    .await
+    .map_err(|e| format!("Failed to measure workspace: {}", e))
+}
+
+/// Limits for `read_skill_source`.
+const SKILL_SOURCE_MAX_FILES: usize = 200;
+const SKILL_SOURCE_PREVIEW_BYTES: usize = 2048;
+
+#[derive(Serialize)]
+struct SkillSourceFile {
+    path: String,
+    size: u64,
+    /// First few KB of text files; None for binary files and directories.
+    preview: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SkillSource {
+    skill_md: String,
+    files: Vec<SkillSourceFile>,
+    truncated: bool,
+}
+
+/// Read at most `SKILL_SOURCE_PREVIEW_BYTES` of a file; None for binary files.
+fn read_skill_preview(path: &std::path::Path) -> Option<String> {
+    use std::io::Read;
+    let mut head = Vec::new();
+    std::fs::File::open(path)
+        .ok()?
+        .take(SKILL_SOURCE_PREVIEW_BYTES as u64)
+        .read_to_end(&mut head)
+        .ok()?;
+    if head.contains(&0) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&head).to_string())
+}
+
+/// Collect files under a skill directory without following symlinks.
+fn collect_skill_files(root: &std::path::Path, dir: &std::path::Path, files: &mut Vec<SkillSourceFile>, truncated: &mut bool) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    let mut entries: Vec<_> = entries.flatten().collect();
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        if files.len() >= SKILL_SOURCE_MAX_FILES {
+            *truncated = true;
+            return;
+        }
+        let path = entry.path();
+        let Ok(meta) = std::fs::symlink_metadata(&path) else { continue };
+        let rel = path.strip_prefix(root).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+        if meta.is_dir() {
+            collect_skill_files(root, &path, files, truncated);
+            continue;
+        }
+        let preview = if meta.is_file() {
+            read_skill_preview(&path)
+        } else {
+            None
+        };
+        files.push(SkillSourceFile { path: rel, size: meta.len(), preview });
+    }
+}
+
+/// Resolve an installed skill's directory, refusing anything (including a
+/// symlinked skill) that ends up outside the workspace skills directory.
+fn resolve_skill_dir(user_id: &str, skill_name: &str) -> Result<std::path::PathBuf, String> {
+    let skills_dir = user_workspace_dir(user_id)?.join("skills");
+    let skills_root = skills_dir
+        .canonicalize()
+        .map_err(|_| "No skills installed".to_string())?;
+    let skill_dir = skills_dir
+        .join(skill_name)
+        .canonicalize()
+        .map_err(|_| format!("Skill '{}' not found", skill_name))?;
+    if !skill_dir.starts_with(&skills_root) || skill_dir == skills_root || !skill_dir.is_dir() {
+        return Err(format!("Skill '{}' is outside the workspace skills directory", skill_name));
+    }
+    Ok(skill_dir)
+}
+
+/// Return a skill's SKILL.md and a listing of its files with short previews,
+/// so users can review a skill before trusting it.
+#[tauri::command]
+async fn read_skill_source(user_id: String, skill_name: String) -> Result<SkillSource, String> {
+    let skill_dir = resolve_skill_dir(&user_id, &skill_name)?;
+
+    let skill_md_path = skill_dir.join("SKILL.md");
+    let meta = std::fs::symlink_metadata(&skill_md_path)
+        .map_err(|e| format!("Failed to read SKILL.md: {}", e))?;
+    if !meta.is_file() {
+        return Err("SKILL.md is not a regular file".to_string());
+    }
+    let skill_md = std::fs::read_to_string(&skill_md_path)
+        .map_err(|e| format!("Failed to read SKILL.md: {}", e))?;
+    let mut files = Vec::new();
+    let mut truncated = false;
+    collect_skill_files(&skill_dir, &skill_dir, &mut files, &mut truncated);
+
+    Ok(SkillSource { skill_md, files, truncated })
+}
+
+const SKILL_TEST_TIMEOUT_SECS: u64 = 60;
+
+#[derive(Serialize)]
+struct SkillValidation {
+    passed: bool,
+    /// "test" when a declared test command ran, "structural" otherwise.
+    kind: String,
+    output: String,
+    problems: Vec<String>,
+}
+
+/// Relative file references in SKILL.md: markdown links and `{baseDir}/...` paths.
+fn skill_file_references(skill_md: &str) -> Vec<String> {
+    let link_re = regex::Regex::new(r"\]\(([^)\s]+)\)").unwrap();
+    let base_re = regex::Regex::new(r"\{baseDir\}/([A-Za-z0-9_./-]+)").unwrap();
+    let mut refs: Vec<String> = link_re
+        .captures_iter(skill_md)
+        .map(|c| c[1].to_string())
+        .filter(|r| !r.contains("://") && !r.starts_with('#') && !r.starts_with("mailto:"))
+        .chain(base_re.captures_iter(skill_md).map(|c| c[1].to_string()))
+        .map(|r| r.split('#').next().unwrap_or("").to_string())
+        .filter(|r| !r.is_empty())
+        .collect();
+    refs.sort();
+    refs.dedup();
+    refs
+}
+
+/// Validate a skill before enabling it. If SKILL.md declares a `test:` (or
+/// `validate:`) command it is run in the skill directory with a minimal
+/// environment and a timeout, in its own process group that is killed as a
+/// whole afterwards. The command runs with the user's privileges, so it only
+/// runs once the user approved that exact command (`approve_test`); until
+/// then the result has kind "needs_approval" and the command as output.
+/// Without a test command, SKILL.md and the files it references are checked
+/// structurally.
+#[tauri::command]
+async fn validate_skill(
+    user_id: String,
+    skill_name: String,
+    approve_test: Option<bool>,
+) -> Result<SkillValidation, String> {
+    let skill_dir = resolve_skill_dir(&user_id, &skill_name)?;
+    let skill_md = match std::fs::read_to_string(skill_dir.join("SKILL.md")) {
+        Ok(content) => content,
+        Err(_) => {
+            return Ok(SkillValidation {
+                passed: false,
+                kind: "structural".to_string(),
+                output: String::new(),
+                problems: vec!["SKILL.md is missing".to_string()],
+            })
+        }
+    };
+    let fields = parse_skill_frontmatter(&skill_md);
+
+    if let Some(test_cmd) = fields.get("test").or_else(|| fields.get("validate")) {
+        let approval_key = format!("{}/{}", user_id, skill_name);
+        if approve_test.unwrap_or(false) {
+            settings::update(|s| {
+                s.approved_skill_tests.insert(approval_key.clone(), test_cmd.clone());
+            })?;
+        }
+        if settings::approved_skill_test(&approval_key).as_ref() != Some(test_cmd) {
+            return Ok(SkillValidation {
+                passed: false,
+                kind: "needs_approval".to_string(),
+                output: test_cmd.clone(),
+                problems: vec![
+                    "SKILL.md declares a test command, which runs with your privileges; review it and approve it to run".to_string(),
+                ],
+            });
+        }
+
+        println!("[Tauri] validate_skill: running test for '{}': {}", skill_name, test_cmd);
+        let (shell, flag) = if cfg!(target_os = "windows") { ("cmd", "/C") } else { ("sh", "-c") };
+        let mut cmd = tokio::process::Command::new(shell);
+        cmd.arg(flag)
+            .arg(test_cmd)
+            .current_dir(&skill_dir)
+            .env_clear()
+            .env("PATH", extended_path())
+            .env("SKILL_DIR", &skill_dir)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true);
+        // Own process group, so anything the test starts is killed with it
+        #[cfg(unix)]
+        cmd.process_group(0);
+        #[cfg(target_os = "windows")]
+        if let Ok(root) = std::env::var("SystemRoot") {
+            cmd.env("SystemRoot", root);
+        }
+        let child = cmd.spawn().map_err(|e| format!("Failed to run skill test: {}", e))?;
+        let pid = child.id();
+        let result = tokio::time::timeout(
+            std::time::Duration::from_secs(SKILL_TEST_TIMEOUT_SECS),
+            child.wait_with_output(),
+        ).await;
+        if let Some(pid) = pid {
+            process_manager::kill_process_group(pid);
+        }
+        return Ok(match result {
+            Ok(Ok(out)) => SkillValidation {
+                passed: out.status.success(),
+                kind: "test".to_string(),
+                output: format!("{}{}", String::from_utf8_lossy(&out.stdout), String::from_utf8_lossy(&out.stderr)),
+                problems: if out.status.success() {
+                    vec![]
+                } else {
+                    vec![format!("Test command exited with {}", out.status)]
+                },
+            },
+            Ok(Err(e)) => return Err(format!("Skill test failed to run: {}", e)),
+            Err(_) => SkillValidation {
+                passed: false,
+                kind: "test".to_string(),
+                output: String::new(),
+                problems: vec![format!("Test command timed out after {}s", SKILL_TEST_TIMEOUT_SECS)],
+            },
+        });
+    }
+
+    let mut problems = Vec::new();
+    if !skill_md.trim_start().starts_with("---") {
+        problems.push("SKILL.md has no frontmatter".to_string());
+    }
+    for key in ["name", "description"] {
+        if !fields.contains_key(key) {
+            problems.push(format!("SKILL.md frontmatter is missing '{}'", key));
+        }
+    }
+    for reference in skill_file_references(&skill_md) {
+        let target = skill_dir.join(&reference);
+        let inside = target.canonicalize().map(|t| t.starts_with(&skill_dir)).unwrap_or(false);
+        if !target.exists() {
+            problems.push(format!("Referenced file not found: {}", reference));
+        } else if !inside {
+            problems.push(format!("Referenced file is outside the skill: {}", reference));
+        }
+    }
+
+    Ok(SkillValidation {
+        passed: problems.is_empty(),
+        kind: "structural".to_string(),
+        output: String::new(),
+        problems,
+    })
+}
+
+const JSON_SCHEMA_TYPES: &[&str] = &["object", "array", "string", "number", "integer", "boolean", "null"];
+
+#[derive(Serialize)]
+struct LintIssue {
+    /// "error" or "warning".
+    severity: String,
+    message: String,
+    /// "SKILL.md:<line>", or "manifest:" followed by a path into the
+    /// custom manifest's function schemas.
+    location: String,
+}
+
+fn lint_issue(issues: &mut Vec<LintIssue>, severity: &str, location: String, message: String) {
+    issues.push(LintIssue { severity: severity.to_string(), message, location });
+}
+
+/// Check a JSON Schema node against the basics tools rely on: a known `type`,
+/// object `properties`, `required` naming real properties, `items` on arrays
+/// and a non-empty `enum`. Recurses into properties and items.
+fn lint_schema(schema: &serde_json::Value, location: &str, issues: &mut Vec<LintIssue>) {
+    let Some(obj) = schema.as_object() else {
+        lint_issue(issues, "error", location.to_string(), "Schema must be an object".to_string());
+        return;
+    };
+
+    let types: Vec<&str> = match obj.get("type") {
+        None => {
+            lint_issue(issues, "warning", location.to_string(), "Schema has no 'type'".to_string());
+            vec![]
+        }
+        Some(serde_json::Value::String(t)) => vec![t.as_str()],
+        Some(serde_json::Value::Array(ts)) if ts.iter().all(|t| t.is_string()) => {
+            ts.iter().filter_map(|t| t.as_str()).collect()
+        }
+        Some(other) => {
+            lint_issue(issues, "error", location.to_string(), format!("'type' must be a string or array of strings, got {}", other));
+            vec![]
+        }
+    };
+    for t in &types {
+        if !JSON_SCHEMA_TYPES.contains(t) {
+            lint_issue(issues, "error", location.to_string(), format!("Unknown type '{}'", t));
+        }
+    }
+
+    let properties = match obj.get("properties") {
+        None => None,
+        Some(serde_json::Value::Object(props)) => Some(props),
+        Some(_) => {
+            lint_issue(issues, "error", format!("{}.properties", location), "'properties' must be an object".to_string());
+            None
+        }
+    };
+    if properties.is_some() && !types.is_empty() && !types.contains(&"object") {
+        lint_issue(issues, "warning", location.to_string(), "'properties' set on a non-object type".to_string());
+    }
+    if let Some(props) = properties {
+        for (name, prop) in props {
+            let prop_location = format!("{}.properties.{}", location, name);
+            if prop.get("description").is_none() {
+                lint_issue(issues, "warning", prop_location.clone(), "Property has no description".to_string());
+            }
+            lint_schema(prop, &prop_location, issues);
+        }
+    }
+
+    match obj.get("required") {
+        None => {}
+        Some(serde_json::Value::Array(required)) => {
+            let mut seen = std::collections::HashSet::new();
+            for (i, entry) in required.iter().enumerate() {
+                let entry_location = format!("{}.required[{}]", location, i);
+                let Some(name) = entry.as_str() else {
+                    lint_issue(issues, "error", entry_location, "'required' entries must be strings".to_string());
+                    continue;
+                };
+                if !seen.insert(name) {
+                    lint_issue(issues, "warning", entry_location, format!("'{}' is listed as required more than once", name));
+                } else if !properties.is_some_and(|p| p.contains_key(name)) {
+                    lint_issue(issues, "error", entry_location, format!("Required property '{}' is not defined in 'properties'", name));
+                }
+            }
+        }
+        Some(_) => {
+            lint_issue(issues, "error", format!("{}.required", location), "'required' must be an array of property names".to_string());
+        }
+    }
+
+    if types.contains(&"array") {
+        match obj.get("items") {
+            None => lint_issue(issues, "warning", location.to_string(), "Array schema has no 'items'".to_string()),
+            Some(items) => lint_schema(items, &format!("{}.items", location), issues),
+        }
+    }
+
+    if let Some(values) = obj.get("enum") {
+        match values.as_array() {
+            Some(values) if !values.is_empty() => {}
+            _ => lint_issue(issues, "error", format!("{}.enum", location), "'enum' must be a non-empty array".to_string()),
+        }
+    }
+}
+
+/// Longest function name the LLM APIs accept.
+const MAX_FUNCTION_NAME_LEN: usize = 64;
+
+/// Lint a skill the way its loaders read it. SKILL.md skills only declare
+/// frontmatter (the server's SKILL.md parser turns `name` into a
+/// `use_<name>` function with a fixed schema), so that is checked with the
+/// parser's rules: frontmatter first, `name` and `description` required, flat
+/// `key: value` lines only, locales as `name_<lang>` / `description_<lang>`.
+/// If a custom desktop manifest with the skill's name is registered, its
+/// function schemas are checked against JSON Schema basics too.
+#[tauri::command]
+async fn lint_skill(user_id: String, skill_name: String) -> Result<Vec<LintIssue>, String> {
+    let skill_dir = resolve_skill_dir(&user_id, &skill_name)?;
+    let skill_md_path = skill_dir.join("SKILL.md");
+    let meta = std::fs::symlink_metadata(&skill_md_path)
+        .map_err(|e| format!("Failed to read SKILL.md: {}", e))?;
+    if !meta.is_file() {
+        return Err("SKILL.md is not a regular file".to_string());
+    }
+    let skill_md = std::fs::read_to_string(&skill_md_path)
+        .map_err(|e| format!("Failed to read SKILL.md: {}", e))?;
+
+    let mut issues = Vec::new();
+    lint_skill_frontmatter(&skill_md, &mut issues);
+
+    if let Some(manifest) = skill_executor::custom_manifest(&skill_name) {
+        let functions = manifest["functions"].as_array().cloned().unwrap_or_default();
+        for (i, function) in functions.iter().enumerate() {
+            let fn_location = format!("manifest:functions[{}]", i);
+            if function["description"].as_str().is_none_or(|d| d.trim().is_empty()) {
+                lint_issue(&mut issues, "warning", fn_location.clone(), "Function has no description".to_string());
+            }
+            let params = &function["parameters"];
+            let params_location = format!("{}.parameters", fn_location);
+            if params["type"].as_str().is_some_and(|t| t != "object") {
+                lint_issue(&mut issues, "error", params_location.clone(), "'parameters' must be of type 'object'".to_string());
+            }
+            lint_schema(params, &params_location, &mut issues);
+        }
+    }
+
+    println!("[Tauri] lint_skill '{}': {} issue(s)", skill_name, issues.len());
+    Ok(issues)
+}
+
+/// Frontmatter checks matching the server's SKILL.md parser.
+fn lint_skill_frontmatter(skill_md: &str, issues: &mut Vec<LintIssue>) {
+    let lines: Vec<&str> = skill_md.lines().collect();
+    let Some(open) = lines.iter().position(|l| !l.trim().is_empty()) else {
+        lint_issue(issues, "error", "SKILL.md:1".to_string(), "SKILL.md is empty".to_string());
+        return;
+    };
+    if !lines[open].trim_start().starts_with("---") {
+        lint_issue(issues, "error", format!("SKILL.md:{}", open + 1), "SKILL.md must start with YAML frontmatter (---)".to_string());
+        return;
+    }
+    let Some(close) = (open + 1..lines.len()).find(|&i| lines[i].trim() == "---") else {
+        lint_issue(issues, "error", format!("SKILL.md:{}", open + 1), "Frontmatter has no closing ---".to_string());
+        return;
+    };
+
+    let mut fields: HashMap<&str, (usize, &str)> = HashMap::new();
+    for (i, line) in lines.iter().enumerate().take(close).skip(open + 1) {
+        let location = format!("SKILL.md:{}", i + 1);
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if line.starts_with(' ') || line.starts_with('\t') || trimmed.starts_with('-') {
+            lint_issue(issues, "warning", location, "Nested YAML is ignored; only flat 'key: value' lines are read".to_string());
+            continue;
+        }
+        let Some((key, value)) = trimmed.split_once(':') else {
+            lint_issue(issues, "warning", location, format!("Line '{}' is not 'key: value' and is ignored", trimmed));
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim().trim_matches('"').trim_matches('\''));
+        if fields.insert(key, (i + 1, value)).is_some() {
+            lint_issue(issues, "warning", location.clone(), format!("'{}' is set more than once; the last value wins", key));
+        }
+        let locale = key.strip_prefix("name_").or_else(|| key.strip_prefix("description_"));
+        if locale.is_some_and(|lang| lang.len() != 2 || !lang.bytes().all(|b| b.is_ascii_lowercase())) {
+            lint_issue(issues, "warning", location, format!("'{}' is not a locale key (expected a two-letter language, e.g. name_zh)", key));
+        }
+    }
+
+    let open_location = format!("SKILL.md:{}", open + 1);
+    for key in ["name", "description"] {
+        match fields.get(key) {
+            Some((_, value)) if !value.is_empty() => {}
+            Some((line, _)) => lint_issue(issues, "error", format!("SKILL.md:{}", line), format!("Frontmatter '{}' is empty", key)),
+            None => lint_issue(issues, "error", open_location.clone(), format!("Frontmatter is missing '{}'", key)),
+        }
+    }
+    if let Some((line, name)) = fields.get("name").filter(|(_, name)| !name.is_empty()) {
+        let function_name = format!("use_{}", name.replace(|c: char| !c.is_ascii_alphanumeric(), "_").to_lowercase());
+        if function_name.len() > MAX_FUNCTION_NAME_LEN {
+            lint_issue(
+                issues,
+                "error",
+                format!("SKILL.md:{}", line),
+                format!("Generated function name '{}' is longer than {} characters", function_name, MAX_FUNCTION_NAME_LEN),
+            );
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SkillVerification {
+    /// None when clawhub's checksum isn't in a format we can compare against.
+    matches: Option<bool>,
+    expected_hash: Option<String>,
+    actual_hash: String,
+    detail: String,
+}
+
+/// Deterministic SHA-256 of a skill directory: every regular file (sorted by
+/// relative path, `.clawhub` metadata excluded) contributes
+/// "<path>\n<sha256 of contents>\n". Symlinks are not followed.
+fn hash_skill_dir(dir: &std::path::Path) -> Result<String, String> {
+    use sha2::{Digest, Sha256};
+
+    fn collect(root: &std::path::Path, dir: &std::path::Path, out: &mut Vec<(String, std::path::PathBuf)>) -> std::io::Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let file_type = std::fs::symlink_metadata(&path)?.file_type();
+            let rel = path.strip_prefix(root).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+            if file_type.is_dir() {
+                if rel != ".clawhub" {
+                    collect(root, &path, out)?;
+                }
+            } else if file_type.is_file() {
+                out.push((rel, path));
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    collect(dir, dir, &mut files).map_err(|e| format!("Failed to read skill files: {}", e))?;
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut hasher = Sha256::new();
+    for (rel, path) in files {
+        let contents = std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", rel, e))?;
+        hasher.update(rel.as_bytes());
+        hasher.update(b"\n");
+        hasher.update(format!("{:x}", Sha256::digest(&contents)).as_bytes());
+        hasher.update(b"\n");
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Compare an installed ClawHub skill against the checksum clawhub reports for it.
+#[tauri::command]
+async fn verify_skill(slug: String, user_id: String) -> Result<SkillVerification, String> {
+    let skill_dir = resolve_skill_dir(&user_id, &slug)?;
+    let actual_hash = hash_skill_dir(&skill_dir)?;
+
+    let path = extended_path();
+    let args = vec!["inspect".to_string(), slug.clone(), "--json".to_string()];
+    let output = run_command_with_timeout("clawhub", &args, &path, CLAWHUB_SEARCH_TIMEOUT_SECS, None).await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("clawhub inspect failed: {}", stderr));
+    }
+    let info: Value = serde_json::from_slice(&output.stdout).unwrap_or(Value::Null);
+    let expected_hash = ["sha256", "hash", "checksum", "integrity"]
+        .iter()
+        .find_map(|key| {
+            info[*key]
+                .as_str()
+                .or_else(|| info["latestVersion"][*key].as_str())
+                .or_else(|| info["version"][*key].as_str())
+        })
+        .map(|h| h.trim().to_string());
+
+    // Only a bare hex SHA-256 can be compared with our directory hash; other
+    // algorithms and encodings (e.g. npm-style "sha512-<base64>") would
+    // always look like a mismatch.
+    let comparable = expected_hash.as_deref().and_then(|h| {
+        let hex = h.strip_prefix("sha256-").or_else(|| h.strip_prefix("sha256:")).unwrap_or(h);
+        (hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit())).then(|| hex.to_lowercase())
+    });
+
+    let (matches, detail) = match (&expected_hash, comparable) {
+        (_, Some(expected)) if expected == actual_hash => (Some(true), "Installed files match the published checksum".to_string()),
+        (_, Some(_)) => (Some(false), "Installed files differ from the published checksum".to_string()),
+        (Some(expected), None) => (None, format!("Unknown checksum format '{}'; cannot compare", expected)),
+        (None, None) => (None, "clawhub did not report a checksum for this skill".to_string()),
+    };
+    if matches != Some(true) {
+        println!("[Tauri] verify_skill: '{}' {}", slug, detail);
+    }
+
+    Ok(SkillVerification { matches, expected_hash, actual_hash, detail })
+}
+
+/// Uninstall a ClawHub skill by removing its directory from the workspace.
+#[tauri::command]
+async fn clawhub_uninstall(slug: String, user_id: String) -> Result<(), String> {
+    if remove_workspace_skill(&user_id, &slug)? {
+        println!("[Tauri] clawhub_uninstall: removed '{}' for user '{}'", slug, user_id);
+    } else {
+        println!("[Tauri] clawhub_uninstall: skill dir not found for '{}'", slug);
+    }
+    Ok(())
+}
+
+/// Remove `skills/<name>` from a user's workspace. Symlinked skills are
+/// unlinked rather than followed, and names that resolve outside the skills
+/// directory are refused. Returns false when there was nothing to remove.
+fn remove_workspace_skill(user_id: &str, name: &str) -> Result<bool, String> {
+    let skills_dir = user_workspace_dir(user_id)?.join("skills");
+    let skill_dir = skills_dir.join(name);
+
+    let meta = match std::fs::symlink_metadata(&skill_dir) {
+        Ok(meta) => meta,
+        Err(_) => return Ok(false),
+    };
+
+    let skills_root = skills_dir
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve skills directory: {}", e))?;
+    // Check the entry's own location (its parent), not a symlink's target.
+    let parent = skill_dir
+        .parent()
+        .and_then(|p| p.canonicalize().ok())
+        .ok_or("Failed to resolve skill directory")?;
+    let file_name = skill_dir.file_name().ok_or("Invalid skill name")?;
+    let resolved = parent.join(file_name);
+    if !resolved.starts_with(&skills_root) || resolved == skills_root {
+        return Err(format!("Refusing to remove '{}': path escapes the workspace skills directory", name));
+    }
+
+    if meta.file_type().is_symlink() {
+        remove_symlink(&resolved)
+            .map_err(|e| format!("Failed to remove skill link: {}", e))?;
+    } else if meta.is_dir() {
+        remove_dir_no_follow(&resolved)
+            .map_err(|e| format!("Failed to remove skill directory: {}", e))?;
+    } else {
+        std::fs::remove_file(&resolved)
+            .map_err(|e| format!("Failed to remove skill file: {}", e))?;
+    }
+    Ok(true)
+}
+
+#[derive(Serialize)]
+struct SkillAuditEntry {
+    /// Directory name under `workspace/skills`, as accepted by `remove_skill`.
+    name: String,
+    valid: bool,
+    issues: Vec<String>,
+}
+
+/// Check every entry of a user's workspace skills directory for problems left
+/// behind by imports and installs: stray files, broken or escaping symlinks,
+/// empty directories, missing or unparseable SKILL.md, and duplicate names.
+#[tauri::command]
+async fn audit_workspace_skills(user_id: String) -> Result<Vec<SkillAuditEntry>, String> {
+    let skills_dir = user_workspace_dir(&user_id)?.join("skills");
+    if !skills_dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let skills_root = skills_dir
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve skills directory: {}", e))?;
+
+    let entries = std::fs::read_dir(&skills_dir)
+        .map_err(|e| format!("Failed to read skills directory: {}", e))?;
+    let mut audit = Vec::new();
+    // Declared skill name -> directories declaring it, for duplicate detection
+    let mut declared: HashMap<String, Vec<usize>> = HashMap::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = entry.path();
+        let mut issues = Vec::new();
+
+        let is_link = std::fs::symlink_metadata(&path)
+            .map(|m| m.file_type().is_symlink())
+            .unwrap_or(false);
+        if is_link {
+            match path.canonicalize() {
+                Err(_) => issues.push("Broken symlink".to_string()),
+                Ok(target) if !target.starts_with(&skills_root) => {
+                    issues.push(format!("Symlink points outside the skills directory: {}", target.display()));
+                }
+                Ok(_) => {}
+            }
+        }
+
+        if issues.is_empty() && !path.is_dir() {
+            issues.push("Not a directory".to_string());
+        }
+        if issues.is_empty() {
+            let empty = std::fs::read_dir(&path).map(|mut d| d.next().is_none()).unwrap_or(true);
+            let skill_md = path.join("SKILL.md");
+            if empty {
+                issues.push("Empty directory".to_string());
+            } else if !skill_md.is_file() {
+                issues.push("Missing SKILL.md".to_string());
+            } else {
+                match std::fs::read_to_string(&skill_md) {
+                    Err(e) => issues.push(format!("Unreadable SKILL.md: {}", e)),
+                    Ok(content) => {
+                        let fields = parse_skill_frontmatter(&content);
+                        if fields.is_empty() {
+                            issues.push("SKILL.md has no frontmatter".to_string());
+                        } else {
+                            if !fields.contains_key("description") {
+                                issues.push("SKILL.md frontmatter has no description".to_string());
+                            }
+                            if let Some(declared_name) = fields.get("name") {
+                                declared.entry(declared_name.clone()).or_default().push(audit.len());
+                            } else {
+                                issues.push("SKILL.md frontmatter has no name".to_string());
+                            }
+                        }
+                    }
+                }
+            }
+        }
 
-    std::fs::copy(&src_server, config_dir.join("server.py"))
-        .map_err(|e| format!("Failed to copy server.py: {}", e))?;
-    if src_reqs.exists() {
-        std::fs::copy(&src_reqs, config_dir.join("requirements.txt"))
-            .map_err(|e| format!("Failed to copy requirements.txt: {}", e))?;
+        audit.push(SkillAuditEntry { name, valid: false, issues });
     }
 
-    // Step 3: pip install requirements
-    let reqs_path = config_dir.join("requirements.txt");
-    if reqs_path.exists() {
-        let pip_result = std::process::Command::new("pip3")
-            .args(&["install", "-r", &reqs_path.to_string_lossy()])
-            .env("PATH", &path)
-            .output()
-            .map_err(|e| format!("Failed to run pip3: {}", e))?;
-        if !pip_result.status.success() {
-            let stderr = String::from_utf8_lossy(&pip_result.stderr);
-            return Ok(CopawInstallResult {
-                success: false,
-                config_dir: String::new(),
-                error: format!("pip install failed: {}", stderr),
-            });
+    for (declared_name, indices) in &declared {
+        if indices.len() < 2 {
+            continue;
+        }
+        let dirs: Vec<String> = indices.iter().map(|&i| audit[i].name.clone()).collect();
+        for &i in indices {
+            let others: Vec<&str> = dirs
+                .iter()
+                .filter(|d| **d != audit[i].name)
+                .map(|d| d.as_str())
+                .collect();
+            audit[i].issues.push(format!(
+                "Duplicate skill name '{}' (also in {})",
+                declared_name,
+                others.join(", ")
+            ));
         }
     }
+    for entry in &mut audit {
+        entry.valid = entry.issues.is_empty();
+    }
+    audit.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(audit)
+}
 
-    // Step 4: Determine base URL from provider
-    let default_base_url = match provider.as_str() {
-        "deepseek" => "https://api.deepseek.com/v1",
-        "openai" => "https://api.openai.com/v1",
-        "gemini" => "https://generativelanguage.googleapis.com/v1beta/openai",
-        "moonshot" => "https://api.moonshot.cn/v1",
-        "qwen" => "https://dashscope.aliyuncs.com/compatible-mode/v1",
-        "zhipu" => "https://open.bigmodel.cn/api/paas/v4",
-        "openrouter" => "https://openrouter.ai/api/v1",
-        _ => "https://api.deepseek.com/v1",
-    };
-    let effective_base_url = base_url.as_deref().unwrap_or(default_base_url);
-
-    let default_model = match provider.as_str() {
-        "deepseek" => "deepseek-chat",
-        "openai" => "gpt-4o",
-        "gemini" => "gemini-2.5-flash",
-        "moonshot" => "kimi-k2.5",
-        "qwen" => "qwen-max",
-        "zhipu" => "glm-4",
-        "openrouter" => "auto",
-        _ => "deepseek-chat",
-    };
-    let effective_model = if model.is_empty() { default_model } else { &model };
+/// Remove a skill directory (or stray entry) from a user's workspace, e.g. one
+/// flagged by `audit_workspace_skills`.
+#[tauri::command]
+async fn remove_skill(user_id: String, name: String) -> Result<(), String> {
+    if !remove_workspace_skill(&user_id, &name)? {
+        return Err(format!("Skill '{}' not found", name));
+    }
+    println!("[Tauri] remove_skill: removed '{}' for user '{}'", name, user_id);
+    Ok(())
+}
 
-    // Step 5: Write .env file
-    let env_content = format!(
-        "LLM_API_KEY={}\nLLM_BASE_URL={}\nLLM_MODEL={}\nCOPAW_PORT={}\nCOPAW_HOST=127.0.0.1\n",
-        api_key, effective_base_url, effective_model, port
-    );
-    std::fs::write(config_dir.join(".env"), &env_content)
-        .map_err(|e| format!("Failed to write .env: {}", e))?;
+/// Remove a symlink itself. On Windows, directory links must be removed with
+/// `remove_dir`; elsewhere `remove_file` unlinks any kind of symlink.
+fn remove_symlink(path: &std::path::Path) -> std::io::Result<()> {
+    #[cfg(windows)]
+    {
+        std::fs::remove_file(path).or_else(|_| std::fs::remove_dir(path))
+    }
+    #[cfg(not(windows))]
+    {
+        std::fs::remove_file(path)
+    }
+}
 
-    Ok(CopawInstallResult {
-        success: true,
-        config_dir: config_dir.to_string_lossy().to_string(),
-        error: String::new(),
-    })
+/// Recursively delete a directory without ever following symlinks: links
+/// inside it are unlinked, never descended into.
+fn remove_dir_no_follow(dir: &std::path::Path) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = std::fs::symlink_metadata(&path)?.file_type();
+        if file_type.is_symlink() {
+            remove_symlink(&path)?;
+        } else if file_type.is_dir() {
+            remove_dir_no_follow(&path)?;
+        } else {
+            std::fs::remove_file(&path)?;
+        }
+    }
+    std::fs::remove_dir(dir)
 }
 
+/// Import a skill from a local directory into the workspace.
+/// If source_path contains SKILL.md, copy the whole directory.
+/// If source_path IS a SKILL.md file, use its parent directory name.
 #[tauri::command]
-async fn start_local_copaw(
-    state: tauri::State<'_, AppState>,
-    port: Option<u16>,
-) -> Result<String, String> {
-    let port = port.unwrap_or(8088);
-    let mut pm = state.process_manager.lock().await;
+async fn import_skill_local(source_path: String, user_id: String) -> Result<String, String> {
+    let source = std::path::PathBuf::from(&source_path);
+    let skills_dir = user_workspace_dir(&user_id)?.join("skills");
 
-    if pm.is_running(COPAW_PROCESS_NAME) {
-        return Ok("already_running".to_string());
-    }
+    std::fs::create_dir_all(&skills_dir)
+        .map_err(|e| format!("Failed to create skills directory: {}", e))?;
 
-    let home = dirs_next::home_dir().ok_or("Cannot find home directory")?;
-    let config_dir = home.join(".agentos").join("copaw");
-    let server_path = config_dir.join("server.py");
+    let (src_dir, skill_name) = if source.is_dir() {
+        // Directory containing SKILL.md
+        let name = source
+            .file_name()
+            .ok_or("Invalid directory name")?
+            .to_string_lossy()
+            .to_string();
+        (source.clone(), name)
+    } else if source.is_file() {
+        // Single SKILL.md file — use parent dir name
+        let parent = source.parent().ok_or("Cannot find parent directory")?;
+        let name = parent
+            .file_name()
+            .ok_or("Invalid parent directory name")?
+            .to_string_lossy()
+            .to_string();
+        (parent.to_path_buf(), name)
+    } else {
+        return Err("Source path does not exist".to_string());
+    };
 
-    if !server_path.exists() {
-        return Err("CoPaw not installed. Run install first.".to_string());
+    let dest = skills_dir.join(&skill_name);
+    if dest.exists() {
+        std::fs::remove_dir_all(&dest)
+            .map_err(|e| format!("Failed to clean existing skill dir: {}", e))?;
     }
 
-    let mut envs = HashMap::new();
-    envs.insert("PATH".to_string(), python_extended_path());
+    // Recursive copy
+    copy_dir_recursive(&src_dir, &dest)
+        .map_err(|e| format!("Failed to copy skill: {}", e))?;
+    if let Ok(canonical_dest) = dest.canonicalize() {
+        mark_skill_scripts_executable(&canonical_dest);
+    }
 
-    let _pid = pm.spawn_with_env(
-        COPAW_PROCESS_NAME,
-        "python3",
-        &[server_path.to_string_lossy().to_string()],
-        Some(&envs),
-    ).map_err(|e| format!("Failed to start CoPaw: {}", e))?;
+    println!("[Tauri] import_skill_local: imported '{}' for user '{}'", skill_name, user_id);
+    Ok(skill_name)
+}
 
-    // Drop the lock before polling
-    drop(pm);
+/// Restore the executable bit on a skill's scripts after it was copied or
+/// downloaded: files SKILL.md references, files under `scripts/` or `bin/`,
+/// `.sh` files, and anything starting with a shebang. No-op on Windows.
+fn mark_skill_scripts_executable(skill_dir: &std::path::Path) {
+    if !cfg!(unix) {
+        return;
+    }
+    let mut candidates: Vec<std::path::PathBuf> = std::fs::read_to_string(skill_dir.join("SKILL.md"))
+        .map(|md| skill_file_references(&md).into_iter().map(|r| skill_dir.join(r)).collect())
+        .unwrap_or_default();
+
+    let mut stack = vec![skill_dir.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else { continue };
+            let path = entry.path();
+            if file_type.is_dir() {
+                stack.push(path);
+            } else if file_type.is_file() {
+                let in_script_dir = path
+                    .strip_prefix(skill_dir)
+                    .ok()
+                    .and_then(|rel| rel.components().next())
+                    .map(|c| c.as_os_str() == "scripts" || c.as_os_str() == "bin")
+                    .unwrap_or(false);
+                let is_shell = path.extension().map(|e| e == "sh").unwrap_or(false);
+                let has_shebang = std::fs::File::open(&path)
+                    .and_then(|mut f| {
+                        use std::io::Read;
+                        let mut head = [0u8; 2];
+                        f.read_exact(&mut head).map(|_| head == *b"#!")
+                    })
+                    .unwrap_or(false);
+                if in_script_dir || is_shell || has_shebang {
+                    candidates.push(path);
+                }
+            }
+        }
+    }
 
-    // Health check: poll until ready
-    let url = format!("http://127.0.0.1:{}/health", port);
-    let client = reqwest::Client::new();
-    for _ in 0..30 {
-        tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
-        if let Ok(resp) = client.get(&url)
-            .timeout(std::time::Duration::from_secs(2))
-            .send()
-            .await
-        {
-            if resp.status().is_success() {
-                println!("[Tauri] Local CoPaw started on port {}", port);
-                return Ok("started".to_string());
+    for path in candidates {
+        // Only regular files inside the skill directory
+        let inside = path.canonicalize().map(|p| p.starts_with(skill_dir)).unwrap_or(false);
+        if inside && path.is_file() {
+            if let Err(e) = skill_executor::make_executable(&path) {
+                println!("[Tauri] Failed to mark {} executable: {}", path.display(), e);
             }
         }
     }
+}
 
-    // Timed out — check if process still alive
-    let pm = state.process_manager.lock().await;
-    if pm.is_running(COPAW_PROCESS_NAME) {
-        Ok("started_no_health".to_string())
-    } else {
-        Err("CoPaw process exited before becoming ready".to_string())
+/// Recursively copy a directory.
+fn copy_dir_recursive(src: &std::path::Path, dst: &std::path::Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dst)?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let src_path = entry.path();
+        let dst_path = dst.join(entry.file_name());
+        if src_path.is_dir() {
+            copy_dir_recursive(&src_path, &dst_path)?;
+        } else {
+            std::fs::copy(&src_path, &dst_path)?;
+        }
     }
+    Ok(())
 }
 
-#[tauri::command]
-async fn stop_local_copaw(
-    state: tauri::State<'_, AppState>,
-    port: Option<u16>,
-) -> Result<(), String> {
-    let port = port.unwrap_or(8088);
-    let mut pm = state.process_manager.lock().await;
-    let _ = pm.kill(COPAW_PROCESS_NAME);
-    drop(pm);
+/// Mask API keys, tokens and passwords in free-form text such as log lines.
+fn redact_secrets(text: &str) -> String {
+    let key_value = regex::Regex::new(
+        r#"(?i)((?:api[_-]?key|token|secret|password|authorization)["']?\s*[:=]\s*["']?)[^\s"',}]+"#,
+    ).unwrap();
+    let bearer = regex::Regex::new(r"(?i)(bearer\s+)[A-Za-z0-9._~+/=-]+").unwrap();
+    let sk_key = regex::Regex::new(r"\bsk-[A-Za-z0-9_-]{8,}").unwrap();
+    let text = key_value.replace_all(text, "${1}[REDACTED]");
+    let text = bearer.replace_all(&text, "${1}[REDACTED]");
+    sk_key.replace_all(&text, "[REDACTED]").to_string()
+}
 
-    // Also kill any process listening on the port
-    if let Ok(output) = std::process::Command::new("lsof")
-        .args(&["-ti", &format!(":{}", port)])
-        .output()
-    {
-        let pids = String::from_utf8_lossy(&output.stdout);
-        for pid_str in pids.split_whitespace() {
-            if pid_str.parse::<u32>().is_ok() {
-                let _ = std::process::Command::new("kill")
-                    .arg(pid_str.trim())
-                    .output();
+/// Settings as included in diagnostics: proxy credentials and default header
+/// values (often auth tokens) are masked.
+fn redacted_settings() -> Value {
+    let mut value = serde_json::to_value(settings::get()).unwrap_or(Value::Null);
+    redact_settings_value(&mut value);
+    value
+}
+
+/// Mask proxy credentials and default header values in serialized settings.
+fn redact_settings_value(value: &mut Value) {
+    if let Some(proxy) = value["http_proxy"].as_str() {
+        let masked = match reqwest::Url::parse(proxy) {
+            Ok(mut url) if !url.username().is_empty() || url.password().is_some() => {
+                let _ = url.set_username("[REDACTED]");
+                let _ = url.set_password(None);
+                url.to_string()
             }
+            Ok(_) => proxy.to_string(),
+            Err(_) => "[REDACTED]".to_string(),
+        };
+        value["http_proxy"] = Value::String(masked);
+    }
+    if let Some(headers) = value["default_headers"].as_object_mut() {
+        for header in headers.values_mut() {
+            *header = Value::String("[REDACTED]".to_string());
         }
     }
+}
 
-    Ok(())
+/// Log lines included per process in `collect_diagnostics`.
+const DIAGNOSTIC_LOG_LINES: usize = 50;
+
+/// Gather everything useful for a bug report into one JSON document:
+/// platform and app version, prerequisite checks, PATH and tool locations,
+/// managed processes with uptimes, and the tail of each process's logs, with
+/// secrets redacted from settings and logs.
+#[tauri::command]
+async fn collect_diagnostics(state: tauri::State<'_, AppState>) -> Result<Value, String> {
+    fn section<T: Serialize>(result: Result<T, String>) -> Value {
+        match result {
+            Ok(v) => serde_json::to_value(v).unwrap_or(Value::Null),
+            Err(e) => serde_json::json!({ "error": e }),
+        }
+    }
+
+    let openclaw = section(check_openclaw_prerequisites().await);
+    let copaw = section(check_copaw_prerequisites().await);
+    let environment = section(debug_environment().await);
+
+    let (processes, logs) = {
+        let pm = state.process_manager.lock().await;
+        let mut processes = Vec::new();
+        let mut logs = serde_json::Map::new();
+        for (name, (status, pid)) in pm.list() {
+            processes.push(serde_json::json!({
+                "name": name,
+                "status": status.to_string(),
+                "pid": pid,
+                "uptimeSecs": pm.uptime_secs(&name),
+            }));
+            let tail: Vec<String> = pm
+                .get_logs(&name, DIAGNOSTIC_LOG_LINES)
+                .unwrap_or_default()
+                .iter()
+                .map(|line| redact_secrets(line))
+                .collect();
+            logs.insert(name, serde_json::json!(tail));
+        }
+        (processes, logs)
+    };
+
+    let (connected, avg_latency_ms) = {
+        let client = state.ws_client.lock().await;
+        (client.is_connected(), client.average_latency())
+    };
+
+    Ok(serde_json::json!({
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "appVersion": env!("CARGO_PKG_VERSION"),
+        "generatedAt": std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64,
+        "server": { "connected": connected, "avgLatencyMs": avg_latency_ms },
+        "openclawPrerequisites": openclaw,
+        "copawPrerequisites": copaw,
+        "environment": environment,
+        "settings": redacted_settings(),
+        "processes": processes,
+        "logs": logs,
+    }))
 }
 
 #[derive(Serialize)]
-struct LocalCopawStatus {
-    running: bool,
-    pid: Option<u32>,
-    port: u16,
+struct ToolLocation {
+    name: String,
+    path: Option<String>,
+    version: String,
 }
 
-#[tauri::command]
-async fn get_local_copaw_status(
-    state: tauri::State<'_, AppState>,
-    port: Option<u16>,
-) -> Result<LocalCopawStatus, String> {
-    let port = port.unwrap_or(8088);
-    let pm = state.process_manager.lock().await;
-    let mut running = pm.is_running(COPAW_PROCESS_NAME);
-    let mut pid = if running {
-        pm.list().into_iter().find(|(n, _)| n == COPAW_PROCESS_NAME).and_then(|(_, info)| info.1)
+#[derive(Serialize)]
+struct DebugEnvironment {
+    os: String,
+    arch: String,
+    path: String,
+    python_path: String,
+    tools: Vec<ToolLocation>,
+}
+
+/// Every copy of an executable on a PATH string, in lookup order (`which -a`).
+fn find_all_in_path(binary: &str, path: &str) -> Vec<std::path::PathBuf> {
+    let candidates: Vec<String> = if cfg!(target_os = "windows") {
+        vec![format!("{}.exe", binary), format!("{}.cmd", binary), binary.to_string()]
     } else {
-        None
+        vec![binary.to_string()]
     };
+    std::env::split_paths(path)
+        .filter_map(|dir| {
+            candidates
+                .iter()
+                .map(|c| dir.join(c))
+                .find(|p| p.is_file())
+        })
+        .collect()
+}
 
-    // Also check if any process is listening on the port
-    if !running {
-        if let Ok(output) = std::process::Command::new("lsof")
-            .args(&["-ti", &format!(":{}", port)])
-            .output()
-        {
-            let pids_str = String::from_utf8_lossy(&output.stdout);
-            if let Some(first_pid) = pids_str.split_whitespace().next() {
-                if let Ok(p) = first_pid.parse::<u32>() {
-                    running = true;
-                    pid = Some(p);
-                }
-            }
-        }
-    }
+/// Locate an executable on a PATH string, like `which`.
+fn find_in_path(binary: &str, path: &str) -> Option<std::path::PathBuf> {
+    find_all_in_path(binary, path).into_iter().next()
+}
 
-    Ok(LocalCopawStatus { running, pid, port })
+#[derive(Serialize)]
+struct BinaryResolution {
+    /// The copy the app will run, or None if it is not on PATH.
+    path: Option<String>,
+    version: String,
+    /// Other copies further down the PATH, hidden by `path`.
+    shadowed_by: Vec<String>,
 }
 
+/// Report which copy of `name` the app's extended PATH resolves to, its
+/// version, and every other copy it hides — for "I installed node 20 but it
+/// says 16" reports. Python tools are looked up on the Python PATH.
 #[tauri::command]
-async fn check_local_copaw_installed() -> Result<bool, String> {
-    let home = dirs_next::home_dir().ok_or("Cannot find home directory")?;
-    let env_path = home.join(".agentos").join("copaw").join(".env");
-    Ok(env_path.exists())
+async fn resolve_binary(name: String) -> Result<BinaryResolution, String> {
+    if name.is_empty() || name.contains(['/', '\\']) {
+        return Err(format!("Invalid binary name '{}'", name));
+    }
+    let search_path = if name.starts_with("python") || name.starts_with("pip") {
+        python_extended_path()
+    } else {
+        extended_path()
+    };
+
+    // The same file can appear via several PATH entries (e.g. /bin -> /usr/bin)
+    let mut seen = std::collections::HashSet::new();
+    let mut copies = find_all_in_path(&name, &search_path)
+        .into_iter()
+        .filter(|p| seen.insert(p.canonicalize().unwrap_or_else(|_| p.clone())))
+        .map(|p| p.to_string_lossy().to_string());
+    let Some(path) = copies.next() else {
+        return Ok(BinaryResolution { path: None, version: String::new(), shadowed_by: vec![] });
+    };
+
+    let version = std::process::Command::new(&path)
+        .arg("--version")
+        .env("PATH", &search_path)
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .map(|out| {
+            // python2 prints its version to stderr
+            let text = if out.stdout.is_empty() { out.stderr } else { out.stdout };
+            String::from_utf8_lossy(&text).trim().to_string()
+        })
+        .unwrap_or_default();
+
+    Ok(BinaryResolution { path: Some(path), version, shadowed_by: copies.collect() })
 }
 
-// ── ClawHub skill management commands (desktop deploy mode) ──
+#[derive(Serialize)]
+struct OpenclawInstallation {
+    path: String,
+    version: String,
+    /// "nvm", "volta", "fnm", "npm_global", "link" (npm link to a checkout) or "path".
+    source: String,
+    /// The copy the app runs: first on the extended PATH.
+    active: bool,
+}
+
+fn openclaw_install_source(path: &std::path::Path, npm_bin: Option<&str>) -> &'static str {
+    let text = path.to_string_lossy();
+    // Volta's bin entries are symlinks to its shim, not to a package
+    if text.contains("/.volta/") {
+        return "volta";
+    }
+    let resolved = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if resolved != path && !resolved.to_string_lossy().contains("node_modules") {
+        return "link";
+    }
+    if text.contains("/.nvm/") {
+        "nvm"
+    } else if text.contains("/.fnm/") {
+        "fnm"
+    } else if npm_bin.is_some_and(|bin| path.parent().is_some_and(|p| p.to_string_lossy() == bin)) {
+        "npm_global"
+    } else {
+        "path"
+    }
+}
 
-/// Ensure clawhub CLI is installed. Auto-installs if missing.
-/// Returns true if clawhub is available after the check.
+/// Every openclaw binary on the machine: the extended PATH in lookup order,
+/// then copies under other nvm/fnm Node versions and Volta's package store
+/// that aren't on PATH. The first entry with `active` is the one that runs,
+/// for "my changes aren't taking effect" reports when a global install and
+/// an `npm link`ed checkout coexist.
 #[tauri::command]
-async fn ensure_clawhub() -> Result<bool, String> {
+async fn list_openclaw_installations() -> Result<Vec<OpenclawInstallation>, String> {
+    let home = dirs_next::home_dir().ok_or("Cannot find home directory")?;
     let path = extended_path();
-    let check = std::process::Command::new("clawhub")
-        .arg("--cli-version")
-        .env("PATH", &path)
-        .output();
-    if check.map(|o| o.status.success()).unwrap_or(false) {
-        return Ok(true);
+    let npm_bin = npm_global_bin(&path);
+
+    let mut candidates = find_all_in_path("openclaw", &path);
+    let mut version_dirs: Vec<std::path::PathBuf> = Vec::new();
+    for (root, bin) in [
+        (home.join(".nvm").join("versions").join("node"), "bin"),
+        (home.join(".fnm").join("node-versions"), "installation/bin"),
+    ] {
+        if let Ok(entries) = std::fs::read_dir(&root) {
+            version_dirs.extend(entries.flatten().map(|e| e.path().join(bin)));
+        }
     }
-    // Not installed — try to install
-    println!("[ensure_clawhub] clawhub not found, installing...");
-    let result = std::process::Command::new("npm")
-        .args(["install", "-g", "clawhub"])
-        .env("PATH", &path)
-        .output()
-        .map_err(|e| format!("Failed to run npm: {}", e))?;
-    if result.status.success() {
-        println!("[ensure_clawhub] clawhub installed successfully");
-        Ok(true)
-    } else {
-        let stderr = String::from_utf8_lossy(&result.stderr);
-        println!("[ensure_clawhub] clawhub install failed: {}", stderr);
-        Ok(false)
+    version_dirs.push(
+        home.join(".volta").join("tools").join("image").join("packages").join("openclaw").join("bin"),
+    );
+    let extra = std::env::join_paths(version_dirs).map_err(|e| format!("Invalid search path: {}", e))?;
+    candidates.extend(find_all_in_path("openclaw", &extra.to_string_lossy()));
+
+    let on_path = find_in_path("openclaw", &path);
+    let mut seen = std::collections::HashSet::new();
+    let installations = candidates
+        .into_iter()
+        .filter(|p| seen.insert(p.canonicalize().unwrap_or_else(|_| p.clone())))
+        .map(|p| {
+            let version = std::process::Command::new(&p)
+                .arg("--version")
+                .env("PATH", &path)
+                .output()
+                .ok()
+                .filter(|out| out.status.success())
+                .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+                .unwrap_or_default();
+            OpenclawInstallation {
+                source: openclaw_install_source(&p, npm_bin.as_deref()).to_string(),
+                active: on_path.as_ref() == Some(&p),
+                path: p.to_string_lossy().to_string(),
+                version,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if installations.len() > 1 {
+        println!("[Tauri] list_openclaw_installations: found {} copies of openclaw", installations.len());
     }
+    Ok(installations)
 }
 
-#[derive(Serialize, Deserialize)]
-struct ClawHubSkill {
-    name: String,
-    slug: String,
-    description: String,
-    author: String,
-    version: String,
+/// Report the PATH the app actually uses and which node/python toolchain it resolves,
+/// for pasting into bug reports.
+#[tauri::command]
+async fn debug_environment() -> Result<DebugEnvironment, String> {
+    let path = extended_path();
+    let python_path = python_extended_path();
+
+    let tools = [
+        ("node", "--version", &path),
+        ("npm", "--version", &path),
+        ("openclaw", "--version", &path),
+        ("clawhub", "--cli-version", &path),
+        ("python3", "--version", &python_path),
+        ("pip3", "--version", &python_path),
+    ]
+    .into_iter()
+    .map(|(name, version_flag, search_path)| {
+        let version = std::process::Command::new(name)
+            .arg(version_flag)
+            .env("PATH", search_path)
+            .output()
+            .ok()
+            .filter(|out| out.status.success())
+            .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+            .unwrap_or_default();
+        ToolLocation {
+            name: name.to_string(),
+            path: find_in_path(name, search_path).map(|p| p.to_string_lossy().to_string()),
+            version,
+        }
+    })
+    .collect();
+
+    Ok(DebugEnvironment {
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        path,
+        python_path,
+        tools,
+    })
 }
 
-/// Search or explore ClawHub marketplace skills via the clawhub CLI.
-/// Empty query = explore (list all); non-empty = search.
 #[tauri::command]
-async fn clawhub_search(query: String, _user_id: String) -> Result<Vec<ClawHubSkill>, String> {
-    let path = extended_path();
+fn frontend_log(msg: String) {
+    println!("[Frontend] {}", msg);
+}
 
-    let output = if query.trim().is_empty() {
-        std::process::Command::new("clawhub")
-            .args(["explore", "--limit", "100"])
-            .env("PATH", &path)
-            .output()
-            .map_err(|e| format!("Failed to run clawhub: {}", e))?
-    } else {
-        std::process::Command::new("clawhub")
-            .args(["search", &query, "--limit", "30"])
-            .env("PATH", &path)
-            .output()
-            .map_err(|e| format!("Failed to run clawhub: {}", e))?
-    };
+// ── Config bundle export / import ──
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("clawhub failed: {}", stderr));
-    }
+/// Name of the manifest file stored at the root of an exported bundle.
+const BUNDLE_MANIFEST: &str = "agentos-bundle.json";
 
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let skills = parse_clawhub_output(&stdout);
-    Ok(skills)
+/// Files that hold API keys and are left out of bundles unless secrets are requested.
+const SECRET_FILE_NAMES: &[&str] = &["auth-profiles.json", ".env"];
+
+/// Directory names never included in a bundle (caches, dependencies).
+const BUNDLE_SKIP_DIRS: &[&str] = &["node_modules", "__pycache__", ".venv", "logs"];
+
+#[derive(Serialize)]
+struct ConfigBundleSummary {
+    path: String,
+    files: usize,
+    includes_secrets: bool,
 }
 
-/// Parse clawhub CLI output into structured skill list.
-fn parse_clawhub_output(output: &str) -> Vec<ClawHubSkill> {
-    if output.trim().is_empty() {
-        return vec![];
-    }
+/// Root of all AgentOS local state (~/.agentos).
+fn agentos_home() -> Result<std::path::PathBuf, String> {
+    let home = dirs_next::home_dir().ok_or("Cannot find home directory")?;
+    Ok(home.join(".agentos"))
+}
 
-    // Try JSON first
-    if let Ok(parsed) = serde_json::from_str::<Vec<Value>>(output) {
-        return parsed
-            .iter()
-            .filter_map(|item| {
-                let slug = item["slug"]
-                    .as_str()
-                    .or_else(|| item["name"].as_str())?
-                    .to_string();
-                Some(ClawHubSkill {
-                    name: item["name"]
-                        .as_str()
-                        .unwrap_or(&slug)
-                        .to_string(),
-                    slug: slug.clone(),
-                    description: item["description"].as_str().unwrap_or("").to_string(),
-                    author: item["author"].as_str().unwrap_or("ClawHub").to_string(),
-                    version: item["version"].as_str().unwrap_or("1.0.0").to_string(),
-                })
-            })
-            .collect();
-    }
+/// How often rotated logs are checked against the retention policy.
+const LOG_CLEANUP_INTERVAL_SECS: u64 = 3600;
 
-    // Parse line-by-line text output
-    let slug_re = regex::Regex::new(r"^[a-z0-9][a-z0-9-]*$").unwrap();
-    let mut skills = vec![];
+#[derive(Serialize)]
+struct LogCleanup {
+    /// Deleted files, relative to ~/.agentos/logs.
+    removed: Vec<String>,
+    freed_bytes: u64,
+    remaining_bytes: u64,
+}
 
-    for line in output.lines() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('-') {
-            continue;
+/// Whether a file in the logs directory is a rotated log: `<name>.log.<n>`,
+/// `<name>.log.gz` or `<name>.log.<n>.gz`. Live `*.log` files and anything
+/// else in the directory are never deleted.
+fn is_rotated_log(name: &str) -> bool {
+    let Some((stem, suffix)) = name.rsplit_once(".log.") else { return false };
+    let index = suffix.strip_suffix(".gz").unwrap_or(suffix);
+    !stem.is_empty() && (suffix == "gz" || (!index.is_empty() && index.bytes().all(|b| b.is_ascii_digit())))
+}
+
+/// Delete rotated logs under ~/.agentos/logs that are older than
+/// `max_age_days`, then the oldest remaining ones while the directory is
+/// larger than `max_bytes`. A limit of 0 is not enforced.
+fn prune_logs(max_bytes: u64, max_age_days: u32) -> Result<LogCleanup, String> {
+    let logs_dir = agentos_home()?.join("logs");
+    let mut files = Vec::new();
+    let mut stack = vec![logs_dir.clone()];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.metadata() else { continue };
+            if meta.is_dir() {
+                stack.push(entry.path());
+            } else if meta.is_file() {
+                let modified = meta.modified().unwrap_or(std::time::UNIX_EPOCH);
+                files.push((entry.path(), meta.len(), modified));
+            }
         }
+    }
 
-        // Split on 2+ whitespace
-        let parts: Vec<&str> = trimmed.split("  ").filter(|s| !s.is_empty()).map(|s| s.trim()).collect();
-        if parts.len() < 2 {
+    let mut total: u64 = files.iter().map(|(_, size, _)| size).sum();
+    let mut rotated: Vec<_> = files
+        .into_iter()
+        .filter(|(path, _, _)| path.file_name().map(|n| is_rotated_log(&n.to_string_lossy())).unwrap_or(false))
+        .collect();
+    rotated.sort_by_key(|(_, _, modified)| *modified);
+
+    let max_age = std::time::Duration::from_secs(max_age_days as u64 * 24 * 3600);
+    let now = std::time::SystemTime::now();
+    let mut removed = Vec::new();
+    let mut freed_bytes = 0;
+    for (path, size, modified) in rotated {
+        let expired = max_age_days > 0 && now.duration_since(modified).unwrap_or_default() > max_age;
+        let over_size = max_bytes > 0 && total > max_bytes;
+        if !expired && !over_size {
             continue;
         }
+        match std::fs::remove_file(&path) {
+            Ok(()) => {
+                total -= size;
+                freed_bytes += size;
+                removed.push(path.strip_prefix(&logs_dir).unwrap_or(&path).to_string_lossy().to_string());
+            }
+            Err(e) => println!("[Logs] Failed to remove {}: {}", path.display(), e),
+        }
+    }
+    if !removed.is_empty() {
+        println!("[Logs] Pruned {} rotated logs, freed {} bytes", removed.len(), freed_bytes);
+    }
+    Ok(LogCleanup { removed, freed_bytes, remaining_bytes: total })
+}
 
-        let first = parts[0];
-        // Try "slug vX.Y.Z" format
-        let slug_version_re = regex::Regex::new(r"^([a-z0-9][a-z0-9-]*)\s+v(\d+\.\d+\.\d+)$").unwrap();
-        let (slug, version) = if let Some(caps) = slug_version_re.captures(first) {
-            (caps[1].to_string(), caps[2].to_string())
-        } else if slug_re.is_match(first) {
-            let ver = parts.get(1)
-                .and_then(|p| p.strip_prefix('v'))
-                .and_then(|v| if v.contains('.') { Some(v.to_string()) } else { None })
-                .unwrap_or_else(|| "1.0.0".to_string());
-            (first.to_string(), ver)
-        } else {
+/// Set the retention policy for ~/.agentos/logs (0 disables a limit) and
+/// apply it right away. Returns what the cleanup freed.
+#[tauri::command]
+async fn set_log_retention(max_bytes: u64, max_age_days: u32) -> Result<LogCleanup, String> {
+    settings::update(|s| {
+        s.log_retention_max_bytes = max_bytes;
+        s.log_retention_max_age_days = max_age_days;
+    })?;
+    tokio::task::spawn_blocking(move || prune_logs(max_bytes, max_age_days))
+        .await
+        .map_err(|e| format!("Log cleanup task failed: {}", e))?
+}
+
+/// Collect files under `dir` (relative to `root`) for bundling, honoring the skip lists.
+fn collect_bundle_files(
+    root: &std::path::Path,
+    dir: &std::path::Path,
+    include_secrets: bool,
+    out: &mut Vec<std::path::PathBuf>,
+) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        let file_type = entry.file_type()?;
+        if file_type.is_symlink() {
             continue;
-        };
+        }
+        if file_type.is_dir() {
+            if !BUNDLE_SKIP_DIRS.contains(&name.as_str()) {
+                collect_bundle_files(root, &path, include_secrets, out)?;
+            }
+        } else if include_secrets || !SECRET_FILE_NAMES.contains(&name.as_str()) {
+            if let Ok(rel) = path.strip_prefix(root) {
+                out.push(rel.to_path_buf());
+            }
+        }
+    }
+    Ok(())
+}
 
-        // Collect description from remaining parts (skip version/time fields)
-        let desc_parts: Vec<&str> = parts[1..].iter()
-            .filter(|p| {
-                let p = p.trim();
-                if p.starts_with('v') && p[1..].contains('.') { return false; }
-                if p.ends_with(" ago") || p == "just now" { return false; }
-                true
-            })
-            .copied()
-            .collect();
+/// Format epoch milliseconds as `YYYY-MM-DD HH:MM:SS UTC`.
+fn format_utc_timestamp(millis: u64) -> String {
+    let secs = millis / 1000;
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // Civil-from-days (proleptic Gregorian calendar)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year, month, day, rem / 3600, rem % 3600 / 60, rem % 60
+    )
+}
 
-        skills.push(ClawHubSkill {
-            name: slug.clone(),
-            slug,
-            description: desc_parts.join(" "),
-            author: "ClawHub".to_string(),
-            version,
-        });
+/// Check an export destination: an absolute file path whose directory exists
+/// inside the home directory but outside ~/.agentos.
+fn sandboxed_export_path(dest_path: &str) -> Result<std::path::PathBuf, String> {
+    let dest = std::path::Path::new(dest_path);
+    if !dest.is_absolute() {
+        return Err(format!("Export path must be absolute: {}", dest_path));
     }
-
-    skills
+    let file_name = dest.file_name().ok_or("Export path has no file name")?;
+    let parent = dest
+        .parent()
+        .and_then(|p| p.canonicalize().ok())
+        .ok_or_else(|| format!("Export directory does not exist: {}", dest_path))?;
+    let home = dirs_next::home_dir()
+        .and_then(|h| h.canonicalize().ok())
+        .ok_or("Cannot find home directory")?;
+    if !parent.starts_with(&home) {
+        return Err("Exports can only be written inside the home directory".to_string());
+    }
+    if agentos_home()?.canonicalize().map(|a| parent.starts_with(a)).unwrap_or(false) {
+        return Err("Exports cannot be written into ~/.agentos".to_string());
+    }
+    let resolved = parent.join(file_name);
+    // Writing through a symlink would land wherever it points
+    if std::fs::symlink_metadata(&resolved).map(|m| m.file_type().is_symlink()).unwrap_or(false) {
+        return Err(format!("Export path is a symlink: {}", resolved.display()));
+    }
+    if resolved.is_dir() {
+        return Err(format!("Export path is a directory: {}", resolved.display()));
+    }
+    Ok(resolved)
 }
 
-/// Install a ClawHub skill into the user's local workspace.
+/// Save a conversation transcript to disk as Markdown or JSON, without a
+/// server round-trip. Returns the path written.
 #[tauri::command]
-async fn clawhub_install(slug: String, user_id: String) -> Result<(), String> {
-    let home = dirs_next::home_dir().ok_or("Cannot find home directory")?;
-    let workspace = home
-        .join(".agentos")
-        .join("openclaw")
-        .join("users")
-        .join(&user_id)
-        .join("workspace");
-    let path = extended_path();
+async fn export_conversation(
+    conversation_id: String,
+    messages: Vec<ChatMessage>,
+    format: String,
+    dest_path: String,
+) -> Result<String, String> {
+    let dest = sandboxed_export_path(&dest_path)?;
+    let exported_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+
+    let content = match format.to_lowercase().as_str() {
+        "json" => {
+            let doc = serde_json::json!({
+                "conversationId": conversation_id,
+                "exportedAt": exported_at,
+                "messages": messages,
+            });
+            serde_json::to_string_pretty(&doc).map_err(|e| e.to_string())?
+        }
+        "markdown" | "md" => {
+            let mut md = format!(
+                "# Conversation {}\n\nExported {} · {} messages\n",
+                conversation_id,
+                format_utc_timestamp(exported_at),
+                messages.len()
+            );
+            for m in &messages {
+                let mut role = m.role.clone();
+                if let Some(first) = role.get_mut(0..1) {
+                    first.make_ascii_uppercase();
+                }
+                md.push_str(&format!("\n---\n\n**{}**", role));
+                if let Some(ts) = m.timestamp {
+                    md.push_str(&format!(" · {}", format_utc_timestamp(ts)));
+                }
+                md.push_str(&format!("\n\n{}\n", m.content.trim_end()));
+            }
+            md
+        }
+        other => return Err(format!("Unsupported export format '{}' (use markdown or json)", other)),
+    };
 
-    let output = std::process::Command::new("clawhub")
-        .args([
-            "install",
-            &slug,
-            "--workdir",
-            workspace.to_str().ok_or("Invalid workspace path")?,
-            "--force",
-            "--no-input",
-        ])
-        .env("PATH", &path)
-        .output()
-        .map_err(|e| format!("Failed to run clawhub install: {}", e))?;
+    std::fs::write(&dest, content).map_err(|e| format!("Failed to write {}: {}", dest.display(), e))?;
+    println!("[Tauri] export_conversation: {} messages to {}", messages.len(), dest.display());
+    Ok(dest.to_string_lossy().to_string())
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("clawhub install failed: {}", stderr));
-    }
+/// Manifest stored at the root of a skill package, next to the skill directory.
+const SKILL_PACKAGE_MANIFEST: &str = "agentos-skill.json";
 
-    println!("[Tauri] clawhub_install: installed '{}' for user '{}'", slug, user_id);
-    Ok(())
+#[derive(Serialize)]
+struct SkillPackage {
+    path: String,
+    sha256: String,
+    files: usize,
 }
 
-/// Uninstall a ClawHub skill by removing its directory from the workspace.
+/// Zip an installed skill for distribution. The archive holds the skill
+/// directory (so unpacking it gives a folder `import_skill_local` accepts)
+/// plus a manifest listing each file with its size and SHA-256. Secrets
+/// (`.env*`, auth profiles), dependency caches and `.clawhub` metadata are
+/// left out. Returns the archive path and its SHA-256.
 #[tauri::command]
-async fn clawhub_uninstall(slug: String, user_id: String) -> Result<(), String> {
-    let home = dirs_next::home_dir().ok_or("Cannot find home directory")?;
-    let skill_dir = home
-        .join(".agentos")
-        .join("openclaw")
-        .join("users")
-        .join(&user_id)
-        .join("workspace")
-        .join("skills")
-        .join(&slug);
-
-    if skill_dir.exists() {
-        std::fs::remove_dir_all(&skill_dir)
-            .map_err(|e| format!("Failed to remove skill directory: {}", e))?;
-        println!("[Tauri] clawhub_uninstall: removed '{}' for user '{}'", slug, user_id);
-    } else {
-        println!("[Tauri] clawhub_uninstall: skill dir not found for '{}'", slug);
+async fn package_skill(user_id: String, skill_name: String, dest_path: String) -> Result<SkillPackage, String> {
+    use sha2::{Digest, Sha256};
+    use std::io::Write;
+
+    let skill_dir = resolve_skill_dir(&user_id, &skill_name)?;
+    if !skill_dir.join("SKILL.md").is_file() {
+        return Err(format!("Skill '{}' has no SKILL.md", skill_name));
+    }
+    let dest = sandboxed_export_path(&dest_path)?;
+
+    let mut files = Vec::new();
+    collect_bundle_files(&skill_dir, &skill_dir, false, &mut files)
+        .map_err(|e| format!("Failed to scan skill files: {}", e))?;
+    files.retain(|rel| {
+        let is_env = rel.file_name().map(|n| n.to_string_lossy().starts_with(".env")).unwrap_or(false);
+        let is_metadata = rel.components().next().map(|c| c.as_os_str() == ".clawhub").unwrap_or(false);
+        !is_env && !is_metadata
+    });
+    files.sort();
+
+    let file = std::fs::File::create(&dest)
+        .map_err(|e| format!("Failed to create package: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::FileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    let mut entries = Vec::new();
+    for rel in &files {
+        let rel_name = rel.to_string_lossy().replace('\\', "/");
+        let data = std::fs::read(skill_dir.join(rel))
+            .map_err(|e| format!("Failed to read {}: {}", rel_name, e))?;
+        let name = format!("{}/{}", skill_name, rel_name);
+        zip.start_file(name.clone(), options)
+            .map_err(|e| format!("Failed to add {} to package: {}", name, e))?;
+        zip.write_all(&data)
+            .map_err(|e| format!("Failed to add {} to package: {}", name, e))?;
+        entries.push(serde_json::json!({
+            "path": rel_name,
+            "size": data.len(),
+            "sha256": format!("{:x}", Sha256::digest(&data)),
+        }));
     }
 
-    Ok(())
+    let frontmatter = std::fs::read_to_string(skill_dir.join("SKILL.md"))
+        .map(|md| parse_skill_frontmatter(&md))
+        .unwrap_or_default();
+    let manifest = serde_json::json!({
+        "version": 1,
+        "name": skill_name,
+        "description": frontmatter.get("description"),
+        "createdAt": std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64,
+        "clientVersion": env!("CARGO_PKG_VERSION"),
+        "files": entries,
+    });
+    zip.start_file(SKILL_PACKAGE_MANIFEST, options)
+        .map_err(|e| format!("Failed to write package manifest: {}", e))?;
+    zip.write_all(serde_json::to_string_pretty(&manifest).unwrap().as_bytes())
+        .map_err(|e| format!("Failed to write package manifest: {}", e))?;
+    zip.finish().map_err(|e| format!("Failed to finalize package: {}", e))?;
+
+    let archive = std::fs::read(&dest).map_err(|e| format!("Failed to hash package: {}", e))?;
+    let sha256 = format!("{:x}", Sha256::digest(&archive));
+    println!("[Tauri] package_skill: '{}' ({} files) -> {}", skill_name, files.len(), dest.display());
+
+    Ok(SkillPackage {
+        path: dest.to_string_lossy().to_string(),
+        sha256,
+        files: files.len(),
+    })
 }
 
-/// Import a skill from a local directory into the workspace.
-/// If source_path contains SKILL.md, copy the whole directory.
-/// If source_path IS a SKILL.md file, use its parent directory name.
+/// Export ~/.agentos into a zip archive at `dest_path`, which must pass
+/// `sandboxed_export_path`. API keys, gateway tokens, provider headers and
+/// proxy credentials are excluded unless `include_secrets` is set.
 #[tauri::command]
-async fn import_skill_local(source_path: String, user_id: String) -> Result<String, String> {
-    let source = std::path::PathBuf::from(&source_path);
-    let home = dirs_next::home_dir().ok_or("Cannot find home directory")?;
-    let skills_dir = home
-        .join(".agentos")
-        .join("openclaw")
-        .join("users")
-        .join(&user_id)
-        .join("workspace")
-        .join("skills");
+async fn export_config_bundle(
+    dest_path: String,
+    include_secrets: Option<bool>,
+) -> Result<ConfigBundleSummary, String> {
+    use std::io::Write;
+
+    let include_secrets = include_secrets.unwrap_or(false);
+    let root = agentos_home()?;
+    if !root.is_dir() {
+        return Err("Nothing to export: ~/.agentos does not exist".to_string());
+    }
 
-    std::fs::create_dir_all(&skills_dir)
-        .map_err(|e| format!("Failed to create skills directory: {}", e))?;
+    let mut files = Vec::new();
+    collect_bundle_files(&root, &root, include_secrets, &mut files)
+        .map_err(|e| format!("Failed to scan config dir: {}", e))?;
 
-    let (src_dir, skill_name) = if source.is_dir() {
-        // Directory containing SKILL.md
-        let name = source
-            .file_name()
-            .ok_or("Invalid directory name")?
-            .to_string_lossy()
-            .to_string();
-        (source.clone(), name)
-    } else if source.is_file() {
-        // Single SKILL.md file — use parent dir name
-        let parent = source.parent().ok_or("Cannot find parent directory")?;
-        let name = parent
-            .file_name()
-            .ok_or("Invalid parent directory name")?
-            .to_string_lossy()
-            .to_string();
-        (parent.to_path_buf(), name)
+    let dest = sandboxed_export_path(&dest_path)?;
+    let file = std::fs::File::create(&dest)
+        .map_err(|e| format!("Failed to create bundle: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::FileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    let manifest = serde_json::json!({
+        "version": 1,
+        "createdAt": std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64,
+        "clientVersion": env!("CARGO_PKG_VERSION"),
+        "includesSecrets": include_secrets,
+    });
+    zip.start_file(BUNDLE_MANIFEST, options)
+        .map_err(|e| format!("Failed to write bundle manifest: {}", e))?;
+    zip.write_all(serde_json::to_string_pretty(&manifest).unwrap().as_bytes())
+        .map_err(|e| format!("Failed to write bundle manifest: {}", e))?;
+
+    for rel in &files {
+        let name = rel.to_string_lossy().replace('\\', "/");
+        let mut data = std::fs::read(root.join(rel))
+            .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+
+        // Blank out gateway tokens and provider headers (Azure's `api-key`)
+        // in openclaw.json when secrets are excluded
+        if !include_secrets && rel.file_name().map(|n| n == "openclaw.json").unwrap_or(false) {
+            if let Ok(mut config) = serde_json::from_slice::<Value>(&data) {
+                if config["gateway"]["auth"]["token"].is_string() {
+                    config["gateway"]["auth"]["token"] = serde_json::json!("");
+                }
+                if let Some(providers) = config.pointer_mut("/models/providers").and_then(|p| p.as_object_mut()) {
+                    for provider in providers.values_mut() {
+                        if let Some(headers) = provider.get_mut("headers").and_then(|h| h.as_object_mut()) {
+                            for value in headers.values_mut() {
+                                *value = serde_json::json!("");
+                            }
+                        }
+                    }
+                }
+                data = serde_json::to_vec_pretty(&config).unwrap();
+            }
+        }
+        // Proxy credentials and default headers in settings.json are secrets too
+        if !include_secrets && rel.as_path() == std::path::Path::new("settings.json") {
+            if let Ok(mut value) = serde_json::from_slice::<Value>(&data) {
+                redact_settings_value(&mut value);
+                data = serde_json::to_vec_pretty(&value).unwrap();
+            }
+        }
+
+        zip.start_file(name.clone(), options)
+            .map_err(|e| format!("Failed to add {} to bundle: {}", name, e))?;
+        zip.write_all(&data)
+            .map_err(|e| format!("Failed to add {} to bundle: {}", name, e))?;
+    }
+
+    zip.finish().map_err(|e| format!("Failed to finalize bundle: {}", e))?;
+    println!("[Tauri] export_config_bundle: {} files -> {}", files.len(), dest.display());
+
+    Ok(ConfigBundleSummary {
+        path: dest.to_string_lossy().to_string(),
+        files: files.len(),
+        includes_secrets: include_secrets,
+    })
+}
+
+/// First existing symlink on the way from `root` to `root/rel`, if any.
+fn symlink_under(root: &std::path::Path, rel: &std::path::Path) -> Option<std::path::PathBuf> {
+    let mut path = root.to_path_buf();
+    for component in rel.components() {
+        path.push(component);
+        match std::fs::symlink_metadata(&path) {
+            Ok(meta) if meta.file_type().is_symlink() => return Some(path),
+            Ok(_) => {}
+            Err(_) => return None,
+        }
+    }
+    None
+}
+
+/// A bundle exported without secrets has blanked gateway tokens, provider
+/// headers and redacted settings; carry the values over from the file being
+/// replaced instead of overwriting a working install with them.
+fn keep_existing_secrets(rel: &std::path::Path, data: Vec<u8>, dest: &std::path::Path) -> Vec<u8> {
+    let is_openclaw = rel.file_name().map(|n| n == "openclaw.json").unwrap_or(false);
+    let is_settings = rel == std::path::Path::new("settings.json");
+    if !is_openclaw && !is_settings {
+        return data;
+    }
+    let Ok(mut incoming) = serde_json::from_slice::<Value>(&data) else { return data };
+    let existing = std::fs::read_to_string(dest)
+        .ok()
+        .and_then(|s| serde_json::from_str::<Value>(&s).ok())
+        .unwrap_or(Value::Null);
+
+    if is_openclaw {
+        if incoming["gateway"]["auth"]["token"].as_str() == Some("") {
+            if let Some(token) = existing["gateway"]["auth"]["token"].as_str() {
+                incoming["gateway"]["auth"]["token"] = serde_json::json!(token);
+            }
+        }
+        if let Some(providers) = incoming.pointer_mut("/models/providers").and_then(|p| p.as_object_mut()) {
+            for (id, provider) in providers.iter_mut() {
+                let Some(headers) = provider.get_mut("headers").and_then(|h| h.as_object_mut()) else { continue };
+                for (name, value) in headers.iter_mut() {
+                    if value.as_str() == Some("") {
+                        if let Some(kept) = existing["models"]["providers"][id]["headers"][name].as_str() {
+                            *value = serde_json::json!(kept);
+                        }
+                    }
+                }
+            }
+        }
     } else {
-        return Err("Source path does not exist".to_string());
-    };
+        if incoming["http_proxy"].as_str().is_some_and(|p| p.contains("[REDACTED]")) {
+            incoming["http_proxy"] = existing["http_proxy"].clone();
+        }
+        if let Some(headers) = incoming["default_headers"].as_object_mut() {
+            headers.retain(|name, value| {
+                if value.as_str() != Some("[REDACTED]") {
+                    return true;
+                }
+                match existing["default_headers"][name].as_str() {
+                    Some(kept) => {
+                        *value = serde_json::json!(kept);
+                        true
+                    }
+                    None => false,
+                }
+            });
+        }
+    }
+    serde_json::to_vec_pretty(&incoming).unwrap_or(data)
+}
 
-    let dest = skills_dir.join(&skill_name);
-    if dest.exists() {
-        std::fs::remove_dir_all(&dest)
-            .map_err(|e| format!("Failed to clean existing skill dir: {}", e))?;
+/// Import a bundle created by `export_config_bundle` into ~/.agentos.
+/// Refuses to overwrite while local agents are running unless `force` is set,
+/// and never writes through a symlink under ~/.agentos. Importing a bundle
+/// without secrets keeps the tokens and headers already on disk.
+#[tauri::command]
+async fn import_config_bundle(
+    state: tauri::State<'_, AppState>,
+    src_path: String,
+    force: Option<bool>,
+) -> Result<ConfigBundleSummary, String> {
+    let force = force.unwrap_or(false);
+    {
+        let pm = state.process_manager.lock().await;
+        // Exited processes stay listed (with their logs) until restarted or killed
+        let running: Vec<String> = pm
+            .list()
+            .into_iter()
+            .filter(|(_, (status, _))| matches!(status, process_manager::ProcessStatus::Running))
+            .map(|(name, _)| name)
+            .collect();
+        if !running.is_empty() && !force {
+            return Err(format!(
+                "Local processes are running ({}). Stop them first or confirm with force.",
+                running.join(", ")
+            ));
+        }
+    }
+
+    let file = std::fs::File::open(&src_path)
+        .map_err(|e| format!("Failed to open bundle: {}", e))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| format!("Not a valid bundle archive: {}", e))?;
+
+    // Validate the manifest before touching anything on disk
+    let manifest: Value = {
+        let entry = archive
+            .by_name(BUNDLE_MANIFEST)
+            .map_err(|_| "Not an AgentOS config bundle (manifest missing)".to_string())?;
+        serde_json::from_reader(entry)
+            .map_err(|e| format!("Invalid bundle manifest: {}", e))?
+    };
+    if manifest["version"].as_u64() != Some(1) {
+        return Err(format!("Unsupported bundle version: {}", manifest["version"]));
     }
+    let includes_secrets = manifest["includesSecrets"].as_bool().unwrap_or(false);
 
-    // Recursive copy
-    copy_dir_recursive(&src_dir, &dest)
-        .map_err(|e| format!("Failed to copy skill: {}", e))?;
+    let root = agentos_home()?;
+    let mut entries = Vec::new();
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i).map_err(|e| format!("Corrupt bundle: {}", e))?;
+        if entry.is_dir() || entry.name() == BUNDLE_MANIFEST {
+            continue;
+        }
+        // Reject absolute paths and `..` components
+        let rel = entry
+            .enclosed_name()
+            .ok_or_else(|| format!("Unsafe path in bundle: {}", entry.name()))?
+            .to_path_buf();
+        entries.push((i, rel));
+    }
 
-    println!("[Tauri] import_skill_local: imported '{}' for user '{}'", skill_name, user_id);
-    Ok(skill_name)
-}
+    for (_, rel) in &entries {
+        if let Some(link) = symlink_under(&root, rel) {
+            return Err(format!("Refusing to import through symlink {}", link.display()));
+        }
+    }
 
-/// Recursively copy a directory.
-fn copy_dir_recursive(src: &std::path::Path, dst: &std::path::Path) -> std::io::Result<()> {
-    std::fs::create_dir_all(dst)?;
-    for entry in std::fs::read_dir(src)? {
-        let entry = entry?;
-        let src_path = entry.path();
-        let dst_path = dst.join(entry.file_name());
-        if src_path.is_dir() {
-            copy_dir_recursive(&src_path, &dst_path)?;
-        } else {
-            std::fs::copy(&src_path, &dst_path)?;
+    for (i, rel) in &entries {
+        let mut entry = archive.by_index(*i).map_err(|e| format!("Corrupt bundle: {}", e))?;
+        let dest = root.join(rel);
+        let mut data = Vec::new();
+        std::io::Read::read_to_end(&mut entry, &mut data)
+            .map_err(|e| format!("Failed to read {} from bundle: {}", rel.display(), e))?;
+        if !includes_secrets {
+            data = keep_existing_secrets(rel, data, &dest);
         }
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(&dest, &data)
+            .map_err(|e| format!("Failed to write {}: {}", dest.display(), e))?;
     }
-    Ok(())
+
+    println!("[Tauri] import_config_bundle: {} files from {}", entries.len(), src_path);
+    Ok(ConfigBundleSummary {
+        path: root.to_string_lossy().to_string(),
+        files: entries.len(),
+        includes_secrets,
+    })
+}
+
+// ── Factory reset ──
+
+/// How long a token from `request_factory_reset` stays valid.
+const FACTORY_RESET_TOKEN_SECS: u64 = 60;
+
+/// Outstanding factory reset confirmation token and when it was issued.
+static FACTORY_RESET_TOKEN: std::sync::Mutex<Option<(String, std::time::Instant)>> = std::sync::Mutex::new(None);
+
+#[derive(Serialize)]
+struct RemovedEntry {
+    name: String,
+    bytes: u64,
+}
+
+#[derive(Serialize)]
+struct FactoryResetSummary {
+    processes: Vec<ShutdownReport>,
+    backup: Option<String>,
+    removed: Vec<RemovedEntry>,
+    total_bytes: u64,
 }
 
+/// First step of a factory reset: returns a one-time token, valid for
+/// `FACTORY_RESET_TOKEN_SECS`, that `factory_reset` must be called with.
 #[tauri::command]
-fn frontend_log(msg: String) {
-    println!("[Frontend] {}", msg);
+fn request_factory_reset() -> String {
+    let token = generate_gateway_token();
+    *FACTORY_RESET_TOKEN.lock().unwrap() = Some((token.clone(), std::time::Instant::now()));
+    token
+}
+
+/// Wipe all local AgentOS state: stop managed processes, sweep the agent
+/// ports and delete ~/.agentos. `confirm_token` must come from
+/// `request_factory_reset`. With `backup_path`, a full bundle (secrets
+/// included) is written there first and the reset is aborted if that fails.
+/// Workspaces moved outside ~/.agentos are left alone.
+#[tauri::command]
+async fn factory_reset(
+    state: tauri::State<'_, AppState>,
+    confirm_token: String,
+    backup_path: Option<String>,
+) -> Result<FactoryResetSummary, String> {
+    {
+        let mut pending = FACTORY_RESET_TOKEN.lock().unwrap();
+        let valid = matches!(
+            pending.as_ref(),
+            Some((token, issued)) if *token == confirm_token
+                && issued.elapsed().as_secs() < FACTORY_RESET_TOKEN_SECS
+        );
+        // One attempt per token
+        pending.take();
+        if !valid {
+            return Err("Invalid or expired confirmation token; call request_factory_reset again".to_string());
+        }
+    }
+
+    let root = agentos_home()?;
+    let backup = match backup_path {
+        Some(ref dest) if root.is_dir() => {
+            let dest = sandboxed_export_path(dest)?;
+            let summary = export_config_bundle(dest.to_string_lossy().to_string(), Some(true)).await?;
+            Some(summary.path)
+        }
+        _ => None,
+    };
+
+    let processes = shutdown_managed_processes(&state.process_manager).await;
+    state.ws_client.lock().await.disconnect().await;
+
+    let mut removed = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(&root) {
+        for entry in entries.flatten() {
+            let bytes = match std::fs::symlink_metadata(entry.path()) {
+                Ok(meta) if meta.is_dir() => dir_usage(&entry.path()),
+                Ok(meta) => meta.len(),
+                Err(_) => 0,
+            };
+            removed.push(RemovedEntry { name: entry.file_name().to_string_lossy().to_string(), bytes });
+        }
+    }
+    if root.exists() {
+        std::fs::remove_dir_all(&root)
+            .map_err(|e| format!("Failed to remove {}: {}", root.display(), e))?;
+    }
+    removed.sort_by(|a, b| a.name.cmp(&b.name));
+    let total_bytes = removed.iter().map(|e| e.bytes).sum();
+
+    // Cached state now points at deleted files
+    settings::reload();
+    http::reset_client();
+
+    println!("[Tauri] factory_reset: removed {} entries ({} bytes)", removed.len(), total_bytes);
+    Ok(FactoryResetSummary { processes, backup, removed, total_bytes })
 }
 
 // ── MCP Bridge commands ──
 
+/// Half-second polls without startup progress before giving up on the MCP bridge.
+const MCP_BRIDGE_IDLE_POLLS: u32 = 30;
+
 /// Start the local MCP bridge process. Reads ~/.agentos/mcp-config.json,
 /// spawns node mcp-bridge.mjs, discovers tools, and returns them.
 #[tauri::command]
 async fn start_mcp_bridge(
     state: tauri::State<'_, AppState>,
+    webview: tauri::Webview,
+    on_progress: Option<JavaScriptChannelId>,
 ) -> Result<Vec<Value>, String> {
+    let on_progress: Option<Channel<Value>> = on_progress.map(|id| id.channel_on(webview));
     let mut pm = state.process_manager.lock().await;
 
     // Kill existing bridge if running
@@ -1446,10 +6856,17 @@ async fn start_mcp_bridge(
         return Ok(vec![]); // No MCP config, return empty tools
     }
 
-    // Spawn the bridge process
+    // Spawn the bridge process with a fresh auth token so other local
+    // processes cannot call its endpoints
+    let token = generate_gateway_token();
     let mut envs = HashMap::new();
     envs.insert("PATH".to_string(), extended_path());
-    let _pid = pm.spawn_with_env(
+    envs.insert("MCP_BRIDGE_TOKEN".to_string(), token.clone());
+    // Follow the bridge's output from before it starts, without holding the
+    // process manager lock afterwards
+    let mut log_rx = pm.subscribe_logs();
+    // Its own process group, so MCP servers it leaves behind can be found
+    let _pid = pm.spawn_group_leader(
         "mcp-bridge",
         "node",
         &[
@@ -1459,88 +6876,607 @@ async fn start_mcp_bridge(
         Some(&envs),
     ).map_err(|e| format!("Failed to start MCP bridge: {}", e))?;
 
-    // Wait for the bridge to print its port (poll logs)
+    drop(pm);
+    let progress_stream = on_progress.as_ref().map(|_| streams::register("mcp_progress"));
+    let mut on_progress = on_progress;
+
+    // Wait for the bridge to print its port. Each MCP server that comes online
+    // resets the idle timer, so slow multi-server configs are not cut off.
     let mut port: u16 = 0;
-    for _ in 0..30 {
+    let mut idle_polls = 0;
+    while port == 0 && idle_polls < MCP_BRIDGE_IDLE_POLLS {
         tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-        if let Ok(logs) = pm.get_logs("mcp-bridge", 20) {
-            for line in &logs {
-                if let Some(p) = line.strip_prefix("MCP_BRIDGE_PORT=") {
-                    if let Ok(parsed) = p.trim().parse::<u16>() {
-                        port = parsed;
-                        break;
-                    }
+        idle_polls += 1;
+        if progress_stream.as_ref().is_some_and(|s| s.is_closed()) {
+            on_progress = None;
+        }
+        for line in take_new_log_lines(&mut log_rx, "mcp-bridge") {
+            let text = line
+                .strip_prefix("[stdout] ")
+                .or_else(|| line.strip_prefix("[stderr] "))
+                .unwrap_or(&line);
+            if let Some(p) = text.strip_prefix("MCP_BRIDGE_PORT=") {
+                if let Ok(parsed) = p.trim().parse::<u16>() {
+                    port = parsed;
                 }
+            } else if let Some(info) = text.strip_prefix("MCP_BRIDGE_SERVER=") {
+                idle_polls = 0;
+                if let (Some(channel), Ok(info)) = (on_progress.as_ref(), serde_json::from_str::<Value>(info)) {
+                    let _ = channel.send(serde_json::json!({ "type": "server", "server": info }));
+                }
+            } else if let Some(ref channel) = on_progress {
+                let _ = channel.send(serde_json::json!({ "type": "log", "line": text }));
             }
         }
-        if port > 0 { break; }
     }
 
     if port == 0 {
-        let _ = pm.kill("mcp-bridge");
+        let _ = state.process_manager.lock().await.kill("mcp-bridge");
         return Err("MCP bridge failed to start (no port detected)".to_string());
     }
 
-    // Store port for skill_executor to use
+    // Store port and token for skill_executor to use
     skill_executor::set_mcp_bridge_port(port);
+    skill_executor::set_mcp_bridge_token(Some(token.clone()));
     println!("[Tauri] MCP bridge started on port {}", port);
 
     // Discover tools via HTTP
-    let tools = discover_mcp_tools_http(port).await?;
+    let tools = discover_mcp_tools_http(port, &token).await?;
     Ok(tools)
 }
 
-/// Stop the MCP bridge process.
+/// How long `stop_mcp_bridge` waits for the bridge to exit after `/shutdown`.
+const MCP_BRIDGE_EXIT_WAIT_MS: u64 = 3000;
+
+#[derive(Serialize)]
+struct McpBridgeStopReport {
+    /// Whether the bridge accepted the `/shutdown` request.
+    graceful: bool,
+    /// MCP servers the bridge closed before exiting.
+    servers_closed: Vec<String>,
+    /// The bridge had to be killed after not exiting in time.
+    force_killed: bool,
+    /// Orphaned MCP server processes found and killed afterwards.
+    orphans_killed: Vec<u32>,
+}
+
+/// Kill MCP server processes the bridge left behind, e.g. after it died
+/// without closing them. The bridge leads its own process group (Unix), which
+/// its MCP servers inherit, so whatever is left in that group is ours.
+/// On Windows the bridge's process tree is killed with it instead.
+fn sweep_mcp_orphans(bridge_pid: u32) -> Vec<u32> {
+    let own_pid = std::process::id();
+    process_manager::group_members(bridge_pid)
+        .into_iter()
+        .filter(|pid| *pid != own_pid && *pid != bridge_pid)
+        .filter(|pid| {
+            println!("[Tauri] Killing orphaned MCP server {}", pid);
+            process_manager::kill_pid(*pid)
+        })
+        .collect()
+}
+
+/// Stop the MCP bridge: ask it to close its MCP servers and exit, wait for
+/// it, kill it if it hangs, then sweep for orphaned MCP server processes.
 #[tauri::command]
 async fn stop_mcp_bridge(
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<McpBridgeStopReport, String> {
+    let port = skill_executor::get_mcp_bridge_port();
+    let token = skill_executor::get_mcp_bridge_token();
     skill_executor::set_mcp_bridge_port(0);
-    let mut pm = state.process_manager.lock().await;
-    let _ = pm.kill("mcp-bridge");
-    Ok(())
+    skill_executor::set_mcp_bridge_token(None);
+
+    let mut graceful = false;
+    let mut servers_closed = Vec::new();
+    if port != 0 {
+        let mut req = http::client()
+            .post(format!("http://127.0.0.1:{}/shutdown", port))
+            .timeout(std::time::Duration::from_secs(5));
+        if let Some(ref t) = token {
+            req = req.bearer_auth(t);
+        }
+        match req.send().await {
+            Ok(resp) if resp.status().is_success() => {
+                graceful = true;
+                let body: Value = resp.json().await.unwrap_or(Value::Null);
+                servers_closed = body["closed"]
+                    .as_array()
+                    .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+                    .unwrap_or_default();
+            }
+            Ok(resp) => println!("[Tauri] stop_mcp_bridge: /shutdown returned {}", resp.status()),
+            Err(e) => println!("[Tauri] stop_mcp_bridge: /shutdown failed: {}", e),
+        }
+    }
+
+    let bridge_pid = state.process_manager.lock().await.pid("mcp-bridge");
+    let force_killed = if graceful {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_millis(MCP_BRIDGE_EXIT_WAIT_MS);
+        loop {
+            let mut pm = state.process_manager.lock().await;
+            let exited = pm.has_exited("mcp-bridge");
+            if exited || std::time::Instant::now() >= deadline {
+                if !exited {
+                    if let Some(pid) = bridge_pid {
+                        process_manager::kill_process_group(pid);
+                    }
+                }
+                let _ = pm.kill("mcp-bridge");
+                break !exited;
+            }
+            drop(pm);
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+    } else {
+        let mut pm = state.process_manager.lock().await;
+        let running = pm.is_running("mcp-bridge");
+        if let (true, Some(pid)) = (running, bridge_pid) {
+            process_manager::kill_process_group(pid);
+        }
+        let _ = pm.kill("mcp-bridge");
+        running
+    };
+
+    let orphans_killed = match bridge_pid {
+        Some(pid) => tokio::task::spawn_blocking(move || sweep_mcp_orphans(pid))
+            .await
+            .unwrap_or_default(),
+        None => Vec::new(),
+    };
+
+    println!(
+        "[Tauri] stop_mcp_bridge: graceful={}, closed {:?}, force_killed={}, orphans {:?}",
+        graceful, servers_closed, force_killed, orphans_killed
+    );
+    Ok(McpBridgeStopReport { graceful, servers_closed, force_killed, orphans_killed })
 }
 
-/// Fetch discovered tools from the running MCP bridge.
-async fn discover_mcp_tools_http(port: u16) -> Result<Vec<Value>, String> {
-    let url = format!("http://127.0.0.1:{}/tools", port);
-    let client = reqwest::Client::new();
-    let resp = client.get(&url)
-        .timeout(std::time::Duration::from_secs(10))
+/// Connection status of one configured MCP server, as reported by the bridge.
+#[derive(Serialize)]
+struct McpServerStatus {
+    name: String,
+    connected: bool,
+    enabled: bool,
+    error: Option<String>,
+    tool_count: usize,
+}
+
+#[derive(Serialize)]
+struct McpBridgeHealth {
+    running: bool,
+    port: Option<u16>,
+    servers: Vec<McpServerStatus>,
+}
+
+/// Report whether the MCP bridge is running and which configured MCP servers
+/// connected, so a short tool list can be traced to the server that failed.
+#[tauri::command]
+async fn get_mcp_bridge_health(
+    state: tauri::State<'_, AppState>,
+) -> Result<McpBridgeHealth, String> {
+    let running = state.process_manager.lock().await.is_running("mcp-bridge");
+    let port = skill_executor::get_mcp_bridge_port();
+    let token = match skill_executor::get_mcp_bridge_token() {
+        Some(token) if running && port != 0 => token,
+        _ => return Ok(McpBridgeHealth { running, port: None, servers: vec![] }),
+    };
+
+    let resp = http::client()
+        .get(format!("http://127.0.0.1:{}/servers", port))
+        .bearer_auth(&token)
+        .timeout(std::time::Duration::from_secs(5))
         .send()
         .await
-        .map_err(|e| format!("Failed to fetch MCP tools: {}", e))?;
+        .map_err(|e| format!("Failed to query MCP bridge: {}", e))?;
+    let body: Value = resp.json().await.map_err(|e| format!("Invalid MCP bridge response: {}", e))?;
+    let servers = body["servers"]
+        .as_array()
+        .map(|list| {
+            list.iter()
+                .map(|s| McpServerStatus {
+                    name: s["name"].as_str().unwrap_or_default().to_string(),
+                    connected: s["connected"].as_bool().unwrap_or(false),
+                    enabled: s["enabled"].as_bool().unwrap_or(true),
+                    error: s["error"].as_str().map(String::from),
+                    tool_count: s["toolCount"].as_u64().unwrap_or(0) as usize,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
 
-    let body: Value = resp.json().await.map_err(|e| format!("Invalid MCP tools response: {}", e))?;
-    let tools = body["tools"].as_array().cloned().unwrap_or_default();
-    println!("[Tauri] Discovered {} MCP tools", tools.len());
-    Ok(tools)
+    Ok(McpBridgeHealth { running, port: Some(port), servers })
+}
+
+/// One server entry from ~/.agentos/mcp-config.json.
+#[derive(Serialize)]
+struct McpServerConfig {
+    name: String,
+    command: String,
+    args: Vec<String>,
+    enabled: bool,
+}
+
+fn mcp_config_path() -> Result<std::path::PathBuf, String> {
+    Ok(agentos_home()?.join("mcp-config.json"))
+}
+
+fn read_mcp_config() -> Result<Vec<Value>, String> {
+    let path = mcp_config_path()?;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read mcp-config.json: {}", e))?;
+    let parsed: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid mcp-config.json: {}", e))?;
+    parsed
+        .as_array()
+        .cloned()
+        .ok_or_else(|| "Invalid mcp-config.json: expected an array of servers".to_string())
+}
+
+/// MCP servers from mcp-config.json with their enabled flag (absent = enabled),
+/// for rendering per-server toggles.
+#[tauri::command]
+fn list_mcp_servers() -> Result<Vec<McpServerConfig>, String> {
+    Ok(read_mcp_config()?
+        .iter()
+        .map(|s| McpServerConfig {
+            name: s["name"].as_str().unwrap_or_default().to_string(),
+            command: s["command"].as_str().unwrap_or_default().to_string(),
+            args: s["args"]
+                .as_array()
+                .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+                .unwrap_or_default(),
+            enabled: s["enabled"].as_bool().unwrap_or(true),
+        })
+        .collect())
+}
+
+#[derive(Serialize)]
+struct McpCommandCheck {
+    server: String,
+    command: String,
+    /// Full path the command resolves to on the extended PATH.
+    resolved: Option<String>,
+    ok: bool,
+    error: Option<String>,
+}
+
+/// Preflight for mcp-config.json: check that every server's launch command
+/// exists (absolute/relative paths directly, bare names on the extended PATH)
+/// without running anything.
+#[tauri::command]
+fn validate_mcp_config() -> Result<Vec<McpCommandCheck>, String> {
+    let path = extended_path();
+    Ok(read_mcp_config()?
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let server = s["name"].as_str().map(String::from).unwrap_or_else(|| format!("#{}", i + 1));
+            let command = s["command"].as_str().unwrap_or_default().trim().to_string();
+            let (resolved, error) = if command.is_empty() {
+                (None, Some("No command configured".to_string()))
+            } else if command.contains(['/', '\\']) {
+                let candidate = std::path::Path::new(&command);
+                if candidate.is_file() {
+                    (Some(command.clone()), None)
+                } else {
+                    (None, Some(format!("{} does not exist", command)))
+                }
+            } else {
+                match find_in_path(&command, &path) {
+                    Some(p) => (Some(p.to_string_lossy().to_string()), None),
+                    None => (None, Some(format!("'{}' was not found on PATH", command))),
+                }
+            };
+            McpCommandCheck { server, command, ok: error.is_none(), resolved, error }
+        })
+        .collect())
+}
+
+/// Enable or disable one MCP server in mcp-config.json. When the bridge is
+/// running it reconnects or drops just that server; the bridge's resulting
+/// tool list is returned so it can be re-registered (empty if not running).
+#[tauri::command]
+async fn set_mcp_server_enabled(
+    state: tauri::State<'_, AppState>,
+    name: String,
+    enabled: bool,
+) -> Result<Vec<Value>, String> {
+    let mut servers = read_mcp_config()?;
+    let server = servers
+        .iter_mut()
+        .find(|s| s["name"].as_str() == Some(name.as_str()))
+        .ok_or_else(|| format!("MCP server '{}' not found in mcp-config.json", name))?;
+    server["enabled"] = Value::Bool(enabled);
+    let content = serde_json::to_string_pretty(&servers)
+        .map_err(|e| format!("Failed to serialize mcp-config.json: {}", e))?;
+    std::fs::write(mcp_config_path()?, content)
+        .map_err(|e| format!("Failed to write mcp-config.json: {}", e))?;
+    println!("[Tauri] MCP server '{}' {}", name, if enabled { "enabled" } else { "disabled" });
+
+    let running = state.process_manager.lock().await.is_running("mcp-bridge");
+    let port = skill_executor::get_mcp_bridge_port();
+    let token = match skill_executor::get_mcp_bridge_token() {
+        Some(token) if running && port != 0 => token,
+        _ => return Ok(vec![]),
+    };
+    // Connecting a server can take a while (npx downloads, slow startups)
+    let resp = http::client()
+        .post(format!("http://127.0.0.1:{}/servers/refresh", port))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({ "name": name }))
+        .timeout(std::time::Duration::from_secs(60))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to refresh MCP server: {}", e))?;
+    let body: Value = resp.json().await.map_err(|e| format!("Invalid MCP bridge response: {}", e))?;
+    if let Some(err) = body["error"].as_str() {
+        return Err(format!("MCP bridge failed to refresh '{}': {}", name, err));
+    }
+    Ok(body["tools"].as_array().cloned().unwrap_or_default())
+}
+
+/// How long to keep retrying the bridge's `/tools` while requests fail.
+const MCP_DISCOVERY_DEADLINE_SECS: u64 = 15;
+const MCP_DISCOVERY_POLL_MS: u64 = 1000;
+
+/// Fetch discovered tools from the running MCP bridge. The bridge connects
+/// (or gives up on) every configured server before it prints its port, so the
+/// first successful `/tools` response is final, empty or not. Failed requests
+/// are retried until the deadline passes.
+async fn discover_mcp_tools_http(port: u16, token: &str) -> Result<Vec<Value>, String> {
+    let url = format!("http://127.0.0.1:{}/tools", port);
+    let client = http::client();
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(MCP_DISCOVERY_DEADLINE_SECS);
+
+    loop {
+        let attempt = async {
+            let resp = client.get(&url)
+                .bearer_auth(token)
+                .timeout(std::time::Duration::from_secs(10))
+                .send()
+                .await
+                .map_err(|e| format!("Failed to fetch MCP tools: {}", e))?;
+            let body: Value = resp.json().await.map_err(|e| format!("Invalid MCP tools response: {}", e))?;
+            Ok::<_, String>(body["tools"].as_array().cloned().unwrap_or_default())
+        }.await;
+
+        match attempt {
+            Ok(tools) => {
+                println!("[Tauri] Discovered {} MCP tools", tools.len());
+                return Ok(tools);
+            }
+            Err(e) => {
+                println!("[Tauri] MCP tool discovery attempt failed: {}", e);
+                if tokio::time::Instant::now() + std::time::Duration::from_millis(MCP_DISCOVERY_POLL_MS) >= deadline {
+                    return Err(e);
+                }
+            }
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(MCP_DISCOVERY_POLL_MS)).await;
+    }
+}
+
+/// List desktop skills and which of their functions are enabled.
+#[tauri::command]
+fn get_desktop_skills() -> Vec<Value> {
+    skill_executor::get_desktop_skills()
+}
+
+/// Enable or disable a desktop skill or single function, then re-register
+/// with the server so the advertised manifest matches.
+#[tauri::command]
+async fn set_desktop_skill_enabled(
+    state: tauri::State<'_, AppState>,
+    skill: String,
+    enabled: bool,
+) -> Result<(), String> {
+    skill_executor::set_skill_enabled(&skill, enabled)?;
+    let client = state.ws_client.lock().await;
+    if client.is_connected() {
+        client.send_desktop_register().await.map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Advertise an additional desktop skill whose functions map onto built-in
+/// handlers, then re-register with the server if connected.
+#[tauri::command]
+async fn register_custom_skill_manifest(
+    state: tauri::State<'_, AppState>,
+    manifest: Value,
+) -> Result<(), String> {
+    skill_executor::register_custom_manifest(manifest)?;
+    let client = state.ws_client.lock().await;
+    if client.is_connected() {
+        client.send_desktop_register().await.map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn unregister_custom_skill_manifest(
+    state: tauri::State<'_, AppState>,
+    name: String,
+) -> Result<bool, String> {
+    let removed = skill_executor::unregister_custom_manifest(&name);
+    let client = state.ws_client.lock().await;
+    if removed && client.is_connected() {
+        client.send_desktop_register().await.map_err(|e| e.to_string())?;
+    }
+    Ok(removed)
+}
+
+/// Persisted defaults such as the local OpenClaw/CoPaw ports.
+#[tauri::command]
+fn get_settings() -> settings::Settings {
+    settings::get()
+}
+
+/// Replace the persisted settings. Port changes for an installed OpenClaw
+/// or CoPaw go through `set_openclaw_port`/`set_copaw_port` so their config
+/// files (and running processes) follow.
+#[tauri::command]
+async fn set_settings(
+    state: tauri::State<'_, AppState>,
+    user_id: Option<String>,
+    settings: settings::Settings,
+) -> Result<(), String> {
+    let current = settings::get();
+    if settings.openclaw_port != current.openclaw_port
+        && openclaw_config_dir(user_id.as_deref())?.join("openclaw.json").exists()
+    {
+        set_openclaw_port(state.clone(), user_id.clone(), settings.openclaw_port).await?;
+    }
+    let home = dirs_next::home_dir().ok_or("Cannot find home directory")?;
+    if settings.copaw_port != current.copaw_port
+        && home.join(".agentos").join("copaw").join(".env").exists()
+    {
+        set_copaw_port(state.clone(), settings.copaw_port).await?;
+    }
+    settings::set(settings)?;
+    http::reset_client();
+    Ok(())
+}
+
+/// Route outbound HTTP (LLM endpoints, registries, health checks) and the
+/// npm/pip/clawhub processes through a proxy. Pass None to clear it.
+#[tauri::command]
+fn set_http_proxy(url: Option<String>) -> Result<(), String> {
+    let url = url.map(|u| u.trim().to_string()).filter(|u| !u.is_empty());
+    if let Some(ref u) = url {
+        reqwest::Proxy::all(u).map_err(|e| format!("Invalid proxy URL: {}", e))?;
+    }
+    settings::update(|s| s.http_proxy = url.clone())?;
+    http::reset_client();
+    println!("[Tauri] HTTP proxy {}", if url.is_some() { "set" } else { "cleared" });
+    Ok(())
+}
+
+/// Choose what the window's close button does: hide to the tray (default)
+/// or shut down managed processes and quit.
+#[tauri::command]
+fn set_close_behavior(to_tray: bool) -> Result<(), String> {
+    settings::update(|s| s.close_to_tray = to_tray)
+}
+
+/// Turn crash/disconnect OS notifications on or off.
+#[tauri::command]
+fn set_notifications_enabled(enabled: bool) -> Result<(), String> {
+    notifications::set_enabled(enabled)
+}
+
+#[tauri::command]
+fn get_notifications_enabled() -> bool {
+    notifications::is_enabled()
+}
+
+/// Return the most recent `desktop.command` invocations, newest first.
+#[tauri::command]
+fn get_command_audit(limit: Option<usize>) -> Vec<skill_executor::AuditEntry> {
+    skill_executor::get_command_audit(limit.unwrap_or(100))
+}
+
+/// Toggle persisting the command audit log to ~/.agentos/command-audit.jsonl.
+#[tauri::command]
+fn set_command_audit_persist(enabled: bool) {
+    skill_executor::set_audit_to_disk(enabled);
+}
+
+/// Default timeout for `http_fetch` calls that don't pass their own.
+#[tauri::command]
+fn set_http_fetch_timeout(timeout_ms: u64) -> Result<(), String> {
+    settings::update(|s| s.http_fetch_timeout_ms = timeout_ms)
+}
+
+/// Set the User-Agent sent on outbound requests (`http_fetch`, MCP and health
+/// checks) and the default headers `http_fetch` adds for `hosts` (subdomains
+/// included). A `None` User-Agent means no User-Agent header is sent;
+/// `None` headers or hosts clear them.
+#[tauri::command]
+fn set_http_default_headers(
+    user_agent: Option<String>,
+    headers: Option<HashMap<String, String>>,
+    hosts: Option<Vec<String>>,
+) -> Result<(), String> {
+    let user_agent = user_agent.map(|u| u.trim().to_string()).filter(|u| !u.is_empty());
+    if let Some(ref ua) = user_agent {
+        http::validate_header("User-Agent", ua)?;
+    }
+    let headers = headers.unwrap_or_default();
+    for (name, value) in &headers {
+        http::validate_header(name, value)?;
+    }
+    let hosts: Vec<String> = hosts
+        .unwrap_or_default()
+        .into_iter()
+        .map(|h| h.trim().to_lowercase())
+        .filter(|h| !h.is_empty())
+        .collect();
+    let count = headers.len();
+    if count > 0 && hosts.is_empty() {
+        println!("[Tauri] HTTP default headers set without hosts; they will not be sent anywhere");
+    }
+    let host_list = hosts.join(", ");
+    settings::update(|s| {
+        s.user_agent = user_agent;
+        s.default_headers = headers;
+        s.default_header_hosts = hosts;
+    })?;
+    http::reset_client();
+    println!("[Tauri] HTTP default headers set ({} headers, hosts: {})", count, host_list);
+    Ok(())
 }
 
-/// Generic HTTP proxy — bypasses webview fetch restrictions.
+/// Generic HTTP proxy — bypasses webview fetch restrictions. Every request is
+/// bounded by `timeout_ms`, or the configured default when omitted. The
+/// configured default headers are added when `url`'s host is one they are
+/// scoped to; `headers` override them (and the User-Agent) for this call.
 #[tauri::command]
 async fn http_fetch(
     url: String,
     method: String,
     body: Option<String>,
     auth_token: Option<String>,
+    timeout_ms: Option<u64>,
+    headers: Option<HashMap<String, String>>,
 ) -> Result<String, String> {
-    let client = reqwest::Client::new();
+    let timeout_ms = timeout_ms
+        .filter(|&ms| ms > 0)
+        .unwrap_or_else(settings::http_fetch_timeout_ms);
+    let timed_out = |e: &reqwest::Error| e.is_timeout().then(|| format!("Request timed out after {}ms", timeout_ms));
+    let client = http::client();
     let mut req = match method.to_uppercase().as_str() {
         "POST" => client.post(&url),
         "PUT" => client.put(&url),
         "DELETE" => client.delete(&url),
         _ => client.get(&url),
     };
+    req = req.headers(http::default_headers_for(&url));
     req = req.header("Content-Type", "application/json");
     if let Some(token) = auth_token {
         req = req.header("Authorization", format!("Bearer {}", token));
     }
+    if let Some(ref headers) = headers {
+        for (name, value) in headers {
+            http::validate_header(name, value)?;
+        }
+        req = req.headers(http::header_map(headers));
+    }
     if let Some(b) = body {
         req = req.body(b);
     }
-    let resp = req.send().await.map_err(|e| e.to_string())?;
-    resp.text().await.map_err(|e| e.to_string())
+    req = req.timeout(std::time::Duration::from_millis(timeout_ms));
+    let resp = req
+        .send()
+        .await
+        .map_err(|e| timed_out(&e).unwrap_or_else(|| e.to_string()))?;
+    resp.text()
+        .await
+        .map_err(|e| timed_out(&e).unwrap_or_else(|| e.to_string()))
 }
 
 // ── Shared types for Tauri command arguments ──
@@ -1549,6 +7485,9 @@ async fn http_fetch(
 pub struct ChatMessage {
     pub role: String,
     pub content: String,
+    /// Milliseconds since the Unix epoch, when the frontend knows it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
 }
 
 // ── App setup ──
@@ -1557,7 +7496,11 @@ pub struct ChatMessage {
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_notification::init())
         .setup(|app| {
+            // Start the npm global bin lookup so extended_path has it early
+            extended_path();
+
             // Build tray menu
             let show = MenuItemBuilder::with_id("show", "Show Window").build(app)?;
             let hide = MenuItemBuilder::with_id("hide", "Hide Window").build(app)?;
@@ -1587,6 +7530,7 @@ pub fn run() {
                             }
                         }
                         "quit" => {
+                            shutdown_before_exit(app);
                             app.exit(0);
                         }
                         _ => {}
@@ -1609,25 +7553,100 @@ pub fn run() {
                 .build(app)?;
 
             // Initialize state
+            let mut ws_client = WsClient::new();
+            ws_client.set_app_handle(app.handle().clone());
+            let ws_client = Arc::new(Mutex::new(ws_client));
+            let process_manager = Arc::new(Mutex::new(ProcessManager::new()));
             app.manage(AppState {
-                ws_client: Arc::new(Mutex::new(WsClient::new())),
-                process_manager: Arc::new(Mutex::new(ProcessManager::new())),
+                ws_client: ws_client.clone(),
+                process_manager: process_manager.clone(),
+                clawhub_cancel: Arc::new(tokio::sync::Notify::new()),
+                install_cancel: Arc::new(InstallCancel::default()),
+            });
+
+            // Find out whether the claude-code skill can be advertised
+            tauri::async_runtime::spawn(async move {
+                refresh_claude_code(&ws_client).await;
+            });
+
+            // Process reaper: detect managed processes that exited on their own
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+                    let exited = process_manager.lock().await.reap();
+                    for (name, code) in exited {
+                        let crashed = code != Some(0);
+                        println!("[Reaper] {} exited (code={:?})", name, code);
+                        let _ = app_handle.emit(
+                            if crashed { "process-crashed" } else { "process-exited" },
+                            serde_json::json!({ "name": name, "code": code }),
+                        );
+                        if crashed {
+                            notifications::notify(
+                                &app_handle,
+                                "AgentOS process crashed",
+                                &format!("{} exited unexpectedly (code {:?})", name, code),
+                            );
+                        }
+                    }
+                }
+            });
+
+            // Log retention: prune rotated logs on startup and then hourly
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    let (max_bytes, max_age_days) = settings::log_retention();
+                    let _ = tokio::task::spawn_blocking(move || prune_logs(max_bytes, max_age_days)).await;
+                    tokio::time::sleep(std::time::Duration::from_secs(LOG_CLEANUP_INTERVAL_SECS)).await;
+                }
             });
 
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             connect_server,
+            connect_server_with_fallback,
+            ping_server,
             disconnect_server,
             send_message,
             stop_generation,
             get_connection_status,
+            set_ws_max_message_size,
+            send_raw_message,
+            cancel_command,
+            subscribe_raw_events,
+            set_forward_unknown_messages,
+            set_file_skill_base_dir,
             launch_agent,
             stop_agent,
             list_agents,
             get_agent_logs,
+            get_last_exit,
+            clear_agent_logs,
+            set_log_capacity,
+            pause_log_capture,
+            resume_log_capture,
+            stream_combined_logs,
+            stop_combined_logs,
+            list_active_streams,
+            close_stream,
+            shutdown_all,
+            request_factory_reset,
+            factory_reset,
+            clear_all_logs,
+            write_agent_stdin,
+            close_agent_stdin,
             frontend_log,
+            debug_environment,
+            resolve_binary,
+            list_openclaw_installations,
+            check_npm_global_bin,
+            collect_diagnostics,
             http_fetch,
+            set_http_fetch_timeout,
+            set_http_default_headers,
+            set_log_retention,
             request_skill_list,
             toggle_skill,
             install_skill,
@@ -1635,35 +7654,105 @@ pub fn run() {
             request_skill_library,
             request_skill_config,
             set_skill_config,
+            snapshot_skill_config,
+            list_skill_config_snapshots,
+            restore_skill_config,
             start_mcp_bridge,
             stop_mcp_bridge,
+            get_mcp_bridge_health,
+            list_mcp_servers,
+            validate_mcp_config,
+            set_mcp_server_enabled,
             check_openclaw_prerequisites,
             install_openclaw,
+            cancel_install,
             start_local_openclaw,
             stop_local_openclaw,
             get_local_openclaw_status,
+            probe_openclaw_health,
+            describe_gateway,
+            benchmark_model,
+            run_self_test,
             update_local_openclaw_config,
             check_local_openclaw_installed,
+            rotate_openclaw_token,
+            repair_openclaw_install,
             upgrade_openclaw,
+            check_openclaw_update,
             check_copaw_prerequisites,
+            check_claude_code_prerequisites,
             install_copaw,
             start_local_copaw,
             stop_local_copaw,
+            get_copaw_logs,
+            check_port_available,
+            get_network_info,
+            set_openclaw_port,
+            set_copaw_port,
+            get_copaw_config,
+            update_copaw_config,
+            find_orphaned_processes,
+            kill_orphaned_process,
+            get_openclaw_config_summary,
+            set_openclaw_concurrency,
+            reload_openclaw_config,
+            get_agent_defaults,
+            set_agent_defaults,
             get_local_copaw_status,
             check_local_copaw_installed,
             ensure_clawhub,
             clawhub_search,
             clawhub_install,
+            rollback_skill_install,
             clawhub_uninstall,
+            audit_workspace_skills,
+            remove_skill,
+            list_installed_skills,
+            get_workspace_usage,
+            get_workspace_root,
+            set_workspace_root,
+            clone_openclaw_profile,
+            package_skill,
+            read_skill_source,
+            validate_skill,
+            lint_skill,
+            verify_skill,
+            cancel_clawhub_operation,
             import_skill_local,
+            get_command_audit,
+            get_desktop_skills,
+            get_settings,
+            set_settings,
+            set_http_proxy,
+            set_close_behavior,
+            set_notifications_enabled,
+            get_notifications_enabled,
+            set_desktop_skill_enabled,
+            register_custom_skill_manifest,
+            unregister_custom_skill_manifest,
+            set_command_audit_persist,
+            export_config_bundle,
+            export_conversation,
+            import_config_bundle,
         ])
         .on_window_event(|window, event| {
-            // Minimize to tray instead of closing
+            // Minimize to tray instead of closing, unless the user opted to quit
             if let tauri::WindowEvent::CloseRequested { api, .. } = event {
-                let _ = window.hide();
-                api.prevent_close();
+                if settings::close_to_tray() {
+                    let _ = window.hide();
+                    api.prevent_close();
+                } else {
+                    let app = window.app_handle();
+                    shutdown_before_exit(app);
+                    app.exit(0);
+                }
             }
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                shutdown_before_exit(app);
+            }
+        });
 }
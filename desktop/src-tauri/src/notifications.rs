@@ -0,0 +1,23 @@
+//! Native OS notifications for crashes and disconnects, shown even while the
+//! window is hidden in the tray. Can be turned off by the user; the choice
+//! is persisted in settings.
+
+use tauri_plugin_notification::NotificationExt;
+
+pub fn set_enabled(enabled: bool) -> Result<(), String> {
+    crate::settings::update(|s| s.notifications_enabled = enabled)
+}
+
+pub fn is_enabled() -> bool {
+    crate::settings::notifications_enabled()
+}
+
+/// Show a notification if notifications are enabled. Failures are only logged.
+pub fn notify(app: &tauri::AppHandle, title: &str, body: &str) {
+    if !is_enabled() {
+        return;
+    }
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        println!("[Notifications] Failed to show notification: {}", e);
+    }
+}
@@ -1,16 +1,23 @@
 use std::collections::HashMap;
-use std::io::{BufRead, BufReader};
-use std::process::{Child, Command, Stdio};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex as StdMutex};
+use tokio::sync::broadcast;
 
 pub struct ProcessInfo {
     child: Child,
+    stdin: Option<ChildStdin>,
     status: ProcessStatus,
-    logs: Arc<StdMutex<Vec<String>>>,
+    logs: LogBuffers,
+    /// Maximum number of log lines kept; shared with the capture threads.
+    log_capacity: Arc<AtomicUsize>,
+    /// While set, captured lines are read and discarded instead of stored.
+    log_paused: Arc<AtomicBool>,
+    started_at: std::time::Instant,
 }
 
 #[derive(Clone, Copy)]
-#[allow(dead_code)]
 pub enum ProcessStatus {
     Running,
     Stopped,
@@ -27,26 +34,241 @@ impl std::fmt::Display for ProcessStatus {
     }
 }
 
-const MAX_LOG_LINES: usize = 1000;
+pub const DEFAULT_LOG_LINES: usize = 1000;
+
+/// Which output stream of a process to read logs from.
+#[derive(Clone, Copy, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+    /// Both streams interleaved in arrival order, with `[stdout]`/`[stderr]` prefixes.
+    #[default]
+    Both,
+}
+
+/// Captured output of a process: the merged view plus one unprefixed buffer
+/// per stream, each bounded by the process's log capacity.
+#[derive(Clone, Default)]
+struct LogBuffers {
+    merged: Arc<StdMutex<Vec<String>>>,
+    stdout: Arc<StdMutex<Vec<String>>>,
+    stderr: Arc<StdMutex<Vec<String>>>,
+}
+
+impl LogBuffers {
+    fn get(&self, stream: LogStream) -> &Arc<StdMutex<Vec<String>>> {
+        match stream {
+            LogStream::Stdout => &self.stdout,
+            LogStream::Stderr => &self.stderr,
+            LogStream::Both => &self.merged,
+        }
+    }
+
+    fn all(&self) -> [&Arc<StdMutex<Vec<String>>>; 3] {
+        [&self.merged, &self.stdout, &self.stderr]
+    }
+}
+
+/// Log lines kept in an ExitRecord.
+const EXIT_LOG_TAIL_LINES: usize = 20;
+
+/// How a managed process last ended, kept after it leaves the process table.
+#[derive(Clone, serde::Serialize)]
+pub struct ExitRecord {
+    /// None when the process was ended by a signal.
+    pub exit_code: Option<i32>,
+    /// Terminating signal on Unix.
+    pub signal: Option<i32>,
+    /// Milliseconds since the Unix epoch.
+    pub exited_at: u64,
+    pub uptime_secs: u64,
+    /// Whether it was stopped through `kill` rather than exiting by itself.
+    pub killed: bool,
+    pub log_tail: Vec<String>,
+}
+
+impl ExitRecord {
+    fn new(info: &ProcessInfo, exit: Option<std::process::ExitStatus>, killed: bool) -> Self {
+        #[cfg(unix)]
+        let signal = {
+            use std::os::unix::process::ExitStatusExt;
+            exit.and_then(|e| e.signal())
+        };
+        #[cfg(not(unix))]
+        let signal = None;
+
+        let log = info.logs.merged.lock().unwrap();
+        let log_tail = log[log.len().saturating_sub(EXIT_LOG_TAIL_LINES)..].to_vec();
+        Self {
+            exit_code: exit.and_then(|e| e.code()),
+            signal,
+            exited_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            uptime_secs: info.started_at.elapsed().as_secs(),
+            killed,
+            log_tail,
+        }
+    }
+}
+
+/// Scheduling priority for a spawned process.
+#[derive(Clone, Copy, Default, serde::Deserialize)]
+pub enum ProcessPriority {
+    #[serde(alias = "low")]
+    Low,
+    #[default]
+    #[serde(alias = "normal")]
+    Normal,
+}
+
+/// Nice value used for `ProcessPriority::Low` on Unix.
+#[cfg(unix)]
+const LOW_PRIORITY_NICE: libc::c_int = 10;
+
+/// Windows `BELOW_NORMAL_PRIORITY_CLASS` creation flag.
+#[cfg(windows)]
+const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x0000_4000;
+
+fn apply_priority(cmd: &mut Command, priority: ProcessPriority) {
+    if let ProcessPriority::Normal = priority {
+        return;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // SAFETY: setpriority is async-signal-safe and touches no shared state
+        unsafe {
+            cmd.pre_exec(|| {
+                // who = 0 targets the calling (child) process
+                if libc::setpriority(libc::PRIO_PROCESS, 0, LOW_PRIORITY_NICE) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(BELOW_NORMAL_PRIORITY_CLASS);
+    }
+}
+
+fn push_log_line(logs: &StdMutex<Vec<String>>, capacity: &AtomicUsize, line: String) {
+    let cap = capacity.load(Ordering::Relaxed).max(1);
+    let mut log = logs.lock().unwrap();
+    if log.len() >= cap {
+        let excess = log.len() + 1 - cap;
+        log.drain(..excess);
+    }
+    log.push(line);
+}
+
+/// A captured output line, broadcast to combined-log subscribers.
+#[derive(Clone, serde::Serialize)]
+pub struct LogEvent {
+    pub source: String,
+    /// Capture time in milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub line: String,
+}
+
+/// Capacity of the combined log broadcast; slow subscribers skip older lines.
+const LOG_EVENT_BUFFER: usize = 1024;
+
+/// Read lines from a child's output on a background thread, storing them in
+/// the merged and per-stream log buffers and broadcasting them as LogEvents. While `paused`
+/// is set the pipe is still drained so the child never blocks on a full pipe,
+/// but the lines are dropped.
+fn capture_output<R: std::io::Read + Send + 'static>(
+    reader: R,
+    tag: &'static str,
+    source: String,
+    logs: LogBuffers,
+    capacity: Arc<AtomicUsize>,
+    paused: Arc<AtomicBool>,
+    events: broadcast::Sender<LogEvent>,
+) {
+    std::thread::spawn(move || {
+        let reader = BufReader::new(reader);
+        for line in reader.lines() {
+            if let Ok(line) = line {
+                if paused.load(Ordering::Relaxed) {
+                    continue;
+                }
+                let stream = if tag == "stderr" { &logs.stderr } else { &logs.stdout };
+                push_log_line(stream, &capacity, line.clone());
+                let line = format!("[{}] {}", tag, line);
+                push_log_line(&logs.merged, &capacity, line.clone());
+                let timestamp = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_millis() as u64)
+                    .unwrap_or(0);
+                // No receivers is the common case; ignore the error
+                let _ = events.send(LogEvent { source: source.clone(), timestamp, line });
+            }
+        }
+    });
+}
+
+/// Processes asked to terminate by `ProcessManager::begin_shutdown`.
+pub struct PendingShutdown {
+    report: Vec<(String, &'static str)>,
+    pending: Vec<(String, ProcessInfo)>,
+}
+
+impl PendingShutdown {
+    /// Wait up to `grace` for the processes to exit, then force-kill the
+    /// rest. Returns `(name, outcome)` where outcome is "stopped cleanly",
+    /// "force-killed" or "not running".
+    pub async fn finish(self, grace: std::time::Duration) -> Vec<(String, &'static str)> {
+        let PendingShutdown { mut report, mut pending } = self;
+        let deadline = std::time::Instant::now() + grace;
+        while !pending.is_empty() && std::time::Instant::now() < deadline {
+            pending.retain_mut(|(name, info)| {
+                if matches!(info.child.try_wait(), Ok(Some(_))) {
+                    report.push((name.clone(), "stopped cleanly"));
+                    false
+                } else {
+                    true
+                }
+            });
+            if !pending.is_empty() {
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            }
+        }
+
+        for (name, mut info) in pending {
+            let _ = info.child.kill();
+            let _ = info.child.wait();
+            report.push((name, "force-killed"));
+        }
+        report
+    }
+}
 
 pub struct ProcessManager {
     processes: HashMap<String, ProcessInfo>,
+    log_events: broadcast::Sender<LogEvent>,
+    /// Most recent exit of each process name, including removed processes.
+    last_exits: HashMap<String, ExitRecord>,
 }
 
 impl ProcessManager {
     pub fn new() -> Self {
         Self {
             processes: HashMap::new(),
+            log_events: broadcast::channel(LOG_EVENT_BUFFER).0,
+            last_exits: HashMap::new(),
         }
     }
 
-    pub fn spawn(
-        &mut self,
-        name: &str,
-        command: &str,
-        args: &[String],
-    ) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
-        self.spawn_with_env(name, command, args, None)
+    /// Subscribe to output lines of all managed processes, in arrival order.
+    pub fn subscribe_logs(&self) -> broadcast::Receiver<LogEvent> {
+        self.log_events.subscribe()
     }
 
     pub fn spawn_with_env(
@@ -55,6 +277,8 @@ impl ProcessManager {
         command: &str,
         args: &[String],
         envs: Option<&HashMap<String, String>>,
+        log_capacity: Option<usize>,
+        priority: Option<ProcessPriority>,
     ) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
         // Kill existing process with the same name
         if self.processes.contains_key(name) {
@@ -63,6 +287,7 @@ impl ProcessManager {
 
         let mut cmd = Command::new(command);
         cmd.args(args)
+            .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
         if let Some(env_map) = envs {
@@ -70,51 +295,73 @@ impl ProcessManager {
                 cmd.env(k, v);
             }
         }
+        apply_priority(&mut cmd, priority.unwrap_or_default());
+        self.track(name, cmd, log_capacity)
+    }
+
+    /// Like `spawn_with_env`, but on Unix the process leads a new process
+    /// group, so processes it starts can be found with `group_members` and
+    /// killed with `kill_process_group` even after it exited.
+    pub fn spawn_group_leader(
+        &mut self,
+        name: &str,
+        command: &str,
+        args: &[String],
+        envs: Option<&HashMap<String, String>>,
+    ) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
+        if self.processes.contains_key(name) {
+            self.kill(name)?;
+        }
+
+        let mut cmd = Command::new(command);
+        cmd.args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(env_map) = envs {
+            cmd.envs(env_map);
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+        self.track(name, cmd, None)
+    }
+
+    /// Spawn `cmd` and track it under `name`, capturing its output.
+    fn track(
+        &mut self,
+        name: &str,
+        mut cmd: Command,
+        log_capacity: Option<usize>,
+    ) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
         let mut child = cmd.spawn()?;
 
         let pid = child.id();
-        let logs = Arc::new(StdMutex::new(Vec::new()));
+        let stdin = child.stdin.take();
+        let logs = LogBuffers::default();
+        let log_capacity = Arc::new(AtomicUsize::new(log_capacity.unwrap_or(DEFAULT_LOG_LINES).max(1)));
+        let log_paused = Arc::new(AtomicBool::new(false));
 
-        // Capture stdout
+        // Capture stdout and stderr
         if let Some(stdout) = child.stdout.take() {
-            let logs_clone = logs.clone();
-            std::thread::spawn(move || {
-                let reader = BufReader::new(stdout);
-                for line in reader.lines() {
-                    if let Ok(line) = line {
-                        let mut log = logs_clone.lock().unwrap();
-                        if log.len() >= MAX_LOG_LINES {
-                            log.remove(0);
-                        }
-                        log.push(format!("[stdout] {}", line));
-                    }
-                }
-            });
+            capture_output(stdout, "stdout", name.to_string(), logs.clone(), log_capacity.clone(), log_paused.clone(), self.log_events.clone());
         }
-
-        // Capture stderr
         if let Some(stderr) = child.stderr.take() {
-            let logs_clone = logs.clone();
-            std::thread::spawn(move || {
-                let reader = BufReader::new(stderr);
-                for line in reader.lines() {
-                    if let Ok(line) = line {
-                        let mut log = logs_clone.lock().unwrap();
-                        if log.len() >= MAX_LOG_LINES {
-                            log.remove(0);
-                        }
-                        log.push(format!("[stderr] {}", line));
-                    }
-                }
-            });
+            capture_output(stderr, "stderr", name.to_string(), logs.clone(), log_capacity.clone(), log_paused.clone(), self.log_events.clone());
         }
 
         self.processes.insert(
             name.to_string(),
             ProcessInfo {
                 child,
+                stdin,
                 status: ProcessStatus::Running,
                 logs,
+                log_capacity,
+                log_paused,
+                started_at: std::time::Instant::now(),
             },
         );
 
@@ -122,17 +369,146 @@ impl ProcessManager {
     }
 
     pub fn is_running(&self, name: &str) -> bool {
-        self.processes.contains_key(name)
+        self.processes
+            .get(name)
+            .map(|info| matches!(info.status, ProcessStatus::Running))
+            .unwrap_or(false)
+    }
+
+    /// Poll managed children for exit. Returns `(name, exit_code)` for each
+    /// process that exited since the last call; its status becomes Stopped
+    /// (exit code 0) or Error, and its logs are kept for inspection.
+    pub fn reap(&mut self) -> Vec<(String, Option<i32>)> {
+        let mut exited = Vec::new();
+        for (name, info) in self.processes.iter_mut() {
+            if !matches!(info.status, ProcessStatus::Running) {
+                continue;
+            }
+            if let Ok(Some(exit)) = info.child.try_wait() {
+                info.status = if exit.success() { ProcessStatus::Stopped } else { ProcessStatus::Error };
+                info.stdin = None;
+                self.last_exits.insert(name.clone(), ExitRecord::new(info, Some(exit), false));
+                exited.push((name.clone(), exit.code()));
+            }
+        }
+        exited
+    }
+
+    /// Whether a tracked process has exited (true if untracked). Unlike
+    /// `reap`, this leaves the exit for the reaper to report.
+    pub fn has_exited(&mut self, name: &str) -> bool {
+        self.processes
+            .get_mut(name)
+            .map(|info| matches!(info.child.try_wait(), Ok(Some(_))))
+            .unwrap_or(true)
     }
 
     pub fn kill(&mut self, name: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         if let Some(mut info) = self.processes.remove(name) {
-            let _ = info.child.kill();
-            let _ = info.child.wait();
+            // An already-reaped process keeps the record of how it really ended
+            if matches!(info.status, ProcessStatus::Running) {
+                let exited = info.child.try_wait().ok().flatten();
+                let _ = info.child.kill();
+                let exit = exited.or_else(|| info.child.wait().ok());
+                self.last_exits.insert(name.to_string(), ExitRecord::new(&info, exit, exited.is_none()));
+            } else {
+                let _ = info.child.wait();
+            }
+        }
+        Ok(())
+    }
+
+    /// How the named process last ended, if it has exited since app start.
+    pub fn last_exit(&self, name: &str) -> Option<ExitRecord> {
+        self.last_exits.get(name).cloned()
+    }
+
+    /// Stop every tracked process: ask each to terminate and hand back the
+    /// ones still running, so the caller can wait for them without holding
+    /// the process manager. Finish with `PendingShutdown::finish`.
+    pub fn begin_shutdown(&mut self) -> PendingShutdown {
+        let mut report = Vec::new();
+        let mut pending = Vec::new();
+        for (name, mut info) in self.processes.drain() {
+            if !matches!(info.status, ProcessStatus::Running) || matches!(info.child.try_wait(), Ok(Some(_))) {
+                report.push((name, "not running"));
+                continue;
+            }
+            // Closing stdin lets stdin-driven agents exit on their own
+            info.stdin = None;
+            terminate_pid(info.child.id());
+            pending.push((name, info));
+        }
+        PendingShutdown { report, pending }
+    }
+
+    /// Write data to a managed process's stdin.
+    /// Fails if the process is unknown or its stdin has been closed.
+    pub fn write_stdin(
+        &mut self,
+        name: &str,
+        data: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let info = self
+            .processes
+            .get_mut(name)
+            .ok_or_else(|| format!("Agent '{}' not found", name))?;
+        let stdin = info
+            .stdin
+            .as_mut()
+            .ok_or_else(|| format!("stdin of '{}' is closed", name))?;
+
+        let result = stdin.write_all(data.as_bytes()).and_then(|_| stdin.flush());
+        if let Err(e) = result {
+            // Child exited or closed its end — drop our handle so later writes fail fast
+            info.stdin = None;
+            return Err(format!("Failed to write to stdin of '{}': {}", name, e).into());
         }
         Ok(())
     }
 
+    /// Close a managed process's stdin (sends EOF).
+    pub fn close_stdin(&mut self, name: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let info = self
+            .processes
+            .get_mut(name)
+            .ok_or_else(|| format!("Agent '{}' not found", name))?;
+        info.stdin = None;
+        Ok(())
+    }
+
+    /// Terminate every process listening on `port`, including ones not started
+    /// by this manager. Returns the pids that were signalled.
+    pub fn kill_by_port(&mut self, port: u16) -> Vec<u32> {
+        let pids = listening_pids(port);
+
+        // Forget tracked processes that are about to be killed
+        let tracked: Vec<String> = self
+            .processes
+            .iter()
+            .filter(|(_, info)| pids.contains(&info.child.id()))
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in tracked {
+            let _ = self.kill(&name);
+        }
+
+        for pid in &pids {
+            kill_pid(*pid);
+        }
+        pids
+    }
+
+    /// Pid of a tracked process.
+    pub fn pid(&self, name: &str) -> Option<u32> {
+        self.processes.get(name).map(|info| info.child.id())
+    }
+
+    /// Pids of all processes currently tracked by this manager.
+    pub fn tracked_pids(&self) -> Vec<u32> {
+        self.processes.values().map(|info| info.child.id()).collect()
+    }
+
     pub fn list(&self) -> Vec<(String, (ProcessStatus, Option<u32>))> {
         self.processes
             .iter()
@@ -146,13 +522,24 @@ impl ProcessManager {
         &self,
         name: &str,
         lines: usize,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        self.get_stream_logs(name, lines, LogStream::Both)
+    }
+
+    /// Last `lines` lines of one output stream, oldest first. Per-stream
+    /// lines carry no `[stdout]`/`[stderr]` prefix.
+    pub fn get_stream_logs(
+        &self,
+        name: &str,
+        lines: usize,
+        stream: LogStream,
     ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
         let info = self
             .processes
             .get(name)
             .ok_or_else(|| format!("Agent '{}' not found", name))?;
 
-        let log = info.logs.lock().unwrap();
+        let log = info.logs.get(stream).lock().unwrap();
         let start = if log.len() > lines {
             log.len() - lines
         } else {
@@ -160,6 +547,251 @@ impl ProcessManager {
         };
         Ok(log[start..].to_vec())
     }
+
+    /// Change how many log lines are kept for a process. Shrinking drops the
+    /// oldest lines immediately.
+    pub fn set_log_capacity(&self, name: &str, lines: usize) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let info = self
+            .processes
+            .get(name)
+            .ok_or_else(|| format!("Agent '{}' not found", name))?;
+        let lines = lines.max(1);
+        info.log_capacity.store(lines, Ordering::Relaxed);
+        for buffer in info.logs.all() {
+            let mut log = buffer.lock().unwrap();
+            if log.len() > lines {
+                let excess = log.len() - lines;
+                log.drain(..excess);
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop or resume storing a process's output. The capture threads keep
+    /// draining its stdout/stderr either way.
+    pub fn set_log_paused(&self, name: &str, paused: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let info = self
+            .processes
+            .get(name)
+            .ok_or_else(|| format!("Agent '{}' not found", name))?;
+        info.log_paused.store(paused, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Seconds since a tracked process was spawned.
+    pub fn uptime_secs(&self, name: &str) -> Option<u64> {
+        self.processes.get(name).map(|info| info.started_at.elapsed().as_secs())
+    }
+
+    /// Empty the captured log buffer of a process. The capture threads push
+    /// under the same mutex, so lines arriving afterwards land in the fresh buffer.
+    pub fn clear_logs(&self, name: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let info = self
+            .processes
+            .get(name)
+            .ok_or_else(|| format!("Agent '{}' not found", name))?;
+        for buffer in info.logs.all() {
+            buffer.lock().unwrap().clear();
+        }
+        Ok(())
+    }
+
+    /// Empty the log buffers of all managed processes.
+    pub fn clear_all_logs(&self) {
+        for info in self.processes.values() {
+            for buffer in info.logs.all() {
+                buffer.lock().unwrap().clear();
+            }
+        }
+    }
+}
+
+/// Find the pids of processes listening on a local TCP port.
+pub fn listening_pids(port: u16) -> Vec<u32> {
+    #[cfg(target_os = "windows")]
+    {
+        // `netstat -ano` rows: Proto  Local Address  Foreign Address  State  PID
+        let output = match Command::new("netstat").args(["-ano", "-p", "TCP"]).output() {
+            Ok(out) => out,
+            Err(_) => return vec![],
+        };
+        let suffix = format!(":{}", port);
+        let mut pids: Vec<u32> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let cols: Vec<&str> = line.split_whitespace().collect();
+                if cols.len() >= 5 && cols[1].ends_with(&suffix) && cols[3] == "LISTENING" {
+                    cols[4].parse().ok()
+                } else {
+                    None
+                }
+            })
+            .collect();
+        pids.sort_unstable();
+        pids.dedup();
+        pids
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let output = match Command::new("lsof")
+            .args(["-ti", &format!("tcp:{}", port), "-sTCP:LISTEN"])
+            .output()
+        {
+            Ok(out) => out,
+            Err(_) => return vec![],
+        };
+        String::from_utf8_lossy(&output.stdout)
+            .split_whitespace()
+            .filter_map(|p| p.parse().ok())
+            .collect()
+    }
+}
+
+/// Ask a process to exit: SIGTERM on Unix, a non-forced taskkill on Windows.
+pub fn terminate_pid(pid: u32) {
+    #[cfg(unix)]
+    // SAFETY: plain syscall on a pid we spawned
+    unsafe {
+        libc::kill(pid as libc::pid_t, libc::SIGTERM);
+    }
+    #[cfg(windows)]
+    let _ = Command::new("taskkill").args(["/PID", &pid.to_string()]).output();
+}
+
+/// Kill a process by pid, outside of manager tracking. Returns true if the kill command succeeded.
+pub fn kill_pid(pid: u32) -> bool {
+    #[cfg(target_os = "windows")]
+    let output = Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/F"])
+        .output();
+    #[cfg(not(target_os = "windows"))]
+    let output = Command::new("kill").arg(pid.to_string()).output();
+    output.map(|o| o.status.success()).unwrap_or(false)
+}
+
+/// Kill a process together with everything it started: its process group on
+/// Unix (the process must have been spawned as a group leader), the whole
+/// tree via `taskkill /T` on Windows.
+pub fn kill_process_group(pid: u32) {
+    #[cfg(unix)]
+    // SAFETY: plain syscall on a process group we created
+    unsafe {
+        libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+    }
+    #[cfg(windows)]
+    let _ = Command::new("taskkill").args(["/PID", &pid.to_string(), "/T", "/F"]).output();
+}
+
+/// Pids of the live processes in process group `pgid` (Unix only; empty on Windows).
+pub fn group_members(pgid: u32) -> Vec<u32> {
+    #[cfg(unix)]
+    {
+        system_processes()
+            .into_iter()
+            // SAFETY: getpgid only reads the process table
+            .filter(|(pid, _)| unsafe { libc::getpgid(*pid as libc::pid_t) } == pgid as libc::pid_t)
+            .map(|(pid, _)| pid)
+            .collect()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pgid;
+        Vec::new()
+    }
+}
+
+/// Snapshot of the system process table as `(pid, command line)` pairs.
+pub fn system_processes() -> Vec<(u32, String)> {
+    #[cfg(target_os = "windows")]
+    {
+        let output = match Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-Command",
+                // -InputObject @(..) keeps a single process serialized as an array
+                "ConvertTo-Json -Compress -InputObject @(Get-CimInstance Win32_Process | Select-Object ProcessId,CommandLine)",
+            ])
+            .output()
+        {
+            Ok(out) => out,
+            Err(_) => return vec![],
+        };
+        let rows: Vec<serde_json::Value> =
+            serde_json::from_slice(&output.stdout).unwrap_or_default();
+        rows.iter()
+            .filter_map(|row| {
+                let pid = row["ProcessId"].as_u64()? as u32;
+                let cmdline = row["CommandLine"].as_str()?.to_string();
+                Some((pid, cmdline))
+            })
+            .collect()
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let output = match Command::new("ps").args(["-axo", "pid=,command="]).output() {
+            Ok(out) => out,
+            Err(_) => return vec![],
+        };
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let (pid, cmdline) = line.trim_start().split_once(' ')?;
+                Some((pid.parse().ok()?, cmdline.trim().to_string()))
+            })
+            .collect()
+    }
+}
+
+/// First local TCP port a pid is listening on, if any.
+pub fn listening_port(pid: u32) -> Option<u16> {
+    #[cfg(target_os = "windows")]
+    {
+        let output = Command::new("netstat").args(["-ano", "-p", "TCP"]).output().ok()?;
+        let pid_str = pid.to_string();
+        String::from_utf8_lossy(&output.stdout).lines().find_map(|line| {
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            if cols.len() >= 5 && cols[3] == "LISTENING" && cols[4] == pid_str {
+                cols[1].rsplit(':').next()?.parse().ok()
+            } else {
+                None
+            }
+        })
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        // -Fn prints one "n<addr>:<port>" line per socket
+        let output = Command::new("lsof")
+            .args(["-a", "-p", &pid.to_string(), "-iTCP", "-sTCP:LISTEN", "-P", "-n", "-Fn"])
+            .output()
+            .ok()?;
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .find_map(|line| line.strip_prefix('n')?.rsplit(':').next()?.parse().ok())
+    }
+}
+
+/// Best-effort executable name of a pid, for user-facing messages.
+pub fn process_name(pid: u32) -> Option<String> {
+    #[cfg(target_os = "windows")]
+    {
+        // CSV row: "node.exe","1234","Console","1","45,000 K"
+        let output = Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+            .output()
+            .ok()?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        let name = text.lines().next()?.split(',').next()?.trim_matches('"').to_string();
+        if name.is_empty() || name.starts_with("INFO:") { None } else { Some(name) }
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let output = Command::new("ps")
+            .args(["-p", &pid.to_string(), "-o", "comm="])
+            .output()
+            .ok()?;
+        let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if name.is_empty() { None } else { Some(name) }
+    }
 }
 
 impl Drop for ProcessManager {
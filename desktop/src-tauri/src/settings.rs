@@ -0,0 +1,191 @@
+//! Persisted desktop settings in ~/.agentos/settings.json. Holds defaults
+//! used when a command is called without an explicit value, such as the
+//! local OpenClaw and CoPaw ports, the outbound HTTP proxy and the
+//! `http_fetch` timeout and identifying headers, window and notification
+//! behavior, per-user workspace locations, on-disk log retention, the base
+//! directory for relative file-skill paths and the skill test commands
+//! the user approved.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex as StdMutex;
+
+pub const DEFAULT_OPENCLAW_PORT: u16 = 18789;
+pub const DEFAULT_COPAW_PORT: u16 = 8088;
+pub const DEFAULT_HTTP_FETCH_TIMEOUT_MS: u64 = 30_000;
+pub const DEFAULT_LOG_RETENTION_MAX_BYTES: u64 = 100 * 1024 * 1024;
+pub const DEFAULT_LOG_RETENTION_MAX_AGE_DAYS: u32 = 14;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub openclaw_port: u16,
+    pub copaw_port: u16,
+    /// Proxy URL (`http://` or `https://`) for outbound requests, if any.
+    pub http_proxy: Option<String>,
+    /// Used by `http_fetch` when the caller passes no `timeout_ms`.
+    pub http_fetch_timeout_ms: u64,
+    /// User-Agent sent on outbound requests instead of reqwest's default.
+    pub user_agent: Option<String>,
+    /// Headers added to `http_fetch` requests for `default_header_hosts`
+    /// unless the request sets them.
+    pub default_headers: HashMap<String, String>,
+    /// Hosts (subdomains included) that receive `default_headers`; when
+    /// empty the headers are sent nowhere.
+    pub default_header_hosts: Vec<String>,
+    /// Closing the main window hides it to the tray instead of quitting.
+    pub close_to_tray: bool,
+    /// Crash and disconnect OS notifications are shown.
+    pub notifications_enabled: bool,
+    /// Workspace directory per user id, for users who moved it off the
+    /// default `~/.agentos/openclaw/users/<uid>/workspace`.
+    pub workspace_roots: HashMap<String, String>,
+    /// Rotated logs in ~/.agentos/logs are pruned, oldest first, while the
+    /// directory exceeds this size. 0 means no size limit.
+    pub log_retention_max_bytes: u64,
+    /// Rotated logs older than this are deleted. 0 means no age limit.
+    pub log_retention_max_age_days: u32,
+    /// Relative paths given to file skills resolve against this directory;
+    /// the home directory when unset.
+    pub file_skill_base_dir: Option<String>,
+    /// Skill test commands the user allowed `validate_skill` to run, keyed by
+    /// `<user id>/<skill name>`. A changed command needs approving again.
+    pub approved_skill_tests: HashMap<String, String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            openclaw_port: DEFAULT_OPENCLAW_PORT,
+            copaw_port: DEFAULT_COPAW_PORT,
+            http_proxy: None,
+            http_fetch_timeout_ms: DEFAULT_HTTP_FETCH_TIMEOUT_MS,
+            user_agent: None,
+            default_headers: HashMap::new(),
+            default_header_hosts: Vec::new(),
+            close_to_tray: true,
+            notifications_enabled: true,
+            workspace_roots: HashMap::new(),
+            log_retention_max_bytes: DEFAULT_LOG_RETENTION_MAX_BYTES,
+            log_retention_max_age_days: DEFAULT_LOG_RETENTION_MAX_AGE_DAYS,
+            file_skill_base_dir: None,
+            approved_skill_tests: HashMap::new(),
+        }
+    }
+}
+
+/// Settings loaded from disk, cached after the first read.
+static SETTINGS: StdMutex<Option<Settings>> = StdMutex::new(None);
+
+fn settings_path() -> Option<std::path::PathBuf> {
+    dirs_next::home_dir().map(|h| h.join(".agentos").join("settings.json"))
+}
+
+/// The cached settings, loading them on first use; a missing or unreadable
+/// file yields the defaults.
+fn cached(slot: &mut Option<Settings>) -> &mut Settings {
+    slot.get_or_insert_with(|| {
+        settings_path()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    })
+}
+
+/// Current settings.
+pub fn get() -> Settings {
+    cached(&mut SETTINGS.lock().unwrap()).clone()
+}
+
+/// Validate and write settings to disk.
+fn persist(settings: &Settings) -> Result<(), String> {
+    if settings.openclaw_port == 0 || settings.copaw_port == 0 {
+        return Err("Ports must be between 1 and 65535".to_string());
+    }
+    if settings.http_fetch_timeout_ms == 0 {
+        return Err("HTTP fetch timeout must be greater than 0".to_string());
+    }
+    if let Some(ref proxy) = settings.http_proxy {
+        if !proxy.starts_with("http://") && !proxy.starts_with("https://") {
+            return Err(format!("Proxy URL must start with http:// or https://, got '{}'", proxy));
+        }
+    }
+    let path = settings_path().ok_or("Cannot find home directory")?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(settings).unwrap())
+        .map_err(|e| format!("Failed to write settings: {}", e))
+}
+
+/// Validate, persist and cache new settings.
+pub fn set(settings: Settings) -> Result<(), String> {
+    let mut slot = SETTINGS.lock().unwrap();
+    persist(&settings)?;
+    *slot = Some(settings);
+    Ok(())
+}
+
+/// Drop the cached settings so the next read reloads them from disk.
+pub fn reload() {
+    *SETTINGS.lock().unwrap() = None;
+}
+
+/// Update settings in place and persist them. The lock is held for the whole
+/// read-modify-write so concurrent updates can't overwrite each other; `f`
+/// must not call back into this module.
+pub fn update(f: impl FnOnce(&mut Settings)) -> Result<(), String> {
+    let mut slot = SETTINGS.lock().unwrap();
+    let mut settings = cached(&mut slot).clone();
+    f(&mut settings);
+    persist(&settings)?;
+    *slot = Some(settings);
+    Ok(())
+}
+
+pub fn openclaw_port() -> u16 {
+    get().openclaw_port
+}
+
+pub fn copaw_port() -> u16 {
+    get().copaw_port
+}
+
+pub fn http_proxy() -> Option<String> {
+    get().http_proxy
+}
+
+pub fn http_fetch_timeout_ms() -> u64 {
+    get().http_fetch_timeout_ms
+}
+
+pub fn user_agent() -> Option<String> {
+    get().user_agent
+}
+
+pub fn close_to_tray() -> bool {
+    get().close_to_tray
+}
+
+pub fn notifications_enabled() -> bool {
+    get().notifications_enabled
+}
+
+/// `(max_bytes, max_age_days)` for on-disk logs; 0 disables a limit.
+pub fn log_retention() -> (u64, u32) {
+    let s = get();
+    (s.log_retention_max_bytes, s.log_retention_max_age_days)
+}
+
+pub fn file_skill_base_dir() -> Option<String> {
+    get().file_skill_base_dir
+}
+
+pub fn approved_skill_test(key: &str) -> Option<String> {
+    get().approved_skill_tests.get(key).cloned()
+}
+
+pub fn workspace_root(user_id: &str) -> Option<String> {
+    get().workspace_roots.get(user_id).cloned()
+}
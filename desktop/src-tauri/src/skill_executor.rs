@@ -1,14 +1,29 @@
 //! Local Skill Executor — Whitelist-based command execution on desktop.
 //!
 //! Only pre-defined function names are allowed:
-//! - `run_shell`: Execute a shell command
+//! - `run_shell`: Execute a shell command (optionally in a pty, streaming output)
 //! - `read_file`: Read a file's contents
+//! - `read_file_range`: Read a byte range or the last N lines of a file
 //! - `write_file`: Write content to a file
-//! - `list_directory`: List directory contents
+//! - `apply_patch`: Apply a unified diff to a file
+//! - `edit_file`: Replace a unique exact string in a file
+//! - `list_directory`: List directory contents (optionally as a depth-limited tree)
+//! - `set_executable`: Set the executable bit on a file or directory tree
 //! - `call_mcp_tool`: Route a tool call to a local MCP bridge
+//! - `run_claude_code`: Run Claude Code, streaming its progress (the server only routes it here when `claude` is installed)
+//!
+//! Calls can also be batched (`execute_batch`), optionally atomically with
+//! file changes rolled back if any call fails.
 
+use serde::Serialize;
 use serde_json::{json, Value};
+use std::collections::{HashSet, VecDeque};
 use std::path::Path;
+use std::sync::{Arc, Mutex as StdMutex};
+
+/// Receives partial output of a running command, forwarded to the server as
+/// `desktop.command.chunk` messages.
+pub type OutputChunks = tokio::sync::mpsc::UnboundedSender<String>;
 
 /// Port of the running MCP bridge HTTP server (set after bridge starts).
 static MCP_BRIDGE_PORT: std::sync::atomic::AtomicU16 = std::sync::atomic::AtomicU16::new(0);
@@ -23,47 +38,1036 @@ pub fn get_mcp_bridge_port() -> u16 {
     MCP_BRIDGE_PORT.load(std::sync::atomic::Ordering::Relaxed)
 }
 
-/// Execute a local command by function name (whitelist approach).
+/// Bearer token the MCP bridge requires on every request (set when the bridge starts).
+static MCP_BRIDGE_TOKEN: StdMutex<Option<String>> = StdMutex::new(None);
+
+/// Set or clear the MCP bridge auth token.
+pub fn set_mcp_bridge_token(token: Option<String>) {
+    *MCP_BRIDGE_TOKEN.lock().unwrap() = token;
+}
+
+/// Get the MCP bridge auth token, if the bridge is running.
+pub fn get_mcp_bridge_token() -> Option<String> {
+    MCP_BRIDGE_TOKEN.lock().unwrap().clone()
+}
+
+/// Functions the user has disabled; `None` until loaded from disk.
+static DISABLED_FUNCTIONS: StdMutex<Option<HashSet<String>>> = StdMutex::new(None);
+
+fn disabled_functions_path() -> Option<std::path::PathBuf> {
+    dirs_next::home_dir().map(|h| h.join(".agentos").join("desktop-skills.json"))
+}
+
+/// Run `f` with the disabled-function set, loading it from disk on first use.
+fn with_disabled_functions<R>(f: impl FnOnce(&mut HashSet<String>) -> R) -> R {
+    let mut guard = DISABLED_FUNCTIONS.lock().unwrap();
+    let set = guard.get_or_insert_with(|| {
+        disabled_functions_path()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|s| serde_json::from_str::<Value>(&s).ok())
+            .and_then(|v| v["disabled"].as_array().cloned())
+            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+            .unwrap_or_default()
+    });
+    f(set)
+}
+
+/// Functions with a built-in handler in `dispatch_local_command`.
+const BUILTIN_HANDLERS: &[&str] = &[
+    "run_shell",
+    "read_file",
+    "read_file_range",
+    "write_file",
+    "apply_patch",
+    "edit_file",
+    "list_directory",
+    "set_executable",
+    "call_mcp_tool",
+    "run_claude_code",
+];
+
+/// Whether the `claude` CLI was found; reported to the server as the
+/// `claude-code` local agent, which gates the server's claude-code skill.
+static CLAUDE_CODE_AVAILABLE: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Record whether Claude Code is installed. Returns true if this changed the
+/// reported local agents (so the caller should re-register).
+pub fn set_claude_code_available(available: bool) -> bool {
+    CLAUDE_CODE_AVAILABLE.swap(available, std::sync::atomic::Ordering::Relaxed) != available
+}
+
+/// Skill manifests registered at runtime by the user, advertised after the built-ins.
+static CUSTOM_MANIFESTS: StdMutex<Vec<Value>> = StdMutex::new(Vec::new());
+
+/// Local agent runtimes reported in desktop.register.
+pub fn local_agents() -> Vec<&'static str> {
+    let mut agents = Vec::new();
+    if CLAUDE_CODE_AVAILABLE.load(std::sync::atomic::Ordering::Relaxed) {
+        agents.push("claude-code");
+    }
+    agents
+}
+
+/// Full manifest of every desktop skill this client can execute:
+/// the built-in skills followed by any custom ones.
+pub fn all_skill_manifests() -> Vec<Value> {
+    let mut manifests = builtin_skill_manifests();
+    manifests.extend(CUSTOM_MANIFESTS.lock().unwrap().iter().cloned());
+    manifests
+}
+
+/// Register (or replace, by skill name) a custom skill manifest. Every
+/// function must map to a built-in handler, either by its own name or through
+/// a `handler` field; an optional `fixedArgs` object is merged over the call args.
+pub fn register_custom_manifest(manifest: Value) -> Result<(), String> {
+    let name = manifest["name"].as_str().ok_or("Manifest is missing 'name'")?;
+    let builtin = builtin_skill_manifests();
+    if builtin.iter().any(|s| s["name"] == name) {
+        return Err(format!("'{}' is a built-in skill", name));
+    }
+    let functions = manifest["functions"]
+        .as_array()
+        .filter(|f| !f.is_empty())
+        .ok_or("Manifest must declare at least one function")?;
+
+    let builtin_functions: HashSet<String> = builtin
+        .iter()
+        .flat_map(|s| s["functions"].as_array().cloned().unwrap_or_default())
+        .filter_map(|f| f["name"].as_str().map(String::from))
+        .collect();
+    let other_custom: HashSet<String> = CUSTOM_MANIFESTS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|s| s["name"] != name)
+        .flat_map(|s| s["functions"].as_array().cloned().unwrap_or_default())
+        .filter_map(|f| f["name"].as_str().map(String::from))
+        .collect();
+
+    for f in functions {
+        let fname = f["name"].as_str().ok_or("Every function needs a 'name'")?;
+        if builtin_functions.contains(fname) || other_custom.contains(fname) {
+            return Err(format!("Function '{}' is already declared by another skill", fname));
+        }
+        let handler = f["handler"].as_str().unwrap_or(fname);
+        if !BUILTIN_HANDLERS.contains(&handler) {
+            return Err(format!("Function '{}' has no handler ('{}' is not a desktop command)", fname, handler));
+        }
+        if !f["parameters"].is_object() {
+            return Err(format!("Function '{}' needs a 'parameters' schema object", fname));
+        }
+        if !f["fixedArgs"].is_null() && !f["fixedArgs"].is_object() {
+            return Err(format!("Function '{}': 'fixedArgs' must be an object", fname));
+        }
+    }
+
+    let mut custom = CUSTOM_MANIFESTS.lock().unwrap();
+    custom.retain(|s| s["name"] != name);
+    custom.push(manifest);
+    Ok(())
+}
+
+/// The registered custom manifest with this skill name, if any.
+pub fn custom_manifest(name: &str) -> Option<Value> {
+    CUSTOM_MANIFESTS.lock().unwrap().iter().find(|s| s["name"] == name).cloned()
+}
+
+/// Remove a custom skill manifest. Returns false if no such custom skill exists.
+pub fn unregister_custom_manifest(name: &str) -> bool {
+    let mut custom = CUSTOM_MANIFESTS.lock().unwrap();
+    let before = custom.len();
+    custom.retain(|s| s["name"] != name);
+    custom.len() != before
+}
+
+/// Map a custom function to its built-in handler and effective args.
+fn resolve_custom_function(function_name: &str, args: &Value) -> Option<(String, Value)> {
+    let custom = CUSTOM_MANIFESTS.lock().unwrap();
+    let f = custom
+        .iter()
+        .flat_map(|s| s["functions"].as_array().cloned().unwrap_or_default())
+        .find(|f| f["name"] == function_name)?;
+    let handler = f["handler"].as_str().unwrap_or(function_name).to_string();
+    let mut merged = if args.is_object() { args.clone() } else { json!({}) };
+    if let (Some(target), Some(fixed)) = (merged.as_object_mut(), f["fixedArgs"].as_object()) {
+        for (k, v) in fixed {
+            target.insert(k.clone(), v.clone());
+        }
+    }
+    Some((handler, merged))
+}
+
+/// Manifests of the skills built into the desktop client.
+fn builtin_skill_manifests() -> Vec<Value> {
+    let manifests = json!([
+        {
+            "name": "shell",
+            "description": "Execute shell commands on the user's desktop computer",
+            "functions": [
+                {
+                    "name": "run_shell",
+                    "description": "Execute a shell command and return stdout/stderr. Use this when the user asks to run commands on their computer.",
+                    "parameters": {
+                        "type": "object",
+                        "properties": {
+                            "command": {
+                                "type": "string",
+                                "description": "The shell command to execute"
+                            },
+                            "timeout": {
+                                "type": "integer",
+                                "description": "Timeout in seconds (default: 30)"
+                            },
+                            "interactive": {
+                                "type": "boolean",
+                                "description": "Run in a pseudo-terminal and stream output; use for tools that need a tty or buffer output when piped. stdout and stderr are merged."
+                            }
+                        },
+                        "required": ["command"]
+                    }
+                }
+            ]
+        },
+        {
+            "name": "filesystem",
+            "description": "Read, write, and list files on the user's desktop computer",
+            "functions": [
+                {
+                    "name": "read_file",
+                    "description": "Read the contents of a file on the user's computer",
+                    "parameters": {
+                        "type": "object",
+                        "properties": {
+                            "path": {
+                                "type": "string",
+                                "description": "Absolute path to the file (~ and $VAR are expanded)"
+                            }
+                        },
+                        "required": ["path"]
+                    }
+                },
+                {
+                    "name": "read_file_range",
+                    "description": "Read part of a file on the user's computer: a byte range (offset/length) or the last N lines (tail_lines). Returns the slice plus the total file size. Prefer this over read_file for large files such as logs.",
+                    "parameters": {
+                        "type": "object",
+                        "properties": {
+                            "path": {
+                                "type": "string",
+                                "description": "Absolute path to the file (~ and $VAR are expanded)"
+                            },
+                            "offset": {
+                                "type": "integer",
+                                "description": "Byte offset to start reading from (default: 0)"
+                            },
+                            "length": {
+                                "type": "integer",
+                                "description": "Number of bytes to read (default and max: 1 MiB)"
+                            },
+                            "tail_lines": {
+                                "type": "integer",
+                                "description": "Return the last N lines instead of a byte range"
+                            }
+                        },
+                        "required": ["path"]
+                    }
+                },
+                {
+                    "name": "write_file",
+                    "description": "Write content to a file on the user's computer",
+                    "parameters": {
+                        "type": "object",
+                        "properties": {
+                            "path": {
+                                "type": "string",
+                                "description": "Absolute path to the file (~ and $VAR are expanded)"
+                            },
+                            "content": {
+                                "type": "string",
+                                "description": "Content to write"
+                            }
+                        },
+                        "required": ["path", "content"]
+                    }
+                },
+                {
+                    "name": "apply_patch",
+                    "description": "Apply a unified diff to a file on the user's computer. Returns the conflicting hunk if the patch does not apply; the file is left unchanged in that case.",
+                    "parameters": {
+                        "type": "object",
+                        "properties": {
+                            "path": {
+                                "type": "string",
+                                "description": "Absolute path to the file (~ and $VAR are expanded)"
+                            },
+                            "patch": {
+                                "type": "string",
+                                "description": "Unified diff to apply"
+                            }
+                        },
+                        "required": ["path", "patch"]
+                    }
+                },
+                {
+                    "name": "edit_file",
+                    "description": "Replace one exact occurrence of old_str with new_str in a file. Fails if old_str is missing or not unique.",
+                    "parameters": {
+                        "type": "object",
+                        "properties": {
+                            "path": {
+                                "type": "string",
+                                "description": "Absolute path to the file (~ and $VAR are expanded)"
+                            },
+                            "old_str": {
+                                "type": "string",
+                                "description": "Exact text to replace (must occur exactly once)"
+                            },
+                            "new_str": {
+                                "type": "string",
+                                "description": "Replacement text"
+                            }
+                        },
+                        "required": ["path", "old_str", "new_str"]
+                    }
+                },
+                {
+                    "name": "list_directory",
+                    "description": "List files and directories in a path on the user's computer",
+                    "parameters": {
+                        "type": "object",
+                        "properties": {
+                            "path": {
+                                "type": "string",
+                                "description": "Absolute path to the directory (~ and $VAR are expanded)"
+                            },
+                            "recursive": {
+                                "type": "boolean",
+                                "description": "Walk subdirectories and return a flat list of relative paths (default false)"
+                            },
+                            "max_depth": {
+                                "type": "integer",
+                                "description": "Maximum depth when recursive (default 3, max 10)"
+                            },
+                            "include_hidden": {
+                                "type": "boolean",
+                                "description": "Include hidden entries, .git and node_modules when recursive (default false)"
+                            }
+                        },
+                        "required": ["path"]
+                    }
+                },
+                {
+                    "name": "set_executable",
+                    "description": "Set the executable bit on a file (e.g. a script), or on every file in a directory. No-op on Windows.",
+                    "parameters": {
+                        "type": "object",
+                        "properties": {
+                            "path": {
+                                "type": "string",
+                                "description": "Absolute path to the file or directory (~ and $VAR are expanded)"
+                            },
+                            "recursive": {
+                                "type": "boolean",
+                                "description": "When path is a directory, apply to all files beneath it (default false)"
+                            }
+                        },
+                        "required": ["path"]
+                    }
+                }
+            ]
+        }
+    ]);
+    manifests.as_array().cloned().unwrap_or_default()
+}
+
+/// Skill manifests with disabled functions removed (skills left empty are dropped).
+pub fn enabled_skill_manifests() -> Vec<Value> {
+    with_disabled_functions(|disabled| {
+        all_skill_manifests()
+            .into_iter()
+            .filter_map(|mut skill| {
+                let functions: Vec<Value> = skill["functions"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|f| !disabled.contains(f["name"].as_str().unwrap_or("")))
+                    .collect();
+                if functions.is_empty() {
+                    return None;
+                }
+                skill["functions"] = json!(functions);
+                Some(skill)
+            })
+            .collect()
+    })
+}
+
+/// Enable or disable a skill (all its functions) or a single function by name.
+/// The setting is persisted to ~/.agentos/desktop-skills.json.
+pub fn set_skill_enabled(name: &str, enabled: bool) -> Result<(), String> {
+    let manifests = all_skill_manifests();
+    let functions: Vec<String> = match manifests.iter().find(|s| s["name"] == name) {
+        Some(skill) => skill["functions"]
+            .as_array()
+            .map(|arr| arr.iter().filter_map(|f| f["name"].as_str().map(String::from)).collect())
+            .unwrap_or_default(),
+        None if manifests
+            .iter()
+            .flat_map(|s| s["functions"].as_array().cloned().unwrap_or_default())
+            .any(|f| f["name"] == name) => vec![name.to_string()],
+        None => return Err(format!("Unknown desktop skill or function: {}", name)),
+    };
+
+    let disabled: Vec<String> = with_disabled_functions(|set| {
+        for f in functions {
+            if enabled {
+                set.remove(&f);
+            } else {
+                set.insert(f);
+            }
+        }
+        let mut list: Vec<String> = set.iter().cloned().collect();
+        list.sort();
+        list
+    });
+
+    if let Some(path) = disabled_functions_path() {
+        let _ = std::fs::create_dir_all(path.parent().unwrap());
+        std::fs::write(&path, serde_json::to_string_pretty(&json!({ "disabled": disabled })).unwrap())
+            .map_err(|e| format!("Failed to save desktop skill settings: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Whether a function may be executed (not disabled by the user).
+pub fn is_function_enabled(function_name: &str) -> bool {
+    with_disabled_functions(|disabled| !disabled.contains(function_name))
+}
+
+/// Every skill with its functions and their enabled state.
+pub fn get_desktop_skills() -> Vec<Value> {
+    with_disabled_functions(|disabled| {
+        all_skill_manifests()
+            .iter()
+            .map(|skill| {
+                let functions: Vec<Value> = skill["functions"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|f| {
+                        let name = f["name"].as_str().unwrap_or("");
+                        json!({ "name": name, "enabled": !disabled.contains(name) })
+                    })
+                    .collect();
+                let enabled = functions.iter().any(|f| f["enabled"] == true);
+                json!({
+                    "skill": skill["name"],
+                    "description": skill["description"],
+                    "enabled": enabled,
+                    "functions": functions,
+                })
+            })
+            .collect()
+    })
+}
+
+/// Maximum number of entries kept in the in-memory command audit log.
+const MAX_AUDIT_ENTRIES: usize = 500;
+
+/// Argument keys whose values are never written to the audit log.
+const REDACTED_ARG_KEYS: &[&str] = &["content", "token", "password", "secret", "apiKey", "api_key", "authToken"];
+
+/// One recorded `desktop.command` invocation.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub timestamp: u64,
+    pub function: String,
+    /// Built-in handler a custom function resolved to; None for built-ins.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub handler: Option<String>,
+    pub args: Value,
+    pub success: bool,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// Ring buffer of recent command invocations (newest at the back).
+static COMMAND_AUDIT: StdMutex<VecDeque<AuditEntry>> = StdMutex::new(VecDeque::new());
+
+/// Whether audit entries are also appended to ~/.agentos/command-audit.jsonl.
+static AUDIT_TO_DISK: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Enable or disable persisting audit entries to disk.
+pub fn set_audit_to_disk(enabled: bool) {
+    AUDIT_TO_DISK.store(enabled, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// Return up to `limit` most recent audit entries, newest first.
+pub fn get_command_audit(limit: usize) -> Vec<AuditEntry> {
+    let audit = COMMAND_AUDIT.lock().unwrap();
+    audit.iter().rev().take(limit).cloned().collect()
+}
+
+/// Replace sensitive or bulky argument values before they are recorded.
+fn redact_args(args: &Value) -> Value {
+    match args {
+        Value::Object(map) => {
+            let redacted = map
+                .iter()
+                .map(|(k, v)| {
+                    let v = if REDACTED_ARG_KEYS.contains(&k.as_str()) {
+                        json!("[redacted]")
+                    } else {
+                        redact_args(v)
+                    };
+                    (k.clone(), v)
+                })
+                .collect();
+            Value::Object(redacted)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(redact_args).collect()),
+        Value::String(s) if s.chars().count() > 200 => {
+            let head: String = s.chars().take(200).collect();
+            json!(format!("{}... [{} chars]", head, s.chars().count()))
+        }
+        other => other.clone(),
+    }
+}
+
+fn record_audit(entry: AuditEntry) {
+    if AUDIT_TO_DISK.load(std::sync::atomic::Ordering::Relaxed) {
+        if let Some(home) = dirs_next::home_dir() {
+            let path = home.join(".agentos").join("command-audit.jsonl");
+            let line = serde_json::to_string(&entry).unwrap_or_default();
+            let written = std::fs::create_dir_all(path.parent().unwrap()).and_then(|_| {
+                use std::io::Write;
+                let mut f = std::fs::OpenOptions::new().create(true).append(true).open(&path)?;
+                writeln!(f, "{}", line)
+            });
+            if let Err(e) = written {
+                println!("[SkillExecutor] Failed to write audit log: {}", e);
+            }
+        }
+    }
+
+    let mut audit = COMMAND_AUDIT.lock().unwrap();
+    if audit.len() >= MAX_AUDIT_ENTRIES {
+        audit.pop_front();
+    }
+    audit.push_back(entry);
+}
+
+/// Why a desktop command failed, reported to the server as `{ code, message }`
+/// so it can decide whether a retry makes sense.
+#[derive(Debug, Clone)]
+pub enum SkillError {
+    UnknownFunction(String),
+    Disabled(String),
+    InvalidArgs(String),
+    NotFound(String),
+    PermissionDenied(String),
+    Timeout(String),
+    /// A dependency such as the MCP bridge is not running or unreachable.
+    Unavailable(String),
+    Cancelled(String),
+    Failed(String),
+    /// A `Failed` error with extra fields for the `desktop.result` error payload.
+    FailedWith(String, Value),
+}
+
+impl SkillError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            SkillError::UnknownFunction(_) => "unknown_function",
+            SkillError::Disabled(_) => "disabled",
+            SkillError::InvalidArgs(_) => "invalid_args",
+            SkillError::NotFound(_) => "not_found",
+            SkillError::PermissionDenied(_) => "permission_denied",
+            SkillError::Timeout(_) => "timeout",
+            SkillError::Unavailable(_) => "unavailable",
+            SkillError::Cancelled(_) => "cancelled",
+            SkillError::Failed(_) | SkillError::FailedWith(..) => "failed",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            SkillError::UnknownFunction(m)
+            | SkillError::Disabled(m)
+            | SkillError::InvalidArgs(m)
+            | SkillError::NotFound(m)
+            | SkillError::PermissionDenied(m)
+            | SkillError::Timeout(m)
+            | SkillError::Unavailable(m)
+            | SkillError::Cancelled(m)
+            | SkillError::Failed(m)
+            | SkillError::FailedWith(m, _) => m,
+        }
+    }
+
+    /// The `error` payload of a failed `desktop.result`.
+    pub fn to_payload(&self) -> Value {
+        let mut payload = json!({ "code": self.code(), "message": self.message() });
+        if let (SkillError::FailedWith(_, Value::Object(extra)), Some(map)) = (self, payload.as_object_mut()) {
+            map.extend(extra.clone());
+        }
+        payload
+    }
+}
+
+impl std::fmt::Display for SkillError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+/// Required string argument, or an `InvalidArgs` error naming it.
+fn arg_str<'a>(args: &'a Value, key: &str) -> Result<&'a str, SkillError> {
+    args[key]
+        .as_str()
+        .ok_or_else(|| SkillError::InvalidArgs(format!("Missing '{}' argument", key)))
+}
+
+/// Required path argument, normalized with `expand_path`.
+fn arg_path(args: &Value, key: &str) -> Result<String, SkillError> {
+    expand_path(arg_str(args, key)?)
+}
+
+/// Expand a leading `~`, a leading `$VAR` and `${VAR}` references anywhere in
+/// a path, then resolve it against the file-skill base directory (the home
+/// directory unless configured) if it is still relative. A `${VAR}` that is
+/// unset is an error, so `${UNSET}/x` never becomes `/x`; any other `$` (an
+/// unset leading `$VAR`, `a$b.txt`, `C:\$Recycle.Bin`) is kept literally.
+/// When a base directory is configured, paths outside it are refused.
+pub fn expand_path(raw: &str) -> Result<String, SkillError> {
+    let home = dirs_next::home_dir()
+        .ok_or_else(|| SkillError::Failed("Cannot find home directory".to_string()))?;
+    let base = crate::settings::file_skill_base_dir().map(std::path::PathBuf::from);
+    expand_path_in(raw, &home, base.as_deref())
+}
+
+/// `expand_path` against the home directory, ignoring any configured base
+/// directory (used to choose a new base directory).
+pub fn expand_path_from_home(raw: &str) -> Result<String, SkillError> {
+    let home = dirs_next::home_dir()
+        .ok_or_else(|| SkillError::Failed("Cannot find home directory".to_string()))?;
+    expand_path_in(raw, &home, None)
+}
+
+fn expand_path_in(raw: &str, home: &Path, base: Option<&Path>) -> Result<String, SkillError> {
+    let mut expanded = String::with_capacity(raw.len());
+    let mut rest = raw;
+    if rest == "~" || rest.starts_with("~/") || rest.starts_with("~\\") {
+        expanded.push_str(&home.to_string_lossy());
+        rest = &rest[1..];
+    } else if let Some(after) = rest.strip_prefix('$').filter(|a| !a.starts_with('{')) {
+        let end = after
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(after.len());
+        let at_separator = after[end..].is_empty() || after[end..].starts_with(['/', '\\']);
+        if end > 0 && at_separator {
+            if let Ok(value) = std::env::var(&after[..end]) {
+                expanded.push_str(&value);
+                rest = &after[end..];
+            }
+        }
+    }
+    while let Some(start) = rest.find("${") {
+        expanded.push_str(&rest[..start]);
+        let braced = &rest[start + 2..];
+        let end = braced
+            .find('}')
+            .ok_or_else(|| SkillError::InvalidArgs(format!("Unclosed '${{' in path '{}'", raw)))?;
+        let name = &braced[..end];
+        let value = std::env::var(name).map_err(|_| {
+            SkillError::InvalidArgs(format!("Environment variable '{}' in path '{}' is not set", name, raw))
+        })?;
+        expanded.push_str(&value);
+        rest = &braced[end + 1..];
+    }
+    expanded.push_str(rest);
+
+    let path = if Path::new(&expanded).is_relative() {
+        base.unwrap_or(home).join(&expanded)
+    } else {
+        std::path::PathBuf::from(&expanded)
+    };
+    let path = normalize_lexically(&path);
+    if let Some(base) = base {
+        let base = normalize_lexically(base);
+        if !path.starts_with(&base) {
+            return Err(SkillError::PermissionDenied(format!(
+                "Path '{}' is outside the file skill base directory {}",
+                raw,
+                base.display()
+            )));
+        }
+    }
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Resolve `.` and `..` components without touching the filesystem.
+fn normalize_lexically(path: &Path) -> std::path::PathBuf {
+    use std::path::Component;
+    let mut out = std::path::PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if matches!(out.components().next_back(), Some(Component::Normal(_))) {
+                    out.pop();
+                } else if !out.has_root() {
+                    out.push("..");
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Classify an I/O error by kind, keeping `context` as the message prefix.
+fn io_error(context: &str, e: std::io::Error) -> SkillError {
+    let message = format!("{}: {}", context, e);
+    match e.kind() {
+        std::io::ErrorKind::NotFound => SkillError::NotFound(message),
+        std::io::ErrorKind::PermissionDenied => SkillError::PermissionDenied(message),
+        std::io::ErrorKind::TimedOut => SkillError::Timeout(message),
+        _ => SkillError::Failed(message),
+    }
+}
+
+fn http_error(context: &str, e: reqwest::Error) -> SkillError {
+    let message = format!("{}: {}", context, e);
+    if e.is_timeout() {
+        SkillError::Timeout(message)
+    } else if e.is_connect() {
+        SkillError::Unavailable(message)
+    } else {
+        SkillError::Failed(message)
+    }
+}
+
+/// Execute a local command by function name and record it in the audit log.
+/// Commands that produce output incrementally send it to `chunks`, if given.
 pub async fn execute_local_command(
     function_name: &str,
     args: &Value,
-) -> Result<Value, String> {
+    chunks: Option<OutputChunks>,
+) -> Result<Value, SkillError> {
+    let started = std::time::Instant::now();
+    let custom = resolve_custom_function(function_name, args);
+    // A custom function is also blocked when its underlying handler is disabled
+    let handler = custom.as_ref().map(|(handler, _)| handler.as_str()).unwrap_or(function_name);
+    let result = if !is_function_enabled(function_name) {
+        Err(SkillError::Disabled(format!("Function '{}' is disabled on this desktop", function_name)))
+    } else if !is_function_enabled(handler) {
+        Err(SkillError::Disabled(format!(
+            "Function '{}' runs '{}', which is disabled on this desktop",
+            function_name, handler
+        )))
+    } else {
+        let mut cancelled = CancelledAudit {
+            function: function_name,
+            handler: custom.as_ref().map(|(handler, _)| handler.clone()),
+            args,
+            started,
+            armed: true,
+        };
+        let result = match custom {
+            Some((ref handler, ref merged_args)) => dispatch_local_command(handler, merged_args, chunks).await,
+            None => dispatch_local_command(function_name, args, chunks).await,
+        };
+        cancelled.armed = false;
+        result
+    };
+
+    record_audit(AuditEntry {
+        timestamp: now_millis(),
+        function: function_name.to_string(),
+        handler: custom.map(|(handler, _)| handler),
+        args: redact_args(args),
+        success: result.is_ok(),
+        error: result.as_ref().err().map(|e| e.to_string()),
+        duration_ms: started.elapsed().as_millis() as u64,
+    });
+
+    result
+}
+
+/// Records a cancelled audit entry if a command is dropped mid-run (its task
+/// was aborted by `desktop.command.cancel`) and so never reaches the normal
+/// `record_audit` call.
+struct CancelledAudit<'a> {
+    function: &'a str,
+    handler: Option<String>,
+    args: &'a Value,
+    started: std::time::Instant,
+    armed: bool,
+}
+
+impl Drop for CancelledAudit<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            record_audit(AuditEntry {
+                timestamp: now_millis(),
+                function: self.function.to_string(),
+                handler: self.handler.take(),
+                args: redact_args(self.args),
+                success: false,
+                error: Some(SkillError::Cancelled("Command cancelled".to_string()).to_string()),
+                duration_ms: self.started.elapsed().as_millis() as u64,
+            });
+        }
+    }
+}
+
+/// Functions whose side effects a batch cannot undo.
+const IRREVERSIBLE_FUNCTIONS: &[&str] = &["run_shell", "call_mcp_tool", "run_claude_code"];
+
+/// How to undo one side effect of an atomic batch.
+enum BatchUndo {
+    /// The file did not exist before the batch wrote it.
+    Delete(std::path::PathBuf),
+    /// The file was overwritten; the original is saved at `backup`.
+    Restore { path: std::path::PathBuf, backup: std::path::PathBuf },
+    Permissions(std::path::PathBuf, std::fs::Permissions),
+}
+
+/// Side effects of an atomic batch, undone in reverse order on failure. Backups
+/// of overwritten files live in a temp directory removed when the journal drops.
+/// A journal dropped while still armed (the batch was cancelled) rolls back too.
+struct BatchJournal {
+    backup_dir: std::path::PathBuf,
+    undo: Vec<BatchUndo>,
+    armed: bool,
+}
+
+impl BatchJournal {
+    fn new() -> Self {
+        Self {
+            backup_dir: std::env::temp_dir().join(format!("agentos-batch-{}", uuid::Uuid::new_v4())),
+            undo: Vec::new(),
+            armed: true,
+        }
+    }
+
+    /// Record what `function` is about to change so it can be undone.
+    fn prepare(&mut self, function: &str, args: &Value) -> Result<(), SkillError> {
+        // Path errors are left for the call itself to report
+        let Ok(path) = arg_path(args, "path") else {
+            return Ok(());
+        };
+        let path = std::path::PathBuf::from(path);
+        match function {
+            "write_file" | "apply_patch" | "edit_file" => {
+                if !path.exists() {
+                    self.undo.push(BatchUndo::Delete(path));
+                    return Ok(());
+                }
+                std::fs::create_dir_all(&self.backup_dir)
+                    .map_err(|e| io_error("Failed to create batch backup directory", e))?;
+                let backup = self.backup_dir.join(self.undo.len().to_string());
+                std::fs::copy(&path, &backup).map_err(|e| io_error("Failed to back up file", e))?;
+                self.undo.push(BatchUndo::Restore { path, backup });
+            }
+            "set_executable" => {
+                let mut stack = vec![path];
+                while let Some(p) = stack.pop() {
+                    let Ok(meta) = std::fs::metadata(&p) else { continue };
+                    if meta.is_dir() {
+                        if let Ok(entries) = std::fs::read_dir(&p) {
+                            stack.extend(entries.flatten().map(|e| e.path()));
+                        }
+                    } else {
+                        self.undo.push(BatchUndo::Permissions(p, meta.permissions()));
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Undo recorded side effects, newest first. Returns what was restored,
+    /// deleted, and any paths that could not be put back.
+    fn rollback(&mut self) -> Value {
+        self.armed = false;
+        let mut restored = Vec::new();
+        let mut deleted = Vec::new();
+        let mut failed = Vec::new();
+        for undo in self.undo.drain(..).rev() {
+            match undo {
+                BatchUndo::Delete(path) => match std::fs::remove_file(&path) {
+                    Ok(()) => deleted.push(path.to_string_lossy().to_string()),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(e) => failed.push(format!("{}: {}", path.display(), e)),
+                },
+                BatchUndo::Restore { path, backup } => match std::fs::copy(&backup, &path) {
+                    Ok(_) => restored.push(path.to_string_lossy().to_string()),
+                    Err(e) => failed.push(format!("{}: {}", path.display(), e)),
+                },
+                BatchUndo::Permissions(path, perms) => {
+                    if let Err(e) = std::fs::set_permissions(&path, perms) {
+                        failed.push(format!("{}: {}", path.display(), e));
+                    }
+                }
+            }
+        }
+        restored.sort();
+        restored.dedup();
+        json!({ "restored": restored, "deleted": deleted, "failed": failed })
+    }
+}
+
+impl Drop for BatchJournal {
+    fn drop(&mut self) {
+        if self.armed && !self.undo.is_empty() {
+            println!("[SkillExecutor] batch aborted, rolling back {} change(s)", self.undo.len());
+            self.rollback();
+        }
+        let _ = std::fs::remove_dir_all(&self.backup_dir);
+    }
+}
+
+/// Execute an ordered list of `{ command, args }` calls. Without `atomic`,
+/// every call runs and reports its own result. With `atomic`, execution stops
+/// at the first failure and file changes made by earlier calls are rolled back
+/// (created files deleted, overwritten ones restored, permissions reset);
+/// shell, MCP and Claude Code calls cannot be undone and are listed as such.
+/// The combined result's `success` is true only if every call succeeded.
+/// Calls don't stream: their output is returned whole in `results`, since the
+/// server only reassembles chunks for a single command's result.
+pub async fn execute_batch(calls: &Value, atomic: bool) -> Result<Value, SkillError> {
+    let calls = calls
+        .as_array()
+        .filter(|c| !c.is_empty())
+        .ok_or_else(|| SkillError::InvalidArgs("'calls' must be a non-empty array".to_string()))?;
+
+    println!("[SkillExecutor] batch: {} call(s) (atomic={})", calls.len(), atomic);
+
+    let mut journal = atomic.then(BatchJournal::new);
+    let mut results = Vec::with_capacity(calls.len());
+    let mut irreversible: Vec<usize> = Vec::new();
+    let mut failed_at = None;
+
+    for (index, call) in calls.iter().enumerate() {
+        let function = call["command"].as_str().unwrap_or("");
+        let args = &call["args"];
+
+        let prepared = match journal.as_mut() {
+            Some(journal) => {
+                let (handler, merged_args) = resolve_custom_function(function, args)
+                    .unwrap_or_else(|| (function.to_string(), args.clone()));
+                if IRREVERSIBLE_FUNCTIONS.contains(&handler.as_str()) {
+                    irreversible.push(index);
+                }
+                journal.prepare(&handler, &merged_args)
+            }
+            None => Ok(()),
+        };
+        let result = match prepared {
+            Ok(()) => execute_local_command(function, args, None).await,
+            Err(e) => Err(e),
+        };
+
+        // A call that reports it changed nothing (`applied: false`) fails the batch too
+        let result = result.and_then(|data| match data.get("applied") {
+            Some(Value::Bool(false)) => Err(SkillError::FailedWith(
+                format!("'{}' did not apply its change", function),
+                data,
+            )),
+            _ => Ok(data),
+        });
+        let failed = result.is_err();
+        results.push(match result {
+            Ok(data) => json!({ "index": index, "command": function, "success": true, "data": data }),
+            Err(err) => json!({ "index": index, "command": function, "success": false, "error": err.to_payload() }),
+        });
+        if failed && failed_at.is_none() {
+            failed_at = Some(index);
+            if atomic {
+                break;
+            }
+        }
+    }
+
+    let mut combined = json!({
+        "success": failed_at.is_none(),
+        "atomic": atomic,
+        "results": results,
+    });
+    if let Some(index) = failed_at {
+        combined["failedAt"] = json!(index);
+    }
+    if let Some(mut journal) = journal {
+        match failed_at {
+            Some(index) => {
+                let mut rollback = journal.rollback();
+                rollback["irreversible"] = json!(irreversible);
+                println!("[SkillExecutor] batch failed at call {}, rolled back: {}", index, rollback);
+                combined["rollback"] = rollback;
+            }
+            None => journal.armed = false,
+        }
+    }
+
+    Ok(combined)
+}
+
+/// Dispatch a local command by function name (whitelist approach).
+async fn dispatch_local_command(
+    function_name: &str,
+    args: &Value,
+    chunks: Option<OutputChunks>,
+) -> Result<Value, SkillError> {
     match function_name {
-        "run_shell" => run_shell(args).await,
-        "read_file" => read_file(args),
+        "run_shell" => run_shell(args, chunks).await,
+        "read_file" => read_file(args, chunks),
+        "read_file_range" => read_file_range(args),
         "write_file" => write_file(args),
+        "apply_patch" => apply_patch(args),
+        "edit_file" => edit_file(args),
         "list_directory" => list_directory(args),
+        "set_executable" => set_executable(args),
         "call_mcp_tool" => call_mcp_tool(args).await,
-        "run_claude_code" => run_claude_code(args).await,
-        _ => Err(format!("Unknown function: {}", function_name)),
+        "run_claude_code" => run_claude_code(args, chunks).await,
+        _ => Err(SkillError::UnknownFunction(format!("Unknown function: {}", function_name))),
     }
 }
 
-/// Execute a shell command and return stdout/stderr.
-async fn run_shell(args: &Value) -> Result<Value, String> {
-    let command = args["command"]
-        .as_str()
-        .ok_or("Missing 'command' argument")?
+/// Execute a shell command and return stdout/stderr. With `interactive: true`
+/// it runs in a pty instead (see `run_shell_pty`).
+async fn run_shell(args: &Value, chunks: Option<OutputChunks>) -> Result<Value, SkillError> {
+    let command = arg_str(args, "command")?
         .trim();
 
     let timeout_secs = args["timeout"].as_u64().unwrap_or(30);
 
+    if args["interactive"].as_bool().unwrap_or(false) {
+        return run_shell_pty(command, timeout_secs, chunks).await;
+    }
+
     println!("[SkillExecutor] run_shell: {}", command);
 
     let child = tokio::process::Command::new(if cfg!(target_os = "windows") { "cmd" } else { "sh" })
         .args(if cfg!(target_os = "windows") { vec!["/C", command] } else { vec!["-c", command] })
         .stdout(std::process::Stdio::piped())
         .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true) // killed if the command is cancelled
         .spawn()
-        .map_err(|e| format!("Failed to spawn command: {}", e))?;
+        .map_err(|e| io_error("Failed to spawn command", e))?;
 
     let result = tokio::time::timeout(
         std::time::Duration::from_secs(timeout_secs),
         child.wait_with_output(),
     )
     .await
-    .map_err(|_| format!("Command timed out after {}s", timeout_secs))?
-    .map_err(|e| format!("Command failed: {}", e))?;
+    .map_err(|_| SkillError::Timeout(format!("Command timed out after {}s", timeout_secs)))?
+    .map_err(|e| io_error("Command failed", e))?;
 
     let stdout = String::from_utf8_lossy(&result.stdout).to_string();
     let stderr = String::from_utf8_lossy(&result.stderr).to_string();
@@ -75,37 +1079,256 @@ async fn run_shell(args: &Value) -> Result<Value, String> {
     }))
 }
 
-/// Read a file's contents.
-fn read_file(args: &Value) -> Result<Value, String> {
-    let path = args["path"]
-        .as_str()
-        .ok_or("Missing 'path' argument")?;
+/// Terminal size reported to commands run in a pty.
+const PTY_ROWS: u16 = 24;
+const PTY_COLS: u16 = 120;
+
+/// How long to keep reading a pty after the command exits, for output still
+/// in flight (or held open by a background grandchild).
+const PTY_DRAIN_MS: u64 = 2000;
+
+/// Kills the pty child when dropped (timeout or cancellation) unless disarmed.
+struct PtyKillGuard(Option<Box<dyn portable_pty::ChildKiller + Send + Sync>>);
+
+impl Drop for PtyKillGuard {
+    fn drop(&mut self) {
+        if let Some(mut killer) = self.0.take() {
+            let _ = killer.kill();
+        }
+    }
+}
+
+/// Run a shell command attached to a pseudo-terminal so tools that check for
+/// a tty (progress bars, prompts, line buffering) behave as in a terminal.
+/// Output arrives on one stream and is sent to `chunks` as it is read; the
+/// full text is returned as `stdout`.
+async fn run_shell_pty(command: &str, timeout_secs: u64, chunks: Option<OutputChunks>) -> Result<Value, SkillError> {
+    use portable_pty::{native_pty_system, CommandBuilder, PtySize};
+
+    println!("[SkillExecutor] run_shell (pty): {}", command);
+
+    let portable_pty::PtyPair { master, slave } = native_pty_system()
+        .openpty(PtySize { rows: PTY_ROWS, cols: PTY_COLS, pixel_width: 0, pixel_height: 0 })
+        .map_err(|e| SkillError::Failed(format!("Failed to open pty: {}", e)))?;
+    let mut cmd = if cfg!(target_os = "windows") {
+        let mut cmd = CommandBuilder::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = CommandBuilder::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    };
+    if let Ok(cwd) = std::env::current_dir() {
+        cmd.cwd(cwd);
+    }
+    let mut child = slave
+        .spawn_command(cmd)
+        .map_err(|e| SkillError::Failed(format!("Failed to spawn command: {}", e)))?;
+    // Only the child may hold the slave end, or reads never see EOF
+    drop(slave);
+    let mut guard = PtyKillGuard(Some(child.clone_killer()));
+
+    let mut reader = master
+        .try_clone_reader()
+        .map_err(|e| SkillError::Failed(format!("Failed to read pty: {}", e)))?;
+    let output = Arc::new(StdMutex::new(Vec::new()));
+    // Taken after draining, so the chunk stream ends even if the reader is still blocked
+    let chunks = Arc::new(StdMutex::new(chunks));
+    let (output_for_reader, chunks_for_reader) = (output.clone(), chunks.clone());
+    let reader_task = tokio::task::spawn_blocking(move || {
+        use std::io::Read;
+        let mut buf = [0u8; 4096];
+        loop {
+            // Linux reports EIO once the child side closes; treat any error as EOF
+            let n = match reader.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            output_for_reader.lock().unwrap().extend_from_slice(&buf[..n]);
+            if let Some(ref tx) = *chunks_for_reader.lock().unwrap() {
+                let _ = tx.send(String::from_utf8_lossy(&buf[..n]).to_string());
+            }
+        }
+    });
+
+    let status = tokio::time::timeout(
+        std::time::Duration::from_secs(timeout_secs),
+        tokio::task::spawn_blocking(move || child.wait()),
+    )
+    .await
+    .map_err(|_| SkillError::Timeout(format!("Command timed out after {}s", timeout_secs)))
+    .and_then(|joined| joined.map_err(|e| SkillError::Failed(format!("Command failed: {}", e))))
+    .and_then(|waited| waited.map_err(|e| io_error("Command failed", e)));
+    if status.is_ok() {
+        guard.0 = None;
+        let _ = tokio::time::timeout(std::time::Duration::from_millis(PTY_DRAIN_MS), reader_task).await;
+    }
+    // Close the chunk stream on every path (timeout included) so its forwarder ends
+    chunks.lock().unwrap().take();
+    drop(master);
+    let status = status?;
+    let stdout = String::from_utf8_lossy(&output.lock().unwrap()).to_string();
+
+    Ok(json!({
+        "exitCode": status.exit_code() as i32,
+        "stdout": stdout,
+        "stderr": "",
+        "interactive": true,
+    }))
+}
+
+/// Files larger than this are sent as `desktop.command.chunk` messages
+/// instead of inline in the result, to keep each WebSocket frame small.
+const READ_FILE_CHUNK_THRESHOLD: usize = 1024 * 1024;
+
+/// Upper bound for one chunk of a chunked `read_file`.
+const READ_FILE_CHUNK_BYTES: usize = 256 * 1024;
+
+/// Read a file's contents. Large files are streamed as ordered chunks when
+/// a chunk channel is available; the result then carries `chunked: true`
+/// and the chunk count instead of `content`, and the server reassembles.
+fn read_file(args: &Value, chunks: Option<OutputChunks>) -> Result<Value, SkillError> {
+    let path = &arg_path(args, "path")?;
 
     println!("[SkillExecutor] read_file: {}", path);
 
     let content = std::fs::read_to_string(path)
-        .map_err(|e| format!("Failed to read file: {}", e))?;
+        .map_err(|e| io_error("Failed to read file", e))?;
+
+    let Some(tx) = chunks.filter(|_| content.len() > READ_FILE_CHUNK_THRESHOLD) else {
+        return Ok(json!({
+            "path": path,
+            "content": content,
+            "size": content.len(),
+        }));
+    };
+
+    let mut count = 0;
+    let mut rest = content.as_str();
+    while !rest.is_empty() {
+        // Split on a char boundary so every chunk is valid UTF-8
+        let mut end = rest.len().min(READ_FILE_CHUNK_BYTES);
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (chunk, tail) = rest.split_at(end);
+        tx.send(chunk.to_string())
+            .map_err(|_| SkillError::Cancelled("Chunk stream closed".to_string()))?;
+        count += 1;
+        rest = tail;
+    }
+    println!("[SkillExecutor] read_file: sent {} bytes in {} chunks", content.len(), count);
 
     Ok(json!({
         "path": path,
-        "content": content,
         "size": content.len(),
+        "chunked": true,
+        "chunks": count,
+    }))
+}
+
+/// Maximum number of bytes returned by a single `read_file_range` call.
+const MAX_RANGE_BYTES: u64 = 1024 * 1024;
+
+/// Read a slice of a file: either `offset`/`length` bytes, or the last `tail_lines` lines.
+/// Seeks instead of loading the whole file, and reports the total size.
+fn read_file_range(args: &Value) -> Result<Value, SkillError> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let path = &arg_path(args, "path")?;
+
+    println!("[SkillExecutor] read_file_range: {}", path);
+
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| io_error("Failed to open file", e))?;
+    let total_size = file
+        .metadata()
+        .map_err(|e| io_error("Failed to stat file", e))?
+        .len();
+
+    if let Some(tail_lines) = args["tail_lines"].as_u64() {
+        // Walk backwards in chunks until enough newlines have been seen
+        const CHUNK: u64 = 8192;
+        let mut pos = total_size;
+        let mut buf: Vec<u8> = Vec::new();
+        let mut newlines = 0u64;
+        while pos > 0 && newlines <= tail_lines && (total_size - pos) < MAX_RANGE_BYTES {
+            let read_len = CHUNK.min(pos);
+            pos -= read_len;
+            file.seek(SeekFrom::Start(pos))
+                .map_err(|e| io_error("Failed to seek", e))?;
+            let mut chunk = vec![0u8; read_len as usize];
+            file.read_exact(&mut chunk)
+                .map_err(|e| io_error("Failed to read file", e))?;
+            newlines += chunk.iter().filter(|b| **b == b'\n').count() as u64;
+            chunk.extend_from_slice(&buf);
+            buf = chunk;
+        }
+
+        // Find the byte index where the last `tail_lines` lines begin
+        let body_end = if buf.last() == Some(&b'\n') { buf.len() - 1 } else { buf.len() };
+        let mut start = if tail_lines == 0 { buf.len() } else { 0 };
+        let mut seen = 0u64;
+        for (i, b) in buf[..body_end].iter().enumerate().rev() {
+            if tail_lines == 0 {
+                break;
+            }
+            if *b == b'\n' {
+                seen += 1;
+                if seen == tail_lines {
+                    start = i + 1;
+                    break;
+                }
+            }
+        }
+        let content = String::from_utf8_lossy(&buf[start..]).to_string();
+        let offset = total_size - (buf.len() - start) as u64;
+
+        return Ok(json!({
+            "path": path,
+            "content": content,
+            "offset": offset,
+            "length": buf.len() - start,
+            "totalSize": total_size,
+            "hasMore": offset > 0,
+        }));
+    }
+
+    let offset = args["offset"].as_u64().unwrap_or(0).min(total_size);
+    let length = args["length"]
+        .as_u64()
+        .unwrap_or(MAX_RANGE_BYTES)
+        .min(MAX_RANGE_BYTES)
+        .min(total_size - offset);
+
+    file.seek(SeekFrom::Start(offset))
+        .map_err(|e| io_error("Failed to seek", e))?;
+    let mut buf = Vec::with_capacity(length as usize);
+    std::io::BufReader::new(file)
+        .take(length)
+        .read_to_end(&mut buf)
+        .map_err(|e| io_error("Failed to read file", e))?;
+
+    Ok(json!({
+        "path": path,
+        "content": String::from_utf8_lossy(&buf),
+        "offset": offset,
+        "length": buf.len(),
+        "totalSize": total_size,
+        "hasMore": offset + (buf.len() as u64) < total_size,
     }))
 }
 
 /// Write content to a file.
-fn write_file(args: &Value) -> Result<Value, String> {
-    let path = args["path"]
-        .as_str()
-        .ok_or("Missing 'path' argument")?;
-    let content = args["content"]
-        .as_str()
-        .ok_or("Missing 'content' argument")?;
+fn write_file(args: &Value) -> Result<Value, SkillError> {
+    let path = &arg_path(args, "path")?;
+    let content = arg_str(args, "content")?;
 
     println!("[SkillExecutor] write_file: {}", path);
 
     std::fs::write(path, content)
-        .map_err(|e| format!("Failed to write file: {}", e))?;
+        .map_err(|e| io_error("Failed to write file", e))?;
 
     Ok(json!({
         "path": path,
@@ -113,21 +1336,129 @@ fn write_file(args: &Value) -> Result<Value, String> {
     }))
 }
 
+/// Apply a unified diff to a file. On conflict, nothing is written and the
+/// call fails with the conflicting hunks in the error.
+fn apply_patch(args: &Value) -> Result<Value, SkillError> {
+    let path = &arg_path(args, "path")?;
+    let diff = arg_str(args, "patch")?;
+
+    println!("[SkillExecutor] apply_patch: {}", path);
+
+    let original = std::fs::read_to_string(path)
+        .map_err(|e| io_error("Failed to read file", e))?;
+    let patch = diffy::Patch::from_str(diff)
+        .map_err(|e| SkillError::InvalidArgs(format!("Invalid patch: {}", e)))?;
+
+    match diffy::apply(&original, &patch) {
+        Ok(patched) => {
+            std::fs::write(path, &patched)
+                .map_err(|e| io_error("Failed to write file", e))?;
+            Ok(json!({
+                "path": path,
+                "applied": true,
+                "hunks": patch.hunks().len(),
+                "bytesWritten": patched.len(),
+            }))
+        }
+        Err(e) => {
+            // diffy reports "error applying hunk #N" (1-based)
+            let message = e.to_string();
+            let conflicting: Vec<String> = message
+                .rsplit('#')
+                .next()
+                .and_then(|n| n.trim().parse::<usize>().ok())
+                .and_then(|n| patch.hunks().get(n.saturating_sub(1)))
+                .map(|h| vec![hunk_text(h)])
+                .unwrap_or_else(|| patch.hunks().iter().map(hunk_text).collect());
+            Err(SkillError::FailedWith(
+                format!("Patch does not apply to {}: {}\n{}", path, message, conflicting.concat()),
+                json!({ "path": path, "conflictingHunks": conflicting }),
+            ))
+        }
+    }
+}
+
+/// Render a hunk in unified diff form (diffy only implements Display for whole patches).
+fn hunk_text(hunk: &diffy::Hunk<'_, str>) -> String {
+    let mut text = format!("@@ -{} +{} @@\n", hunk.old_range(), hunk.new_range());
+    for line in hunk.lines() {
+        let (prefix, content) = match line {
+            diffy::Line::Context(l) => (' ', l),
+            diffy::Line::Delete(l) => ('-', l),
+            diffy::Line::Insert(l) => ('+', l),
+        };
+        text.push(prefix);
+        text.push_str(content);
+        if !content.ends_with('\n') {
+            text.push('\n');
+        }
+    }
+    text
+}
+
+/// Replace a single exact occurrence of `old_str` with `new_str` in a file.
+fn edit_file(args: &Value) -> Result<Value, SkillError> {
+    let path = &arg_path(args, "path")?;
+    let old_str = arg_str(args, "old_str")?;
+    let new_str = arg_str(args, "new_str")?;
+
+    println!("[SkillExecutor] edit_file: {}", path);
+
+    if old_str.is_empty() {
+        return Err(SkillError::InvalidArgs("'old_str' must not be empty".to_string()));
+    }
+
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| io_error("Failed to read file", e))?;
+    match content.matches(old_str).count() {
+        0 => return Err(SkillError::InvalidArgs("'old_str' not found in file".to_string())),
+        1 => {}
+        n => {
+            return Err(SkillError::InvalidArgs(format!(
+                "'old_str' matches {} times; include more context to make it unique",
+                n
+            )))
+        }
+    }
+
+    let updated = content.replacen(old_str, new_str, 1);
+    std::fs::write(path, &updated)
+        .map_err(|e| io_error("Failed to write file", e))?;
+
+    Ok(json!({
+        "path": path,
+        "bytesWritten": updated.len(),
+    }))
+}
+
 /// List directory contents.
-fn list_directory(args: &Value) -> Result<Value, String> {
-    let path = args["path"]
-        .as_str()
-        .ok_or("Missing 'path' argument")?;
+fn list_directory(args: &Value) -> Result<Value, SkillError> {
+    let path = &arg_path(args, "path")?;
 
     println!("[SkillExecutor] list_directory: {}", path);
 
     let dir = Path::new(path);
     if !dir.is_dir() {
-        return Err(format!("Not a directory: {}", path));
+        return Err(SkillError::NotFound(format!("Not a directory: {}", path)));
+    }
+
+    if args["recursive"].as_bool().unwrap_or(false) {
+        let max_depth = args["max_depth"].as_u64().unwrap_or(3).clamp(1, MAX_TREE_DEPTH) as usize;
+        let include_hidden = args["include_hidden"].as_bool().unwrap_or(false);
+        let mut entries = Vec::new();
+        let mut truncated = false;
+        walk_tree(dir, dir, 1, max_depth, include_hidden, &mut entries, &mut truncated);
+        return Ok(json!({
+            "path": path,
+            "entries": entries,
+            "count": entries.len(),
+            "maxDepth": max_depth,
+            "truncated": truncated,
+        }));
     }
 
     let entries: Vec<Value> = std::fs::read_dir(dir)
-        .map_err(|e| format!("Failed to read directory: {}", e))?
+        .map_err(|e| io_error("Failed to read directory", e))?
         .filter_map(|entry| {
             let entry = entry.ok()?;
             let metadata = entry.metadata().ok()?;
@@ -146,9 +1477,186 @@ fn list_directory(args: &Value) -> Result<Value, String> {
     }))
 }
 
-/// Run Claude Code (`claude -p`) on the desktop.
-async fn run_claude_code(args: &Value) -> Result<Value, String> {
-    let prompt = args["prompt"].as_str().ok_or("Missing 'prompt'")?;
+/// Add execute permission wherever read permission is set (u/g/o).
+/// Returns true if the mode changed. Always false on Windows.
+pub fn make_executable(path: &Path) -> std::io::Result<bool> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = std::fs::metadata(path)?.permissions();
+        let mode = perms.mode();
+        let new_mode = mode | ((mode & 0o444) >> 2);
+        if new_mode == mode {
+            return Ok(false);
+        }
+        perms.set_mode(new_mode);
+        std::fs::set_permissions(path, perms)?;
+        Ok(true)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        Ok(false)
+    }
+}
+
+/// Set the executable bit on a file, or on all regular files under a directory.
+fn set_executable(args: &Value) -> Result<Value, SkillError> {
+    let path = &arg_path(args, "path")?;
+    let recursive = args["recursive"].as_bool().unwrap_or(false);
+
+    println!("[SkillExecutor] set_executable: {} (recursive={})", path, recursive);
+
+    let target = Path::new(path);
+    let mut files = Vec::new();
+    if target.is_dir() {
+        if !recursive {
+            return Err(SkillError::InvalidArgs(format!("{} is a directory; pass recursive=true", path)));
+        }
+        let mut stack = vec![target.to_path_buf()];
+        while let Some(dir) = stack.pop() {
+            for entry in std::fs::read_dir(&dir).map_err(|e| io_error("Failed to read directory", e))?.flatten() {
+                let Ok(file_type) = entry.file_type() else { continue };
+                if file_type.is_dir() {
+                    stack.push(entry.path());
+                } else if file_type.is_file() {
+                    files.push(entry.path());
+                }
+            }
+        }
+    } else if target.is_file() {
+        files.push(target.to_path_buf());
+    } else {
+        return Err(SkillError::NotFound(format!("Path not found: {}", path)));
+    }
+
+    let mut changed = 0;
+    for file in &files {
+        if make_executable(file).map_err(|e| io_error(&format!("Failed to chmod {}", file.display()), e))? {
+            changed += 1;
+        }
+    }
+
+    Ok(json!({
+        "path": path,
+        "files": files.len(),
+        "changed": changed,
+        "supported": cfg!(unix),
+    }))
+}
+
+/// Depth and size limits for recursive `list_directory`.
+const MAX_TREE_DEPTH: u64 = 10;
+const MAX_TREE_ENTRIES: usize = 2000;
+
+/// Directories skipped by recursive listing unless hidden entries are requested.
+const TREE_SKIP_DIRS: &[&str] = &[".git", "node_modules"];
+
+/// Depth-first walk collecting `{ path, isDir, size, depth }` with paths relative
+/// to `root`. Symlinks are listed but never followed, so the walk stays inside the tree.
+fn walk_tree(
+    root: &Path,
+    dir: &Path,
+    depth: usize,
+    max_depth: usize,
+    include_hidden: bool,
+    entries: &mut Vec<Value>,
+    truncated: &mut bool,
+) {
+    let mut children: Vec<_> = match std::fs::read_dir(dir) {
+        Ok(rd) => rd.filter_map(|e| e.ok()).collect(),
+        Err(_) => return,
+    };
+    children.sort_by_key(|e| e.file_name());
+
+    for entry in children {
+        if entries.len() >= MAX_TREE_ENTRIES {
+            *truncated = true;
+            return;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !include_hidden && (name.starts_with('.') || TREE_SKIP_DIRS.contains(&name.as_str())) {
+            continue;
+        }
+        let entry_path = entry.path();
+        let Ok(metadata) = std::fs::symlink_metadata(&entry_path) else { continue };
+        let is_dir = metadata.is_dir();
+        let rel = entry_path.strip_prefix(root).unwrap_or(&entry_path).to_string_lossy().replace('\\', "/");
+        entries.push(json!({
+            "path": rel,
+            "isDir": is_dir,
+            "isSymlink": metadata.file_type().is_symlink(),
+            "size": metadata.len(),
+            "depth": depth,
+        }));
+        if is_dir && depth < max_depth {
+            walk_tree(root, &entry_path, depth + 1, max_depth, include_hidden, entries, truncated);
+        }
+    }
+}
+
+/// Longest text or tool input preview carried by a Claude Code progress chunk.
+const CLAUDE_PROGRESS_PREVIEW_CHARS: usize = 500;
+
+fn preview(text: &str) -> String {
+    if text.chars().count() > CLAUDE_PROGRESS_PREVIEW_CHARS {
+        let cut: String = text.chars().take(CLAUDE_PROGRESS_PREVIEW_CHARS).collect();
+        format!("{}…", cut)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Turn one line of `claude --output-format stream-json` into progress
+/// events for the UI: `init`, `text`, `tool_use`, `tool_result` and `result`,
+/// each tagged with `kind`. Unknown event types yield nothing.
+fn claude_stream_progress(event: &Value) -> Vec<Value> {
+    let content = || event["message"]["content"].as_array().cloned().unwrap_or_default();
+    match event["type"].as_str().unwrap_or("") {
+        "system" if event["subtype"] == "init" => vec![json!({
+            "kind": "init",
+            "model": event["model"],
+            "sessionId": event["session_id"],
+        })],
+        "assistant" => content()
+            .iter()
+            .filter_map(|item| match item["type"].as_str()? {
+                "text" => Some(json!({ "kind": "text", "text": preview(item["text"].as_str()?) })),
+                "tool_use" => Some(json!({
+                    "kind": "tool_use",
+                    "tool": item["name"],
+                    "input": preview(&item["input"].to_string()),
+                })),
+                _ => None,
+            })
+            .collect(),
+        "user" => content()
+            .iter()
+            .filter(|item| item["type"] == "tool_result")
+            .map(|item| json!({
+                "kind": "tool_result",
+                "isError": item["is_error"].as_bool().unwrap_or(false),
+            }))
+            .collect(),
+        "result" => vec![json!({
+            "kind": "result",
+            "isError": event["is_error"].as_bool().unwrap_or(false),
+            "turns": event["num_turns"],
+            "durationMs": event["duration_ms"],
+        })],
+        _ => vec![],
+    }
+}
+
+/// Run Claude Code (`claude -p`) on the desktop. Output is requested as
+/// stream-json and each turn is sent to `chunks` as a JSON progress event
+/// (see `claude_stream_progress`) while it runs; lines that aren't JSON
+/// (older CLIs) are forwarded as plain text. The result carries the final
+/// summary and exit status.
+async fn run_claude_code(args: &Value, chunks: Option<OutputChunks>) -> Result<Value, SkillError> {
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt};
+
+    let prompt = arg_str(args, "prompt")?;
     let project_path = args["project_path"].as_str().unwrap_or("~");
     let max_turns = args["max_turns"].as_u64().unwrap_or(25);
     let timeout_secs = args["timeout"].as_u64().unwrap_or(300); // 5 minutes
@@ -157,55 +1665,106 @@ async fn run_claude_code(args: &Value) -> Result<Value, String> {
 
     // Use env var for prompt to avoid shell escaping issues; shell expands ~
     let cmd = format!(
-        "cd {} && claude -p \"$CLAUDE_PROMPT\" --output-format text --max-turns {}",
+        "cd {} && claude -p \"$CLAUDE_PROMPT\" --output-format stream-json --verbose --max-turns {}",
         project_path, max_turns
     );
 
-    let child = tokio::process::Command::new("sh")
+    let mut child = tokio::process::Command::new("sh")
         .arg("-l") // login shell to ensure PATH includes claude
         .arg("-c")
         .arg(&cmd)
         .env("CLAUDE_PROMPT", prompt)
         .stdout(std::process::Stdio::piped())
         .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true) // killed if the command is cancelled
         .spawn()
-        .map_err(|e| format!("Failed to spawn claude: {}", e))?;
+        .map_err(|e| io_error("Failed to spawn claude", e))?;
 
-    let result = tokio::time::timeout(
-        std::time::Duration::from_secs(timeout_secs),
-        child.wait_with_output(),
-    )
-    .await
-    .map_err(|_| format!("Claude Code timed out after {}s", timeout_secs))?
-    .map_err(|e| format!("Claude Code failed: {}", e))?;
+    let stdout = child.stdout.take().ok_or_else(|| SkillError::Failed("claude stdout not captured".to_string()))?;
+    let mut stderr_pipe = child.stderr.take();
+    let stderr_task = tokio::spawn(async move {
+        let mut stderr = String::new();
+        if let Some(ref mut pipe) = stderr_pipe {
+            let _ = pipe.read_to_string(&mut stderr).await;
+        }
+        stderr
+    });
+
+    let send = |data: String| {
+        if let Some(ref tx) = chunks {
+            let _ = tx.send(data);
+        }
+    };
+    let run = async {
+        let mut lines = tokio::io::BufReader::new(stdout).lines();
+        let mut text = String::new();
+        let mut result: Option<Value> = None;
+        while let Some(line) = lines.next_line().await? {
+            match serde_json::from_str::<Value>(&line) {
+                Ok(event) if event.is_object() => {
+                    for progress in claude_stream_progress(&event) {
+                        send(progress.to_string());
+                    }
+                    if event["type"] == "result" {
+                        result = Some(event);
+                    }
+                }
+                _ => {
+                    text.push_str(&line);
+                    text.push('\n');
+                    send(format!("{}\n", line));
+                }
+            }
+        }
+        let status = child.wait().await?;
+        Ok::<_, std::io::Error>((status, text, result))
+    };
 
-    let output = String::from_utf8_lossy(&result.stdout).to_string();
+    let (status, text, result) = tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), run)
+        .await
+        .map_err(|_| SkillError::Timeout(format!("Claude Code timed out after {}s", timeout_secs)))?
+        .map_err(|e| io_error("Claude Code failed", e))?;
+    let stderr = stderr_task.await.unwrap_or_default();
+
+    let output = result
+        .as_ref()
+        .and_then(|r| r["result"].as_str().map(String::from))
+        .unwrap_or(text);
     let truncated = if output.len() > 8000 {
-        format!("{}...\n\n[Output truncated, total {} chars]", &output[..8000], output.len())
+        let mut end = 8000;
+        while !output.is_char_boundary(end) {
+            end -= 1;
+        }
+        format!("{}...\n\n[Output truncated, total {} chars]", &output[..end], output.len())
     } else {
         output
     };
 
-    Ok(json!({
-        "exitCode": result.status.code().unwrap_or(-1),
+    let mut response = json!({
+        "exitCode": status.code().unwrap_or(-1),
         "output": truncated,
-        "stderr": String::from_utf8_lossy(&result.stderr).to_string(),
-    }))
+        "stderr": stderr,
+    });
+    if let Some(r) = result {
+        response["isError"] = json!(r["is_error"].as_bool().unwrap_or(false));
+        response["turns"] = r["num_turns"].clone();
+        response["durationMs"] = r["duration_ms"].clone();
+        response["costUsd"] = r["total_cost_usd"].clone();
+    }
+    Ok(response)
 }
 
 /// Route a tool call to the local MCP bridge HTTP server.
-async fn call_mcp_tool(args: &Value) -> Result<Value, String> {
+async fn call_mcp_tool(args: &Value) -> Result<Value, SkillError> {
     let port = get_mcp_bridge_port();
     if port == 0 {
-        return Err("MCP bridge is not running".to_string());
+        return Err(SkillError::Unavailable("MCP bridge is not running".to_string()));
     }
+    let token = get_mcp_bridge_token()
+        .ok_or_else(|| SkillError::Unavailable("MCP bridge auth token is not set".to_string()))?;
 
-    let server = args["server"]
-        .as_str()
-        .ok_or("Missing 'server' argument")?;
-    let tool = args["tool"]
-        .as_str()
-        .ok_or("Missing 'tool' argument")?;
+    let server = arg_str(args, "server")?;
+    let tool = arg_str(args, "tool")?;
     let arguments = &args["arguments"];
 
     println!("[SkillExecutor] call_mcp_tool: {}/{}", server, tool);
@@ -217,17 +1776,95 @@ async fn call_mcp_tool(args: &Value) -> Result<Value, String> {
         "arguments": arguments,
     });
 
-    let client = reqwest::Client::new();
+    let client = crate::http::client();
     let resp = client
         .post(&url)
+        .bearer_auth(&token)
         .json(&body)
         .timeout(std::time::Duration::from_secs(30))
         .send()
         .await
-        .map_err(|e| format!("MCP bridge request failed: {}", e))?;
+        .map_err(|e| http_error("MCP bridge request failed", e))?;
 
-    let text = resp.text().await.map_err(|e| format!("Failed to read MCP response: {}", e))?;
+    let text = resp.text().await.map_err(|e| http_error("Failed to read MCP response", e))?;
     let parsed: Value = serde_json::from_str(&text).unwrap_or(json!({"result": text}));
 
     Ok(parsed)
 }
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn expand(raw: &str, base: Option<&str>) -> Result<String, SkillError> {
+        expand_path_in(raw, Path::new("/home/user"), base.map(Path::new))
+    }
+
+    #[test]
+    fn expands_tilde() {
+        assert_eq!(expand("~", None).unwrap(), "/home/user");
+        assert_eq!(expand("~/notes.txt", None).unwrap(), "/home/user/notes.txt");
+        // Only a leading `~` is the home directory
+        assert_eq!(expand("a/~/b", None).unwrap(), "/home/user/a/~/b");
+    }
+
+    #[test]
+    fn expands_braced_variables() {
+        std::env::set_var("AGENTOS_TEST_EXPAND_DIR", "/data/projects");
+        assert_eq!(expand("${AGENTOS_TEST_EXPAND_DIR}/x", None).unwrap(), "/data/projects/x");
+        assert_eq!(expand("/srv/${AGENTOS_TEST_EXPAND_DIR}", None).unwrap(), "/srv/data/projects");
+        assert!(matches!(expand("${AGENTOS_TEST_EXPAND_DIR", None), Err(SkillError::InvalidArgs(_))));
+    }
+
+    #[test]
+    fn expands_a_leading_variable() {
+        std::env::set_var("AGENTOS_TEST_EXPAND_LEAD", "/opt/tools");
+        assert_eq!(expand("$AGENTOS_TEST_EXPAND_LEAD/bin", None).unwrap(), "/opt/tools/bin");
+        assert_eq!(expand("$AGENTOS_TEST_EXPAND_LEAD", None).unwrap(), "/opt/tools");
+        // Not a whole leading component, so left alone
+        assert_eq!(expand("/x/$AGENTOS_TEST_EXPAND_LEAD", None).unwrap(), "/x/$AGENTOS_TEST_EXPAND_LEAD");
+    }
+
+    #[test]
+    fn unset_variables() {
+        std::env::remove_var("AGENTOS_TEST_EXPAND_UNSET");
+        assert!(matches!(expand("${AGENTOS_TEST_EXPAND_UNSET}/x", None), Err(SkillError::InvalidArgs(_))));
+        assert_eq!(
+            expand("$AGENTOS_TEST_EXPAND_UNSET/x", None).unwrap(),
+            "/home/user/$AGENTOS_TEST_EXPAND_UNSET/x"
+        );
+        assert_eq!(expand("/tmp/a$b.txt", None).unwrap(), "/tmp/a$b.txt");
+    }
+
+    #[test]
+    fn keeps_a_lone_dollar() {
+        assert_eq!(expand("/tmp/$", None).unwrap(), "/tmp/$");
+        assert_eq!(expand("/tmp/$/x", None).unwrap(), "/tmp/$/x");
+        assert_eq!(expand("$", None).unwrap(), "/home/user/$");
+    }
+
+    #[test]
+    fn resolves_relative_paths() {
+        assert_eq!(expand("docs/a.md", None).unwrap(), "/home/user/docs/a.md");
+        assert_eq!(expand("./docs/../a.md", None).unwrap(), "/home/user/a.md");
+        assert_eq!(expand("docs/a.md", Some("/work")).unwrap(), "/work/docs/a.md");
+    }
+
+    #[test]
+    fn refuses_paths_outside_the_base_dir() {
+        assert!(matches!(expand("../../etc/x", Some("/work/project")), Err(SkillError::PermissionDenied(_))));
+        assert!(matches!(expand("/etc/passwd", Some("/work")), Err(SkillError::PermissionDenied(_))));
+        assert!(matches!(expand("/work-other/x", Some("/work")), Err(SkillError::PermissionDenied(_))));
+        assert!(matches!(expand("~/x", Some("/work")), Err(SkillError::PermissionDenied(_))));
+        assert_eq!(expand("/work/sub/../x", Some("/work")).unwrap(), "/work/x");
+        // Without a configured base directory nothing is restricted
+        assert_eq!(expand("../../etc/x", None).unwrap(), "/etc/x");
+    }
+}
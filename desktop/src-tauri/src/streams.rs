@@ -0,0 +1,87 @@
+//! Registry of long-lived channels pushing to the frontend (chat events,
+//! combined logs, process startup logs, MCP bridge progress), so a
+//! subscription the UI forgot to close can be found and ended.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use tokio::sync::Notify;
+
+#[derive(Clone, Serialize)]
+pub struct StreamInfo {
+    pub id: String,
+    pub kind: String,
+    /// Milliseconds since the Unix epoch.
+    pub started_at: u64,
+}
+
+/// Close request shared between the registry and the stream's task.
+#[derive(Default)]
+struct CloseSignal {
+    notify: Notify,
+    closed: AtomicBool,
+}
+
+struct Entry {
+    info: StreamInfo,
+    close: Arc<CloseSignal>,
+}
+
+static STREAMS: StdMutex<Vec<Entry>> = StdMutex::new(Vec::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Held by the task feeding a stream; dropping it unregisters the stream.
+pub struct StreamGuard {
+    id: String,
+    close: Arc<CloseSignal>,
+}
+
+impl StreamGuard {
+    /// Resolves once `close` was called for this stream.
+    pub async fn closed(&self) {
+        self.close.notify.notified().await;
+    }
+
+    /// Whether `close` was called, for tasks that poll instead of waiting.
+    pub fn is_closed(&self) -> bool {
+        self.close.closed.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for StreamGuard {
+    fn drop(&mut self) {
+        STREAMS.lock().unwrap().retain(|e| e.info.id != self.id);
+    }
+}
+
+pub fn register(kind: &str) -> StreamGuard {
+    let id = format!("{}-{}", kind, NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    let close = Arc::new(CloseSignal::default());
+    let started_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    STREAMS.lock().unwrap().push(Entry {
+        info: StreamInfo { id: id.clone(), kind: kind.to_string(), started_at },
+        close: close.clone(),
+    });
+    StreamGuard { id, close }
+}
+
+/// Active streams, oldest first.
+pub fn list() -> Vec<StreamInfo> {
+    STREAMS.lock().unwrap().iter().map(|e| e.info.clone()).collect()
+}
+
+/// Ask a stream's task to stop. Returns false if no such stream is active.
+pub fn close(id: &str) -> bool {
+    match STREAMS.lock().unwrap().iter().find(|e| e.info.id == id) {
+        Some(entry) => {
+            entry.close.closed.store(true, Ordering::SeqCst);
+            // notify_one keeps a permit if the task is not waiting right now
+            entry.close.notify.notify_one();
+            true
+        }
+        None => false,
+    }
+}
@@ -1,12 +1,20 @@
 use futures_util::{SinkExt, StreamExt};
 use serde_json::{json, Value};
-use std::sync::Arc;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
 use tauri::ipc::Channel;
 use tokio::sync::{Mutex, oneshot};
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio_tungstenite::{
+    connect_async_with_config,
+    tungstenite::{protocol::WebSocketConfig, Message},
+};
 
 use crate::ChatMessage;
+use crate::events::{ClientEvent, RawEvent, UnknownMessage};
+use crate::notifications;
 use crate::skill_executor;
+use crate::streams;
 
 type WsSink = futures_util::stream::SplitSink<
     tokio_tungstenite::WebSocketStream<
@@ -15,33 +23,125 @@ type WsSink = futures_util::stream::SplitSink<
     Message,
 >;
 
+/// Version of the desktop <-> server message protocol spoken by this client.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Desktop client version, reported to the server in the connect handshake.
+const CLIENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// In-flight `desktop.command` tasks keyed by commandId.
+type RunningCommands = Arc<StdMutex<HashMap<String, tokio::task::AbortHandle>>>;
+
+/// Outstanding `ping` messages keyed by message id, resolved by the matching `pong`.
+type PendingPings = Arc<StdMutex<HashMap<String, oneshot::Sender<()>>>>;
+
+/// Waiters for a `skill.config.response`, keyed by skill name.
+type PendingConfigs = Arc<StdMutex<HashMap<String, Vec<oneshot::Sender<Value>>>>>;
+
+/// Conversations with a `chat.send` awaiting `chat.done`, with the send time.
+type StreamingChats = Arc<StdMutex<HashMap<String, std::time::Instant>>>;
+
+/// Repeated `chat.stop` calls within this window are coalesced into one.
+const STOP_DEBOUNCE_MS: u64 = 500;
+
+/// A generation with no `chat.done` after this long no longer blocks new sends.
+const STREAMING_STALE_SECS: u64 = 600;
+
+/// Bound on unmatched `skill.start` events remembered for timing.
+const MAX_OPEN_SKILL_STARTS: usize = 256;
+
+/// Number of recent round-trip samples kept for the rolling average.
+const LATENCY_SAMPLES: usize = 10;
+
+/// Default upper bound for a single WebSocket message (16 MiB). Larger text
+/// frames are logged and skipped without being parsed.
+pub const DEFAULT_MAX_MESSAGE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Hard transport limit: frames beyond this make tungstenite drop the
+/// connection. Kept above the parse limit so oversized messages are skipped
+/// rather than disconnecting.
+const TRANSPORT_MAX_MESSAGE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Timeout for the WebSocket connect and for the CONNECT handshake when the
+/// caller does not pass one.
+pub const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 15;
+
 /// Result of the initial connection handshake
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct ConnectResult {
     pub session_id: String,
     pub device_id: String,
     pub skills: Vec<String>,
+    /// Protocol version reported by the server (None for servers that predate versioning).
+    pub server_protocol_version: Option<u32>,
+    /// Set when the server speaks a different protocol version than this client.
+    pub version_warning: Option<String>,
+    /// Index of the candidate that connected, when connecting with fallbacks.
+    pub candidate_index: Option<usize>,
 }
 
 pub struct WsClient {
     sink: Option<Arc<Mutex<WsSink>>>,
-    connected: bool,
+    /// Shared with the read loop task, which clears it when the loop ends.
+    connected: Arc<AtomicBool>,
     session_id: Option<String>,
     read_handle: Option<tokio::task::JoinHandle<()>>,
+    max_message_bytes: usize,
+    forward_raw_events: Arc<AtomicBool>,
+    forward_unknown: Arc<AtomicBool>,
+    app_handle: Option<tauri::AppHandle>,
+    running_commands: RunningCommands,
+    pending_pings: PendingPings,
+    pending_configs: PendingConfigs,
+    latency_samples: VecDeque<u64>,
+    streaming: StreamingChats,
+    last_stop: StdMutex<Option<std::time::Instant>>,
 }
 
 impl WsClient {
     pub fn new() -> Self {
         Self {
             sink: None,
-            connected: false,
+            connected: Arc::new(AtomicBool::new(false)),
             session_id: None,
             read_handle: None,
+            max_message_bytes: DEFAULT_MAX_MESSAGE_BYTES,
+            forward_raw_events: Arc::new(AtomicBool::new(false)),
+            forward_unknown: Arc::new(AtomicBool::new(true)),
+            app_handle: None,
+            running_commands: Arc::new(StdMutex::new(HashMap::new())),
+            pending_pings: Arc::new(StdMutex::new(HashMap::new())),
+            pending_configs: Arc::new(StdMutex::new(HashMap::new())),
+            latency_samples: VecDeque::new(),
+            streaming: Arc::new(StdMutex::new(HashMap::new())),
+            last_stop: StdMutex::new(None),
         }
     }
 
+    /// Attach the app handle used for OS notifications on unexpected disconnects.
+    pub fn set_app_handle(&mut self, app_handle: tauri::AppHandle) {
+        self.app_handle = Some(app_handle);
+    }
+
+    /// Forward server messages with unhandled types to the channel as `raw` events.
+    pub fn set_forward_raw_events(&self, enabled: bool) {
+        self.forward_raw_events.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Forward server messages with unrecognized types to the channel as
+    /// `unknown` events (on by default). Independent of `raw` forwarding.
+    pub fn set_forward_unknown_messages(&self, enabled: bool) {
+        self.forward_unknown.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Set the largest text message that is parsed (bigger ones are skipped);
+    /// takes effect on the next connect.
+    pub fn set_max_message_size(&mut self, bytes: usize) {
+        self.max_message_bytes = bytes;
+    }
+
     pub fn is_connected(&self) -> bool {
-        self.connected
+        self.connected.load(Ordering::SeqCst)
     }
 
     pub async fn connect(
@@ -56,12 +156,23 @@ impl WsClient {
         agent_url: Option<String>,
         agent_token: Option<String>,
         agent_protocol: Option<String>,
-        channel: Channel<Value>,
+        connect_timeout_secs: Option<u64>,
+        channel: Channel<ClientEvent>,
     ) -> Result<ConnectResult, Box<dyn std::error::Error + Send + Sync>> {
         self.disconnect().await;
+        let timeout_secs = connect_timeout_secs.unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS);
+        let timeout = std::time::Duration::from_secs(timeout_secs);
 
         println!("[WsClient] Connecting to: {}", url);
-        let (ws_stream, _) = connect_async(url).await?;
+        let transport_max = self.max_message_bytes.max(TRANSPORT_MAX_MESSAGE_BYTES);
+        let ws_config = WebSocketConfig {
+            max_message_size: Some(transport_max),
+            max_frame_size: Some(transport_max),
+            ..Default::default()
+        };
+        let (ws_stream, _) = tokio::time::timeout(timeout, connect_async_with_config(url, Some(ws_config), false))
+            .await
+            .map_err(|_| format!("Connection timeout: could not reach {} within {} seconds", url, timeout_secs))??;
         println!("[WsClient] WebSocket TCP connected");
         let (write, read) = ws_stream.split();
 
@@ -76,6 +187,8 @@ impl WsClient {
             "payload": {
                 "mode": mode,
                 "deviceId": device_id,
+                "clientVersion": CLIENT_VERSION,
+                "protocolVersion": PROTOCOL_VERSION,
                 "authToken": auth_token,
                 "apiKey": api_key,
                 "model": model,
@@ -94,8 +207,28 @@ impl WsClient {
         // Spawn read loop — uses IPC Channel instead of Tauri events
         let sink_clone = sink.clone();
         let tx_clone = tx.clone();
+        let max_message_bytes = self.max_message_bytes;
+        let forward_raw = self.forward_raw_events.clone();
+        let forward_unknown = self.forward_unknown.clone();
+        let app_handle = self.app_handle.clone();
+        let running = self.running_commands.clone();
+        let pings = self.pending_pings.clone();
+        let configs = self.pending_configs.clone();
+        let streaming = self.streaming.clone();
+        let connected = self.connected.clone();
+        let sink_for_close = sink.clone();
         let handle = tokio::spawn(async move {
-            Self::read_loop(read, channel, sink_clone, tx_clone).await;
+            let reason = Self::read_loop(read, channel, sink_clone, tx_clone, max_message_bytes, forward_raw, forward_unknown, running, pings, configs, streaming).await;
+            connected.store(false, Ordering::SeqCst);
+            if reason == "stream_closed" {
+                // Closed on request (close_stream), so hang up rather than
+                // leave the server sending commands nobody reads
+                let _ = sink_for_close.lock().await.close().await;
+                return;
+            }
+            if let Some(app) = app_handle {
+                notifications::notify(&app, "AgentOS disconnected", &format!("Connection to server lost ({})", reason));
+            }
         });
         self.read_handle = Some(handle);
 
@@ -106,11 +239,8 @@ impl WsClient {
             println!("[WsClient] CONNECT message sent (mode: {})", mode);
         }
 
-        // Wait for server response (15s timeout)
-        let result = tokio::time::timeout(
-            std::time::Duration::from_secs(15),
-            rx,
-        ).await;
+        // Wait for server response
+        let result = tokio::time::timeout(timeout, rx).await;
 
         match result {
             Ok(Ok(Ok(payload))) => {
@@ -120,7 +250,35 @@ impl WsClient {
                     .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
                     .unwrap_or_default();
 
-                self.connected = true;
+                // Refuse to continue if the server requires a newer client
+                if let Some(min_version) = payload["minClientVersion"].as_str() {
+                    if compare_versions(CLIENT_VERSION, min_version) == std::cmp::Ordering::Less {
+                        println!("[WsClient] Client {} is older than server minimum {}", CLIENT_VERSION, min_version);
+                        self.disconnect().await;
+                        return Err(format!(
+                            "Incompatible client version: server requires >= {}, this client is {}. Please update AgentOS Desktop.",
+                            min_version, CLIENT_VERSION
+                        ).into());
+                    }
+                }
+
+                let server_protocol_version = payload["protocolVersion"].as_u64().map(|v| v as u32);
+                let version_warning = match server_protocol_version {
+                    Some(v) if v < PROTOCOL_VERSION => Some(format!(
+                        "Server protocol v{} is older than client protocol v{}; some features may not work. Please upgrade the server.",
+                        v, PROTOCOL_VERSION
+                    )),
+                    Some(v) if v > PROTOCOL_VERSION => Some(format!(
+                        "Server protocol v{} is newer than client protocol v{}; please update AgentOS Desktop.",
+                        v, PROTOCOL_VERSION
+                    )),
+                    _ => None,
+                };
+                if let Some(ref warning) = version_warning {
+                    println!("[WsClient] Version warning: {}", warning);
+                }
+
+                self.connected.store(true, Ordering::SeqCst);
                 self.session_id = Some(session_id.clone());
                 println!("[WsClient] Connected! sessionId={}, skills={:?}", session_id, skills);
 
@@ -130,7 +288,14 @@ impl WsClient {
                     println!("[WsClient] Failed to send desktop.register: {}", e);
                 }
 
-                Ok(ConnectResult { session_id, device_id, skills })
+                Ok(ConnectResult {
+                    session_id,
+                    device_id,
+                    skills,
+                    server_protocol_version,
+                    version_warning,
+                    candidate_index: None,
+                })
             }
             Ok(Ok(Err(err_msg))) => {
                 println!("[WsClient] Server rejected: {}", err_msg);
@@ -143,9 +308,9 @@ impl WsClient {
                 Err("Connection failed: server closed connection".into())
             }
             Err(_) => {
-                println!("[WsClient] Connection timeout (15s)");
+                println!("[WsClient] Connection timeout ({}s)", timeout_secs);
                 self.disconnect().await;
-                Err("Connection timeout: server did not respond within 15 seconds".into())
+                Err(format!("Connection timeout: server did not respond within {} seconds", timeout_secs).into())
             }
         }
     }
@@ -156,13 +321,40 @@ impl WsClient {
                 tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
             >,
         >,
-        channel: Channel<Value>,
+        channel: Channel<ClientEvent>,
         sink: Arc<Mutex<WsSink>>,
         connect_tx: Arc<Mutex<Option<oneshot::Sender<Result<Value, String>>>>>,
-    ) {
+        max_message_bytes: usize,
+        forward_raw: Arc<AtomicBool>,
+        forward_unknown: Arc<AtomicBool>,
+        running: RunningCommands,
+        pings: PendingPings,
+        configs: PendingConfigs,
+        streaming: StreamingChats,
+    ) -> String {
         println!("[WsClient] Read loop started");
-        while let Some(msg) = read.next().await {
+        // Start times of skills awaiting their result, by correlation key
+        let mut skill_starts: HashMap<String, std::time::Instant> = HashMap::new();
+        let stream = streams::register("chat_events");
+        loop {
+            let msg = tokio::select! {
+                msg = read.next() => match msg {
+                    Some(msg) => msg,
+                    None => break,
+                },
+                _ = stream.closed() => {
+                    println!("[WsClient] Event stream closed");
+                    let _ = channel.send(ClientEvent::disconnected("stream_closed"));
+                    return "stream_closed".to_string();
+                }
+            };
             match msg {
+                Ok(Message::Text(text)) if text.len() > max_message_bytes => {
+                    println!("[WsClient] Skipping oversized text frame ({} bytes > {})", text.len(), max_message_bytes);
+                }
+                Ok(Message::Binary(data)) => {
+                    println!("[WsClient] Ignoring binary frame ({} bytes)", data.len());
+                }
                 Ok(Message::Text(text)) => {
                     if let Ok(parsed) = serde_json::from_str::<Value>(&text) {
                         let msg_type = parsed["type"].as_str().unwrap_or("");
@@ -182,75 +374,127 @@ impl WsClient {
                                 let mut guard = connect_tx.lock().await;
                                 if let Some(tx) = guard.take() {
                                     let _ = tx.send(Err(err.to_string()));
-                                } else {
-                                    let _ = channel.send(json!({"type": "error", "payload": payload}));
+                                } else if let Some(event) = ClientEvent::from_server(msg_type, payload) {
+                                    if let Some(conversation_id) = payload["conversationId"].as_str() {
+                                        streaming.lock().unwrap().remove(conversation_id);
+                                    }
+                                    let _ = channel.send(event);
                                 }
                             }
-                            "chat.chunk" => {
-                                let _ = channel.send(json!({"type": "chat.chunk", "payload": &parsed["payload"]}));
-                            }
-                            "chat.done" => {
-                                let _ = channel.send(json!({"type": "chat.done", "payload": &parsed["payload"]}));
-                            }
-                            "skill.start" => {
-                                let _ = channel.send(json!({"type": "skill.start", "payload": &parsed["payload"]}));
-                            }
-                            "skill.result" => {
-                                let _ = channel.send(json!({"type": "skill.result", "payload": &parsed["payload"]}));
-                            }
-                            "push.message" => {
-                                let _ = channel.send(json!({"type": "push.message", "payload": &parsed["payload"]}));
-                            }
-                            "skill.list.response" => {
-                                let _ = channel.send(json!({"type": "skill.list.response", "payload": &parsed["payload"]}));
+                            "skill.config.response" => {
+                                let payload = &parsed["payload"];
+                                let skill_name = payload["skillName"].as_str().unwrap_or("");
+                                let waiters = configs.lock().unwrap().remove(skill_name).unwrap_or_default();
+                                for tx in waiters {
+                                    let _ = tx.send(payload.clone());
+                                }
+                                if let Some(event) = ClientEvent::from_server(msg_type, payload) {
+                                    let _ = channel.send(event);
+                                }
                             }
-                            "skill.library.response" => {
-                                let _ = channel.send(json!({"type": "skill.library.response", "payload": &parsed["payload"]}));
+                            "skill.start" | "skill.result" => {
+                                let mut event = ClientEvent::from_server(msg_type, &parsed["payload"]);
+                                match event {
+                                    Some(ClientEvent::SkillStart(ref start)) => {
+                                        if skill_starts.len() >= MAX_OPEN_SKILL_STARTS {
+                                            skill_starts.clear();
+                                        }
+                                        skill_starts.insert(start.correlation_key(), std::time::Instant::now());
+                                    }
+                                    Some(ClientEvent::SkillResult(ref mut result)) => {
+                                        result.elapsed_ms = skill_starts
+                                            .remove(&result.correlation_key())
+                                            .map(|t| t.elapsed().as_millis() as u64);
+                                    }
+                                    _ => {}
+                                }
+                                if let Some(event) = event {
+                                    let _ = channel.send(event);
+                                }
                             }
-                            "skill.config.response" => {
-                                let _ = channel.send(json!({"type": "skill.config.response", "payload": &parsed["payload"]}));
+                            "chat.chunk" | "chat.done" | "push.message" | "skill.progress"
+                            | "skill.list.response" | "skill.library.response" => {
+                                if msg_type == "chat.done" {
+                                    if let Some(conversation_id) = parsed["payload"]["conversationId"].as_str() {
+                                        streaming.lock().unwrap().remove(conversation_id);
+                                    }
+                                }
+                                if let Some(event) = ClientEvent::from_server(msg_type, &parsed["payload"]) {
+                                    let _ = channel.send(event);
+                                }
                             }
-                            "desktop.command" => {
-                                // Server is requesting local command execution
+                            "desktop.command" | "desktop.command.batch" => {
+                                // Server is requesting local command execution; a batch carries
+                                // an ordered `calls` list of { command, args } and an `atomic` flag
                                 let payload = parsed["payload"].clone();
                                 let command_id = payload["commandId"].as_str().unwrap_or("").to_string();
+                                let is_batch = msg_type == "desktop.command.batch";
                                 let function_name = payload["command"].as_str().unwrap_or("").to_string();
                                 let args = payload["args"].clone();
 
-                                println!("[WsClient] desktop.command: {} (id={})", function_name, command_id);
+                                if is_batch {
+                                    println!("[WsClient] desktop.command.batch: {} call(s) (id={})",
+                                        payload["calls"].as_array().map_or(0, |c| c.len()), command_id);
+                                } else {
+                                    println!("[WsClient] desktop.command: {} (id={})", function_name, command_id);
+                                }
 
                                 let sink_for_result = sink.clone();
+                                let running_for_task = running.clone();
+                                let task_id = command_id.clone();
+                                // Hold the registry lock across spawn + insert so a fast-finishing
+                                // task cannot look itself up before it is registered
+                                let mut registry = running.lock().unwrap();
                                 // Spawn async task to execute and respond
-                                tokio::spawn(async move {
-                                    let result = skill_executor::execute_local_command(&function_name, &args).await;
-
-                                    let result_msg = match result {
-                                        Ok(data) => json!({
-                                            "id": uuid::Uuid::new_v4().to_string(),
-                                            "type": "desktop.result",
-                                            "timestamp": chrono_timestamp(),
-                                            "payload": {
-                                                "commandId": command_id,
-                                                "success": true,
-                                                "data": data,
-                                            }
-                                        }),
-                                        Err(err) => json!({
-                                            "id": uuid::Uuid::new_v4().to_string(),
-                                            "type": "desktop.result",
-                                            "timestamp": chrono_timestamp(),
-                                            "payload": {
-                                                "commandId": command_id,
-                                                "success": false,
-                                                "error": err,
-                                            }
-                                        }),
+                                let sink_for_chunks = sink.clone();
+                                let chunk_id = command_id.clone();
+                                let task = tokio::spawn(async move {
+                                    // Partial output goes out as desktop.command.chunk, ahead of the result
+                                    let (chunk_tx, mut chunk_rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+                                    let forwarder = tokio::spawn(async move {
+                                        let mut seq: u64 = 0;
+                                        while let Some(data) = chunk_rx.recv().await {
+                                            let msg = json!({
+                                                "id": uuid::Uuid::new_v4().to_string(),
+                                                "type": "desktop.command.chunk",
+                                                "timestamp": chrono_timestamp(),
+                                                "payload": { "commandId": chunk_id, "seq": seq, "data": data },
+                                            });
+                                            seq += 1;
+                                            let _ = sink_for_chunks.lock().await.send(Message::Text(msg.to_string())).await;
+                                        }
+                                    });
+                                    let result = if is_batch {
+                                        let atomic = payload["atomic"].as_bool().unwrap_or(false);
+                                        drop(chunk_tx);
+                                        skill_executor::execute_batch(&payload["calls"], atomic).await
+                                    } else {
+                                        skill_executor::execute_local_command(&function_name, &args, Some(chunk_tx)).await
                                     };
+                                    let _ = forwarder.await;
 
-                                    if let Ok(mut s) = sink_for_result.try_lock() {
-                                        let _ = s.send(Message::Text(result_msg.to_string())).await;
+                                    // If the command was cancelled meanwhile, the cancel path already replied
+                                    if running_for_task.lock().unwrap().remove(&task_id).is_none() {
+                                        return;
                                     }
+
+                                    // Wait for the sink rather than drop the result while chunks are being sent
+                                    let mut result_msg = desktop_result_message(&task_id, result);
+                                    if is_batch && result_msg["payload"]["data"]["success"] == json!(false) {
+                                        // The combined result is still sent as `data`, with the batch marked failed
+                                        let failed_at = &result_msg["payload"]["data"]["failedAt"];
+                                        let error = skill_executor::SkillError::Failed(format!("Batch call {} failed", failed_at));
+                                        result_msg["payload"]["success"] = json!(false);
+                                        result_msg["payload"]["error"] = error.to_payload();
+                                    }
+                                    let _ = sink_for_result.lock().await.send(Message::Text(result_msg.to_string())).await;
                                 });
+                                registry.insert(command_id, task.abort_handle());
+                            }
+                            "desktop.command.cancel" => {
+                                let command_id = parsed["payload"]["commandId"].as_str().unwrap_or("");
+                                println!("[WsClient] desktop.command.cancel (id={})", command_id);
+                                Self::cancel_running_command(&running, &sink, command_id).await;
                             }
                             "ping" => {
                                 let pong = json!({
@@ -262,25 +506,59 @@ impl WsClient {
                                     let _ = s.send(Message::Text(pong.to_string())).await;
                                 }
                             }
-                            _ => {}
+                            "pong" => {
+                                let mut pending = pings.lock().unwrap();
+                                // Older servers don't echo the ping id; resolve any outstanding ping then
+                                let key = parsed["payload"]["pingId"]
+                                    .as_str()
+                                    .map(String::from)
+                                    .or_else(|| pending.keys().next().cloned());
+                                if let Some(tx) = key.and_then(|k| pending.remove(&k)) {
+                                    let _ = tx.send(());
+                                }
+                            }
+                            _ => {
+                                println!("[WsClient] Unrecognized message type: {}", msg_type);
+                                if forward_unknown.load(Ordering::Relaxed) {
+                                    let _ = channel.send(ClientEvent::Unknown(UnknownMessage {
+                                        original_type: msg_type.to_string(),
+                                        payload: parsed["payload"].clone(),
+                                    }));
+                                }
+                                if forward_raw.load(Ordering::Relaxed) {
+                                    let _ = channel.send(ClientEvent::Raw(RawEvent {
+                                        message_type: msg_type.to_string(),
+                                        payload: parsed["payload"].clone(),
+                                    }));
+                                }
+                            }
                         }
                     }
                 }
                 Ok(Message::Close(frame)) => {
                     println!("[WsClient] Close frame: {:?}", frame);
-                    let _ = channel.send(json!({"type": "disconnected", "payload": {"reason": "server_close"}}));
-                    return;
+                    let _ = channel.send(ClientEvent::disconnected("server_close"));
+                    return "server_close".to_string();
+                }
+                Err(tokio_tungstenite::tungstenite::Error::Capacity(e)) => {
+                    // Oversized message rejected by tungstenite; the stream is no longer usable
+                    println!("[WsClient] Message exceeded size limit: {}", e);
+                    let reason = format!("message_too_large: {}", e);
+                    let _ = channel.send(ClientEvent::disconnected(reason.clone()));
+                    return reason;
                 }
                 Err(e) => {
                     println!("[WsClient] Read error: {:?}", e);
-                    let _ = channel.send(json!({"type": "disconnected", "payload": {"reason": format!("error: {}", e)}}));
-                    return;
+                    let reason = format!("error: {}", e);
+                    let _ = channel.send(ClientEvent::disconnected(reason.clone()));
+                    return reason;
                 }
                 _ => {}
             }
         }
         println!("[WsClient] Stream ended");
-        let _ = channel.send(json!({"type": "disconnected", "payload": {"reason": "stream_ended"}}));
+        let _ = channel.send(ClientEvent::disconnected("stream_ended"));
+        "stream_ended".to_string()
     }
 
     /// Send desktop.register with capabilities and skill manifests.
@@ -300,84 +578,8 @@ impl WsClient {
                 "os": std::env::consts::OS,
                 "arch": std::env::consts::ARCH,
                 "hostname": host,
-                "skillManifests": [
-                    {
-                        "name": "shell",
-                        "description": "Execute shell commands on the user's desktop computer",
-                        "functions": [
-                            {
-                                "name": "run_shell",
-                                "description": "Execute a shell command and return stdout/stderr. Use this when the user asks to run commands on their computer.",
-                                "parameters": {
-                                    "type": "object",
-                                    "properties": {
-                                        "command": {
-                                            "type": "string",
-                                            "description": "The shell command to execute"
-                                        },
-                                        "timeout": {
-                                            "type": "integer",
-                                            "description": "Timeout in seconds (default: 30)"
-                                        }
-                                    },
-                                    "required": ["command"]
-                                }
-                            }
-                        ]
-                    },
-                    {
-                        "name": "filesystem",
-                        "description": "Read, write, and list files on the user's desktop computer",
-                        "functions": [
-                            {
-                                "name": "read_file",
-                                "description": "Read the contents of a file on the user's computer",
-                                "parameters": {
-                                    "type": "object",
-                                    "properties": {
-                                        "path": {
-                                            "type": "string",
-                                            "description": "Absolute path to the file"
-                                        }
-                                    },
-                                    "required": ["path"]
-                                }
-                            },
-                            {
-                                "name": "write_file",
-                                "description": "Write content to a file on the user's computer",
-                                "parameters": {
-                                    "type": "object",
-                                    "properties": {
-                                        "path": {
-                                            "type": "string",
-                                            "description": "Absolute path to the file"
-                                        },
-                                        "content": {
-                                            "type": "string",
-                                            "description": "Content to write"
-                                        }
-                                    },
-                                    "required": ["path", "content"]
-                                }
-                            },
-                            {
-                                "name": "list_directory",
-                                "description": "List files and directories in a path on the user's computer",
-                                "parameters": {
-                                    "type": "object",
-                                    "properties": {
-                                        "path": {
-                                            "type": "string",
-                                            "description": "Absolute path to the directory"
-                                        }
-                                    },
-                                    "required": ["path"]
-                                }
-                            }
-                        ]
-                    }
-                ]
+                "localAgents": skill_executor::local_agents(),
+                "skillManifests": skill_executor::enabled_skill_manifests(),
             }
         });
 
@@ -387,14 +589,31 @@ impl WsClient {
         Ok(())
     }
 
+    /// Send a chat message. Refused while an earlier message in the same
+    /// conversation is still generating, unless `force` is set.
     pub async fn send_chat(
         &self,
         conversation_id: &str,
         content: &str,
         history: &[ChatMessage],
+        force: bool,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let sink = self.sink.as_ref().ok_or("Not connected")?;
 
+        {
+            let mut streaming = self.streaming.lock().unwrap();
+            let in_progress = streaming
+                .get(conversation_id)
+                .map(|sent| sent.elapsed().as_secs() < STREAMING_STALE_SECS)
+                .unwrap_or(false);
+            if in_progress && !force {
+                return Err(format!("Generation in progress for conversation {}", conversation_id).into());
+            }
+            streaming.insert(conversation_id.to_string(), std::time::Instant::now());
+        }
+        // A new generation gets its own stop; don't let it be debounced away
+        *self.last_stop.lock().unwrap() = None;
+
         let history_json: Vec<Value> = history
             .iter()
             .map(|m| json!({"role": m.role, "content": m.content}))
@@ -412,12 +631,27 @@ impl WsClient {
         });
 
         let mut s = sink.lock().await;
-        s.send(Message::Text(msg.to_string())).await?;
+        if let Err(e) = s.send(Message::Text(msg.to_string())).await {
+            self.streaming.lock().unwrap().remove(conversation_id);
+            return Err(e.into());
+        }
         Ok(())
     }
 
+    /// Stop the session's current generation. Calls repeated within
+    /// `STOP_DEBOUNCE_MS` with no `send_chat` in between are coalesced and
+    /// send nothing.
     pub async fn stop_chat(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let sink = self.sink.as_ref().ok_or("Not connected")?;
+        {
+            let mut last_stop = self.last_stop.lock().unwrap();
+            if last_stop.map(|t| t.elapsed().as_millis() < STOP_DEBOUNCE_MS as u128).unwrap_or(false) {
+                return Ok(());
+            }
+            *last_stop = Some(std::time::Instant::now());
+        }
+        // The server aborts whatever the session is generating, which may not end with chat.done
+        self.streaming.lock().unwrap().clear();
         let msg = json!({
             "id": uuid::Uuid::new_v4().to_string(),
             "type": "chat.stop",
@@ -529,6 +763,23 @@ impl WsClient {
         Ok(())
     }
 
+    /// Request a skill's config and return a receiver resolved with the
+    /// `skill.config.response` payload. Await it without holding the client lock.
+    pub async fn fetch_skill_config(
+        &self,
+        skill_name: &str,
+    ) -> Result<oneshot::Receiver<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let (tx, rx) = oneshot::channel();
+        self.pending_configs
+            .lock()
+            .unwrap()
+            .entry(skill_name.to_string())
+            .or_default()
+            .push(tx);
+        self.send_skill_config_get(skill_name).await?;
+        Ok(rx)
+    }
+
     pub async fn send_skill_config_set(
         &self,
         skill_name: &str,
@@ -549,6 +800,110 @@ impl WsClient {
         Ok(())
     }
 
+    /// Abort an in-flight desktop command. Returns false if no such command is running.
+    pub async fn cancel_command(&self, command_id: &str) -> bool {
+        match self.sink.as_ref() {
+            Some(sink) => Self::cancel_running_command(&self.running_commands, sink, command_id).await,
+            None => false,
+        }
+    }
+
+    /// Abort the task (its child process is killed on drop) and report a
+    /// cancelled `desktop.result` to the server.
+    async fn cancel_running_command(
+        running: &RunningCommands,
+        sink: &Arc<Mutex<WsSink>>,
+        command_id: &str,
+    ) -> bool {
+        let handle = running.lock().unwrap().remove(command_id);
+        let Some(handle) = handle else {
+            return false;
+        };
+        handle.abort();
+
+        let mut msg = desktop_result_message(
+            command_id,
+            Err(skill_executor::SkillError::Cancelled("Command cancelled".to_string())),
+        );
+        msg["payload"]["cancelled"] = json!(true);
+        let mut s = sink.lock().await;
+        let _ = s.send(Message::Text(msg.to_string())).await;
+        println!("[WsClient] Cancelled desktop command {}", command_id);
+        true
+    }
+
+    /// Send an arbitrary message type wrapped in the standard envelope.
+    pub async fn send_raw(
+        &self,
+        msg_type: &str,
+        payload: &Value,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let sink = self.sink.as_ref().ok_or("Not connected")?;
+        let msg = json!({
+            "id": uuid::Uuid::new_v4().to_string(),
+            "type": msg_type,
+            "timestamp": chrono_timestamp(),
+            "payload": payload
+        });
+        let mut s = sink.lock().await;
+        s.send(Message::Text(msg.to_string())).await?;
+        Ok(())
+    }
+
+    /// Send a `ping` and return its id plus a receiver resolved by the matching `pong`.
+    /// The caller should await the receiver without holding the client lock.
+    pub async fn send_ping(&self) -> Result<(String, oneshot::Receiver<()>), Box<dyn std::error::Error + Send + Sync>> {
+        let sink = self.sink.as_ref().ok_or("Not connected")?;
+        let ping_id = uuid::Uuid::new_v4().to_string();
+        let (tx, rx) = oneshot::channel();
+        self.pending_pings.lock().unwrap().insert(ping_id.clone(), tx);
+
+        let msg = json!({
+            "id": ping_id,
+            "type": "ping",
+            "timestamp": chrono_timestamp(),
+        });
+        let mut s = sink.lock().await;
+        if let Err(e) = s.send(Message::Text(msg.to_string())).await {
+            self.pending_pings.lock().unwrap().remove(&ping_id);
+            return Err(e.into());
+        }
+        Ok((ping_id, rx))
+    }
+
+    /// Drop config waiters for `skill_name` whose receiver is gone (timed out).
+    pub fn forget_config_waiters(&self, skill_name: &str) {
+        let mut pending = self.pending_configs.lock().unwrap();
+        if let Some(waiters) = pending.get_mut(skill_name) {
+            waiters.retain(|tx| !tx.is_closed());
+            if waiters.is_empty() {
+                pending.remove(skill_name);
+            }
+        }
+    }
+
+    /// Drop a ping that never got its pong.
+    pub fn forget_ping(&self, ping_id: &str) {
+        self.pending_pings.lock().unwrap().remove(ping_id);
+    }
+
+    /// Record a round-trip sample and return the rolling average in ms.
+    pub fn record_latency(&mut self, rtt_ms: u64) -> f64 {
+        if self.latency_samples.len() >= LATENCY_SAMPLES {
+            self.latency_samples.pop_front();
+        }
+        self.latency_samples.push_back(rtt_ms);
+        self.average_latency().unwrap_or(rtt_ms as f64)
+    }
+
+    /// Rolling average of recent round trips, if any were measured on this connection.
+    pub fn average_latency(&self) -> Option<f64> {
+        if self.latency_samples.is_empty() {
+            return None;
+        }
+        Some(self.latency_samples.iter().sum::<u64>() as f64 / self.latency_samples.len() as f64)
+    }
+
     pub async fn disconnect(&mut self) {
         println!("[WsClient] Disconnecting...");
         if let Some(handle) = self.read_handle.take() {
@@ -559,8 +914,82 @@ impl WsClient {
                 let _ = s.close().await;
             }
         }
-        self.connected = false;
+        self.connected.store(false, Ordering::SeqCst);
         self.session_id = None;
+        self.pending_pings.lock().unwrap().clear();
+        self.pending_configs.lock().unwrap().clear();
+        self.streaming.lock().unwrap().clear();
+        self.latency_samples.clear();
+    }
+}
+
+/// Build the `desktop.result` message reporting a command's outcome.
+fn desktop_result_message(command_id: &str, result: Result<Value, skill_executor::SkillError>) -> Value {
+    let payload = match result {
+        Ok(data) => json!({
+            "commandId": command_id,
+            "success": true,
+            "data": data,
+        }),
+        Err(err) => json!({
+            "commandId": command_id,
+            "success": false,
+            "error": err.to_payload(),
+        }),
+    };
+    json!({
+        "id": uuid::Uuid::new_v4().to_string(),
+        "type": "desktop.result",
+        "timestamp": chrono_timestamp(),
+        "payload": payload,
+    })
+}
+
+/// Compare two dotted version strings numerically ("0.10.0" > "0.9.3").
+/// Missing or non-numeric components are treated as 0. A prerelease suffix
+/// sorts below its release ("2026.1.5-beta.1" < "2026.1.5").
+pub(crate) fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+    let split = |v: &str| -> (Vec<u64>, Option<String>) {
+        let v = v.trim_start_matches('v');
+        let v = v.split('+').next().unwrap_or(v);
+        let (core, pre) = match v.split_once('-') {
+            Some((core, pre)) => (core, Some(pre.to_string())),
+            None => (v, None),
+        };
+        let nums = core.split('.').take(3).map(|p| p.parse().unwrap_or(0)).collect();
+        (nums, pre)
+    };
+    let ((pa, pre_a), (pb, pre_b)) = (split(a), split(b));
+    for i in 0..3 {
+        let (x, y) = (pa.get(i).copied().unwrap_or(0), pb.get(i).copied().unwrap_or(0));
+        if x != y {
+            return x.cmp(&y);
+        }
+    }
+    match (pre_a, pre_b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(x), Some(y)) => {
+            let (mut xs, mut ys) = (x.split('.'), y.split('.'));
+            loop {
+                let ord = match (xs.next(), ys.next()) {
+                    (None, None) => return Ordering::Equal,
+                    (None, Some(_)) => return Ordering::Less,
+                    (Some(_), None) => return Ordering::Greater,
+                    (Some(p), Some(q)) => match (p.parse::<u64>(), q.parse::<u64>()) {
+                        (Ok(m), Ok(n)) => m.cmp(&n),
+                        (Ok(_), Err(_)) => Ordering::Less,
+                        (Err(_), Ok(_)) => Ordering::Greater,
+                        (Err(_), Err(_)) => p.cmp(q),
+                    },
+                };
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+        }
     }
 }
 
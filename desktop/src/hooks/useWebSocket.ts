@@ -10,6 +10,8 @@ interface WsEvent {
     fullContent?: string;
     skillName?: string;
     description?: string;
+    output?: string;
+    event?: Record<string, unknown>;
     message?: string;
     reason?: string;
     skillsInvoked?: Array<{
@@ -24,6 +26,29 @@ interface ConnectResult {
   session_id: string;
   device_id: string;
   skills: string[];
+  server_protocol_version: number | null;
+  version_warning: string | null;
+  candidate_index?: number | null;
+}
+
+/** Last non-empty line of streamed skill output, trimmed for the status line */
+function lastOutputLine(output: string): string {
+  const lines = output.split(/\r?\n/).map((l) => l.trim()).filter(Boolean);
+  return (lines[lines.length - 1] || '').slice(-120);
+}
+
+/** Status line for a structured progress event, e.g. a Claude Code turn */
+function progressEventLine(event: Record<string, unknown>): string {
+  switch (event.kind) {
+    case 'text':
+      return lastOutputLine(String(event.text ?? ''));
+    case 'tool_use':
+      return `Using ${String(event.tool ?? 'tool')}...`;
+    case 'result':
+      return event.isError ? 'Failed' : `Done in ${String(event.turns ?? '?')} turns`;
+    default:
+      return '';
+  }
 }
 
 function flog(msg: string) {
@@ -96,6 +121,15 @@ export function useWebSocket() {
               });
               break;
             }
+            case 'skill.progress': {
+              const line = payload?.event
+                ? progressEventLine(payload.event)
+                : lastOutputLine(payload?.output || '');
+              if (line) {
+                setActiveSkill({ name: payload?.skillName || 'unknown', description: line });
+              }
+              break;
+            }
             case 'skill.result': {
               setActiveSkill(null);
               break;
@@ -150,6 +184,9 @@ export function useWebSocket() {
         });
 
         flog('connect_server resolved: sessionId=' + result.session_id);
+        if (result.version_warning) {
+          flog('connect_server version warning: ' + result.version_warning);
+        }
         setConnected(true);
         setConnecting(false);
         setSessionId(result.session_id);
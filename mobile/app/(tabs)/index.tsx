@@ -43,6 +43,7 @@ import type {
   ErrorMessage,
   SkillStartMessage,
   SkillResultMessage,
+  SkillProgressMessage,
   PushMessage,
   ServerMessage,
   SkillListResponseMessage,
@@ -405,6 +406,21 @@ export default function ChatScreen() {
         setActiveSkill({ name: skill.payload.skillName, description: skill.payload.description });
       });
 
+      const unsubSkillProgress = client.on(MessageType.SKILL_PROGRESS, (msg: ServerMessage) => {
+        const progress = msg as SkillProgressMessage;
+        // Show the latest line of live output (e.g. an interactive run_shell) or,
+        // for structured progress such as a Claude Code turn, its text or tool
+        const event = progress.payload.event;
+        const text = event
+          ? (event.kind === 'text' ? String(event.text ?? '') : event.kind === 'tool_use' ? `Using ${String(event.tool ?? 'tool')}...` : '')
+          : progress.payload.output || '';
+        const lines = text.split(/\r?\n/).map((l) => l.trim()).filter(Boolean);
+        const line = (lines[lines.length - 1] || '').slice(-120);
+        if (line) {
+          setActiveSkill({ name: progress.payload.skillName, description: line });
+        }
+      });
+
       const unsubSkillResult = client.on(MessageType.SKILL_RESULT, (msg: ServerMessage) => {
         const result = msg as SkillResultMessage;
         // Show completed skill briefly, then clear — don't add to messages array
@@ -486,6 +502,7 @@ export default function ChatScreen() {
         unsubChunk();
         unsubDone();
         unsubSkillStart();
+        unsubSkillProgress();
         unsubSkillResult();
         unsubPush();
         unsubError();
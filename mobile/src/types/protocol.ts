@@ -27,6 +27,7 @@ export enum MessageType {
   CHAT_DONE = 'chat.done',
   SKILL_START = 'skill.start',
   SKILL_RESULT = 'skill.result',
+  SKILL_PROGRESS = 'skill.progress',
   PUSH_MESSAGE = 'push.message',
   SKILL_LIST_RESPONSE = 'skill.list.response',
   SKILL_LIBRARY_RESPONSE = 'skill.library.response',
@@ -184,6 +185,18 @@ export interface SkillResultMessage extends BaseMessage {
   };
 }
 
+/** Live output of a running skill, between its skill.start and skill.result */
+export interface SkillProgressMessage extends BaseMessage {
+  type: MessageType.SKILL_PROGRESS;
+  payload: {
+    conversationId: string;
+    skillName: string;
+    invocationId?: string;
+    output?: string;
+    event?: Record<string, unknown>;
+  };
+}
+
 export interface PushMessage extends BaseMessage {
   type: MessageType.PUSH_MESSAGE;
   payload: {
@@ -274,6 +287,7 @@ export type ServerMessage =
   | ChatDoneMessage
   | SkillStartMessage
   | SkillResultMessage
+  | SkillProgressMessage
   | PushMessage
   | SkillListResponseMessage
   | SkillLibraryResponseMessage
@@ -5,6 +5,7 @@
 
 import type { SkillHandler } from '../registry.js';
 import { executeOnDesktop } from '../../websocket/handler.js';
+import { getDesktopSession } from '../../adapters/desktop.js';
 
 const runClaudeCode: SkillHandler = async (args, context) => {
   const userId = context?.userId;
@@ -12,13 +13,31 @@ const runClaudeCode: SkillHandler = async (args, context) => {
     throw new Error('请先登录后再使用 Claude Code 远程功能');
   }
 
+  // The desktop reports 'claude-code' in localAgents only when the claude CLI is installed
+  const localAgents = getDesktopSession(userId)?.capabilities.localAgents;
+  if (!localAgents?.includes('claude-code')) {
+    throw new Error('桌面端未检测到 Claude Code，请先在电脑上安装 claude CLI');
+  }
+
   const prompt = args.prompt as string;
   if (!prompt) {
     throw new Error('Missing prompt parameter');
   }
 
+  // Each chunk is one stream-json progress event (init, text, tool_use, tool_result, result)
+  const onProgress = context?.onProgress;
+  const onChunk = onProgress
+    ? (data: string) => {
+      try {
+        onProgress({ event: JSON.parse(data) as Record<string, unknown> });
+      } catch {
+        onProgress({ output: data });
+      }
+    }
+    : undefined;
+
   // 5 minute timeout for Claude Code execution
-  return executeOnDesktop(userId, 'run_claude_code', args, 300000);
+  return executeOnDesktop(userId, 'run_claude_code', args, 300000, onChunk);
 };
 
 /** All handlers exported for registry registration */
@@ -35,9 +35,17 @@ export interface FunctionCallingTool {
 }
 
 /** User context for skill visibility filtering */
+/** Progress reported by a skill while it runs; see SkillProgressMessage */
+export interface SkillProgress {
+  output?: string;
+  event?: Record<string, unknown>;
+}
+
 export interface SkillUserContext {
   userId?: string | null;
   userPhone?: string | null;
+  /** Forwards live progress to the client as skill.progress, when the caller supports it */
+  onProgress?: (progress: SkillProgress) => void;
 }
 
 class SkillRegistry {
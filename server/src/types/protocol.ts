@@ -27,6 +27,7 @@ export enum MessageType {
   CHAT_DONE = 'chat.done',
   SKILL_START = 'skill.start',
   SKILL_RESULT = 'skill.result',
+  SKILL_PROGRESS = 'skill.progress',
   PUSH_MESSAGE = 'push.message',
   SKILL_LIST_RESPONSE = 'skill.list.response',
   SKILL_LIBRARY_RESPONSE = 'skill.library.response',
@@ -36,7 +37,9 @@ export enum MessageType {
   // Desktop <-> Server
   DESKTOP_REGISTER = 'desktop.register',
   DESKTOP_COMMAND = 'desktop.command',
+  DESKTOP_COMMAND_BATCH = 'desktop.command.batch',
   DESKTOP_RESULT = 'desktop.result',
+  DESKTOP_COMMAND_CHUNK = 'desktop.command.chunk',
 
   // Bridge <-> Server (OpenClaw Bridge protocol)
   BRIDGE_REGISTER = 'bridge.register',
@@ -184,6 +187,8 @@ export interface SkillStartMessage extends BaseMessage {
     conversationId: string;
     skillName: string;
     description: string;
+    /** Shared with the matching skill.result */
+    invocationId?: string;
   };
 }
 
@@ -195,6 +200,22 @@ export interface SkillResultMessage extends BaseMessage {
     success: boolean;
     data?: Record<string, unknown>;
     error?: string;
+    /** Same id as the skill.start this result belongs to */
+    invocationId?: string;
+  };
+}
+
+/** Live output of a running skill, between its skill.start and skill.result */
+export interface SkillProgressMessage extends BaseMessage {
+  type: MessageType.SKILL_PROGRESS;
+  payload: {
+    conversationId: string;
+    skillName: string;
+    invocationId?: string;
+    /** Plain output, e.g. terminal text from an interactive run_shell */
+    output?: string;
+    /** Structured progress, e.g. one Claude Code turn */
+    event?: Record<string, unknown>;
   };
 }
 
@@ -338,6 +359,18 @@ export interface DesktopCommandMessage extends BaseMessage {
   };
 }
 
+/** Server asks desktop to run several commands in order; with `atomic`, a
+ *  failure stops the batch and rolls back file changes made by earlier calls */
+export interface DesktopCommandBatchMessage extends BaseMessage {
+  type: MessageType.DESKTOP_COMMAND_BATCH;
+  payload: {
+    calls: Array<{ command: string; args?: Record<string, unknown> }>;
+    atomic?: boolean;
+    /** Unique ID for correlating the batch with its single result */
+    commandId: string;
+  };
+}
+
 /** Desktop sends execution result back */
 export interface DesktopResultMessage extends BaseMessage {
   type: MessageType.DESKTOP_RESULT;
@@ -345,10 +378,30 @@ export interface DesktopResultMessage extends BaseMessage {
     commandId: string;
     success: boolean;
     data?: Record<string, unknown>;
-    error?: string;
+    /** Older desktops send a plain string; newer ones send `{ code, message }` */
+    error?: string | DesktopErrorPayload;
+    cancelled?: boolean;
+  };
+}
+
+/** Desktop streams partial output of a running command before its result */
+export interface DesktopCommandChunkMessage extends BaseMessage {
+  type: MessageType.DESKTOP_COMMAND_CHUNK;
+  payload: {
+    commandId: string;
+    /** Increments from 0 per command */
+    seq: number;
+    data: string;
   };
 }
 
+/** Structured desktop command failure, e.g. `{ code: 'timeout', message: '...' }` */
+export interface DesktopErrorPayload {
+  code: 'unknown_function' | 'disabled' | 'invalid_args' | 'not_found' | 'permission_denied'
+    | 'timeout' | 'unavailable' | 'cancelled' | 'failed';
+  message: string;
+}
+
 // ===== Bridge Messages =====
 
 /** Bridge registers with server, declaring its userId and capabilities */
@@ -437,11 +490,15 @@ export interface PingMessage extends BaseMessage {
 
 export interface PongMessage extends BaseMessage {
   type: MessageType.PONG;
+  payload?: {
+    /** id of the ping being answered, so clients can measure round-trip time */
+    pingId: string;
+  };
 }
 
 // ===== Union =====
 
-export type ClientMessage = ConnectMessage | ChatSendMessage | ChatStopMessage | SkillListRequestMessage | SkillToggleMessage | SkillInstallMessage | SkillUninstallMessage | SkillLibraryRequestMessage | SkillConfigGetMessage | SkillConfigSetMessage | DesktopRegisterMessage | DesktopCommandMessage | DesktopResultMessage | BridgeRegisterMessage | BridgeChatChunkMessage | BridgeChatDoneMessage | BridgeChatErrorMessage | BridgeSkillEventMessage | BridgeStatusMessage | PingMessage;
+export type ClientMessage = ConnectMessage | ChatSendMessage | ChatStopMessage | SkillListRequestMessage | SkillToggleMessage | SkillInstallMessage | SkillUninstallMessage | SkillLibraryRequestMessage | SkillConfigGetMessage | SkillConfigSetMessage | DesktopRegisterMessage | DesktopCommandMessage | DesktopResultMessage | DesktopCommandChunkMessage | BridgeRegisterMessage | BridgeChatChunkMessage | BridgeChatDoneMessage | BridgeChatErrorMessage | BridgeSkillEventMessage | BridgeStatusMessage | PingMessage;
 
 export type ServerMessage =
   | ConnectedMessage
@@ -449,11 +506,13 @@ export type ServerMessage =
   | ChatDoneMessage
   | SkillStartMessage
   | SkillResultMessage
+  | SkillProgressMessage
   | PushMessage
   | SkillListResponseMessage
   | SkillLibraryResponseMessage
   | SkillConfigResponseMessage
   | DesktopCommandMessage
+  | DesktopCommandBatchMessage
   | DesktopResultMessage
   | BridgeRegisteredMessage
   | BridgeChatRequestMessage
@@ -16,7 +16,9 @@ import {
   type SkillLibraryRequestMessage,
   type DesktopRegisterMessage,
   type DesktopCommandMessage,
+  type DesktopCommandBatchMessage,
   type DesktopResultMessage,
+  type DesktopCommandChunkMessage,
   type BridgeRegisterMessage,
   type BridgeChatChunkMessage,
   type BridgeChatDoneMessage,
@@ -29,7 +31,7 @@ import type { LLMProvider, ToolCall } from '../providers/base.js';
 import { OpenClawAdapter } from '../adapters/openclaw.js';
 import { isAgentAdapter, type AgentAdapter } from '../adapters/base.js';
 import { DesktopAdapter, registerDesktopSession, unregisterDesktopSession, getDesktopSession, hasDesktopOnline } from '../adapters/desktop.js';
-import { skillRegistry } from '../skills/registry.js';
+import { skillRegistry, type SkillHandler, type SkillUserContext } from '../skills/registry.js';
 import { checkRateLimit, incrementCount } from '../middleware/rateLimit.js';
 import {
   installSkillForUser,
@@ -139,25 +141,31 @@ const pendingBridgeChats = new Map<string, PendingBridgeChat>();
 
 // ── Desktop pending commands: commandId → { resolve, reject, timer } ──
 interface PendingDesktopCommand {
-  resolve: (value: { success: boolean; data?: Record<string, unknown>; error?: string }) => void;
+  resolve: (value: { success: boolean; data?: Record<string, unknown>; error?: string; errorCode?: string }) => void;
   reject: (reason: Error) => void;
+  onChunk?: (data: string) => void;
+  /** Chunks received so far, by seq, for results sent as `chunked` */
+  chunks: string[];
   timer: ReturnType<typeof setTimeout>;
 }
 const pendingDesktopCommands = new Map<string, PendingDesktopCommand>();
 
+/** Error from a failed desktop command; `data` holds the partial result, if any */
+export type DesktopCommandError = Error & { code?: string; data?: Record<string, unknown> };
+
 /**
- * Execute a function on the user's desktop client.
- * Sends DESKTOP_COMMAND via WebSocket and waits for DESKTOP_RESULT.
+ * Send the command message built for a fresh commandId to the user's desktop
+ * client and wait for its DESKTOP_RESULT, resolving with the result data as JSON.
  */
-export async function executeOnDesktop(
+function sendToDesktop(
   userId: string,
-  functionName: string,
-  args: Record<string, unknown>,
-  timeout = 30000,
+  buildMessage: (commandId: string) => DesktopCommandMessage | DesktopCommandBatchMessage,
+  timeout: number,
+  onChunk?: (data: string) => void,
 ): Promise<string> {
   const desktopWs = desktopWebSockets.get(userId);
   if (!desktopWs || desktopWs.readyState !== WebSocket.OPEN) {
-    throw new Error('Desktop client not connected');
+    return Promise.reject(new Error('Desktop client not connected'));
   }
 
   const commandId = uuidv4();
@@ -175,7 +183,11 @@ export async function executeOnDesktop(
         if (result.success) {
           resolve(JSON.stringify(result.data || {}));
         } else {
-          reject(new Error(result.error || 'Desktop command failed'));
+          const err = new Error(result.error || 'Desktop command failed') as DesktopCommandError;
+          err.code = result.errorCode;
+          // e.g. a failed batch's per-call results and rollback report
+          err.data = result.data;
+          reject(err);
         }
       },
       reject: (err) => {
@@ -183,25 +195,59 @@ export async function executeOnDesktop(
         pendingDesktopCommands.delete(commandId);
         reject(err);
       },
+      onChunk,
+      chunks: [],
       timer,
     });
 
-    // Send command to desktop
-    send(desktopWs, {
-      id: uuidv4(),
-      type: MessageType.DESKTOP_COMMAND,
-      timestamp: Date.now(),
-      payload: {
-        command: functionName,
-        args,
-        commandId,
-      },
-    });
+    const message = buildMessage(commandId);
+    send(desktopWs, message);
 
-    console.log(`[Desktop] Sent command "${functionName}" (id=${commandId}) to desktop for user ${userId}`);
+    console.log(`[Desktop] Sent ${message.type} (id=${commandId}) to desktop for user ${userId}`);
   });
 }
 
+/**
+ * Execute a function on the user's desktop client.
+ * Sends DESKTOP_COMMAND via WebSocket and waits for DESKTOP_RESULT.
+ * `onChunk` sees every DESKTOP_COMMAND_CHUNK as it arrives, including the
+ * pieces of a `chunked` result, so only pass one for live-output functions.
+ */
+export async function executeOnDesktop(
+  userId: string,
+  functionName: string,
+  args: Record<string, unknown>,
+  timeout = 30000,
+  onChunk?: (data: string) => void,
+): Promise<string> {
+  return sendToDesktop(userId, (commandId) => ({
+    id: uuidv4(),
+    type: MessageType.DESKTOP_COMMAND,
+    timestamp: Date.now(),
+    payload: { command: functionName, args, commandId },
+  }), timeout, onChunk);
+}
+
+/**
+ * Execute several functions on the user's desktop client in order, as one
+ * DESKTOP_COMMAND_BATCH. Resolves with `{ success, atomic, results, ... }`;
+ * if any call fails it rejects with a DesktopCommandError whose `data` holds
+ * the per-call results and, for atomic batches, the rollback report.
+ */
+export async function executeBatchOnDesktop(
+  userId: string,
+  calls: Array<{ command: string; args?: Record<string, unknown> }>,
+  atomic = false,
+  timeout = 60000,
+): Promise<string> {
+  return sendToDesktop(userId, (commandId) => ({
+    id: uuidv4(),
+    type: MessageType.DESKTOP_COMMAND_BATCH,
+    timestamp: Date.now(),
+    payload: { calls, atomic, commandId },
+  }), timeout);
+}
+
 // ── Push message infrastructure ──
 const pendingPushMessages: Array<{ content: string; timestamp: number }> = [];
 let activePushWs: WebSocket | null = null;
@@ -359,10 +405,29 @@ export function handleConnection(ws: WebSocket): void {
           const resultPayload = (message as DesktopResultMessage).payload;
           const pending = pendingDesktopCommands.get(resultPayload.commandId);
           if (pending) {
+            const rawError = resultPayload.error;
+            const data = resultPayload.data;
+            // Large results (e.g. read_file) arrive as chunks; reassemble them into `content`
+            if (resultPayload.success && data?.chunked === true) {
+              const expected = Number(data.chunks ?? 0);
+              const received = pending.chunks.filter((c) => c !== undefined).length;
+              if (received !== expected) {
+                pending.resolve({
+                  success: false,
+                  error: `Incomplete transfer: received ${received} of ${expected} chunks`,
+                  errorCode: 'failed',
+                });
+                break;
+              }
+              data.content = pending.chunks.join('');
+              delete data.chunked;
+              delete data.chunks;
+            }
             pending.resolve({
               success: resultPayload.success,
-              data: resultPayload.data,
-              error: resultPayload.error,
+              data,
+              error: typeof rawError === 'string' ? rawError : rawError?.message,
+              errorCode: typeof rawError === 'string' ? undefined : rawError?.code,
             });
           } else {
             console.log('[Desktop] Result received for unknown commandId:', resultPayload.commandId);
@@ -370,6 +435,17 @@ export function handleConnection(ws: WebSocket): void {
           break;
         }
 
+        case MessageType.DESKTOP_COMMAND_CHUNK: {
+          // Partial output of a running desktop command; chunks are best-effort
+          const chunkPayload = (message as DesktopCommandChunkMessage).payload;
+          const pendingCommand = pendingDesktopCommands.get(chunkPayload.commandId);
+          if (pendingCommand) {
+            pendingCommand.chunks[chunkPayload.seq] = chunkPayload.data;
+            pendingCommand.onChunk?.(chunkPayload.data);
+          }
+          break;
+        }
+
         case MessageType.BRIDGE_REGISTER:
           handleBridgeRegister(ws, message as BridgeRegisterMessage);
           break;
@@ -422,7 +498,7 @@ export function handleConnection(ws: WebSocket): void {
         }
 
         case MessageType.PING:
-          send(ws, { id: uuidv4(), type: MessageType.PONG, timestamp: Date.now() });
+          send(ws, { id: uuidv4(), type: MessageType.PONG, timestamp: Date.now(), payload: { pingId: message.id } });
           break;
 
         default:
@@ -830,6 +906,7 @@ async function handleChatSend(
 
   let fullContent = '';
   const skillsInvoked: SkillInvocation[] = [];
+  const invocations = createInvocationTracker();
 
   try {
     console.log(`[Chat] handleChatSend: mode=${session.mode}, content="${content.slice(0, 50)}", wsState=${ws.readyState}`);
@@ -888,11 +965,12 @@ async function handleChatSend(
           },
           onSkillEvent: (phase, skillName, data, error) => {
             if (phase === 'start') {
+              const invocationId = invocations.start(skillName);
               send(ws, {
                 id: uuidv4(),
                 type: MessageType.SKILL_START,
                 timestamp: Date.now(),
-                payload: { conversationId, skillName, description: `Running ${skillName}...` },
+                payload: { conversationId, skillName, description: `Running ${skillName}...`, invocationId },
               });
             } else if (phase === 'result') {
               const resultData = data || {};
@@ -900,7 +978,7 @@ async function handleChatSend(
                 id: uuidv4(),
                 type: MessageType.SKILL_RESULT,
                 timestamp: Date.now(),
-                payload: { conversationId, skillName, success: true, data: resultData },
+                payload: { conversationId, skillName, success: true, data: resultData, invocationId: invocations.finish(skillName) },
               });
               skillsInvoked.push({ name: skillName, input: {}, output: resultData });
             } else if (phase === 'error') {
@@ -908,7 +986,7 @@ async function handleChatSend(
                 id: uuidv4(),
                 type: MessageType.SKILL_RESULT,
                 timestamp: Date.now(),
-                payload: { conversationId, skillName, success: false, error: error || 'Tool error' },
+                payload: { conversationId, skillName, success: false, error: error || 'Tool error', invocationId: invocations.finish(skillName) },
               });
             }
           },
@@ -933,6 +1011,7 @@ async function handleChatSend(
               conversationId,
               skillName: event.name,
               description: `Running ${event.name}...`,
+              invocationId: invocations.start(event.name),
             },
           });
         } else if (event.phase === 'result') {
@@ -946,6 +1025,7 @@ async function handleChatSend(
               skillName: event.name,
               success: true,
               data: resultData,
+              invocationId: invocations.finish(event.name),
             },
           });
           skillsInvoked.push({
@@ -963,6 +1043,7 @@ async function handleChatSend(
               skillName: event.name,
               success: false,
               error: event.error || 'Tool execution failed',
+              invocationId: invocations.finish(event.name),
             },
           });
         }
@@ -1125,6 +1206,7 @@ async function handleFunctionCallingChat(
         args = {};
       }
 
+      const invocationId = toolCall.id || uuidv4();
       send(ws, {
         id: uuidv4(),
         type: MessageType.SKILL_START,
@@ -1133,12 +1215,25 @@ async function handleFunctionCallingChat(
           conversationId,
           skillName: functionName,
           description: `Running ${functionName}...`,
+          invocationId,
         },
       });
 
       let toolResult: string;
       try {
-        const userCtxExec = { userId: session.userId, userPhone: session.userPhone };
+        const userCtxExec: SkillUserContext = {
+          userId: session.userId,
+          userPhone: session.userPhone,
+          onProgress: (progress) => {
+            if (abortController.signal.aborted) return;
+            send(ws, {
+              id: uuidv4(),
+              type: MessageType.SKILL_PROGRESS,
+              timestamp: Date.now(),
+              payload: { conversationId, skillName: functionName, invocationId, ...progress },
+            });
+          },
+        };
         const execInstalledNames = session.userId
           ? getUserInstalledSkillNames(session.userId)
           : null;
@@ -1162,6 +1257,7 @@ async function handleFunctionCallingChat(
             skillName,
             success: true,
             data: resultData,
+            invocationId,
           },
         });
 
@@ -1184,6 +1280,7 @@ async function handleFunctionCallingChat(
             skillName: functionName,
             success: false,
             error: errorMsg,
+            invocationId,
           },
         });
 
@@ -1224,6 +1321,9 @@ async function handleFunctionCallingChat(
 /** Track which desktop skills are registered per userId, for cleanup on disconnect */
 const desktopSkillNames = new Map<string, string[]>();
 
+/** Desktop functions whose chunks are live output (other chunks carry a large result) */
+const DESKTOP_LIVE_OUTPUT_FUNCTIONS = new Set(['run_shell']);
+
 function handleDesktopRegister(ws: WebSocket, session: Session, message: DesktopRegisterMessage): void {
   const userId = session.userId;
   if (!userId) return;
@@ -1282,13 +1382,17 @@ function handleDesktopRegister(ws: WebSocket, session: Session, message: Desktop
     };
 
     // Create proxy handlers that route to desktop execution
-    const handlers: Record<string, (args: Record<string, unknown>) => Promise<string>> = {};
+    const handlers: Record<string, SkillHandler> = {};
     for (const fn of sm.functions) {
       const fnName = fn.name;
       const capturedUserId = userId;
       const fnTimeout = fn.timeout || 30000;
-      handlers[fnName] = async (args: Record<string, unknown>) => {
-        return executeOnDesktop(capturedUserId, fnName, args, fnTimeout);
+      handlers[fnName] = async (args, context) => {
+        const onProgress = context?.onProgress;
+        const onChunk = onProgress && DESKTOP_LIVE_OUTPUT_FUNCTIONS.has(fnName)
+          ? (data: string) => onProgress({ output: data })
+          : undefined;
+        return executeOnDesktop(capturedUserId, fnName, args, fnTimeout, onChunk);
       };
     }
 
@@ -1431,6 +1535,27 @@ export function sendChatViaBridge(
   console.log(`[Bridge] Chat request sent to bridge for user ${userId}, convId=${conversationId}`);
 }
 
+/**
+ * Pairs tool start/result events that carry no id of their own (agent adapters,
+ * bridges): each start gets a fresh invocationId, and each result takes the
+ * oldest open id for the same skill name.
+ */
+function createInvocationTracker() {
+  const open = new Map<string, string[]>();
+  return {
+    start(skillName: string): string {
+      const id = uuidv4();
+      const queue = open.get(skillName) ?? [];
+      queue.push(id);
+      open.set(skillName, queue);
+      return id;
+    },
+    finish(skillName: string): string | undefined {
+      return open.get(skillName)?.shift();
+    },
+  };
+}
+
 function send(ws: WebSocket, message: ServerMessage): void {
   const type = message.type;
   if (ws.readyState === WebSocket.OPEN) {